@@ -0,0 +1,5 @@
+//! the `ndarray_conv_*` functions of the ffi module of ndarray-conv, exported from a
+//! shared library: `cargo build --release -p ndarray-conv-ffi` builds
+//! `libndarray_conv_ffi.so` (`ndarray_conv_ffi.dll` on windows).
+
+pub use ndarray_conv::ffi::*;
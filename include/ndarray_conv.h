@@ -0,0 +1,66 @@
+/* C interface of ndarray-conv, built as a shared library by the ndarray-conv-ffi crate:
+ * `cargo build --release -p ndarray-conv-ffi` gives libndarray_conv_ffi.so
+ * (ndarray_conv_ffi.dll, libndarray_conv_ffi.dylib). */
+
+#ifndef NDARRAY_CONV_H
+#define NDARRAY_CONV_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* status codes */
+#define NDARRAY_CONV_OK 0
+#define NDARRAY_CONV_NULL_POINTER 1
+#define NDARRAY_CONV_INVALID_ARGUMENT 2
+#define NDARRAY_CONV_INVALID_SHAPE 3
+#define NDARRAY_CONV_OUTPUT_TOO_SMALL 4
+#define NDARRAY_CONV_PANIC 5
+
+/* modes */
+#define NDARRAY_CONV_MODE_FULL 0
+#define NDARRAY_CONV_MODE_SAME 1
+#define NDARRAY_CONV_MODE_VALID 2
+#define NDARRAY_CONV_MODE_CIRCULAR 3
+/* causal along the last axis */
+#define NDARRAY_CONV_MODE_CAUSAL 4
+
+/* paddings, padding_value is the constant of NDARRAY_CONV_PADDING_CONST */
+#define NDARRAY_CONV_PADDING_ZEROS 0
+#define NDARRAY_CONV_PADDING_CONST 1
+#define NDARRAY_CONV_PADDING_REFLECT 2
+#define NDARRAY_CONV_PADDING_SYMMETRIC 3
+#define NDARRAY_CONV_PADDING_REPLICATE 4
+#define NDARRAY_CONV_PADDING_CIRCULAR 5
+
+const char *ndarray_conv_status_message(int32_t status);
+
+/*
+ * cross-correlation of input with kernel.
+ * shapes hold N elements, strides N elements in units of elements or NULL for row major.
+ * output_shape receives the output shape, output the output in row major order.
+ * a NULL output only queries the output shape.
+ */
+#define NDARRAY_CONV_DECLARE(name, T)                                                          \
+    int32_t name(const T *input, const size_t *input_shape, const ptrdiff_t *input_strides,    \
+                 const T *kernel, const size_t *kernel_shape, const ptrdiff_t *kernel_strides, \
+                 int32_t mode, int32_t padding, T padding_value, T *output, size_t output_len, \
+                 size_t *output_shape);
+
+NDARRAY_CONV_DECLARE(ndarray_conv_f32_1d, float)
+NDARRAY_CONV_DECLARE(ndarray_conv_f32_2d, float)
+NDARRAY_CONV_DECLARE(ndarray_conv_f32_3d, float)
+NDARRAY_CONV_DECLARE(ndarray_conv_f64_1d, double)
+NDARRAY_CONV_DECLARE(ndarray_conv_f64_2d, double)
+NDARRAY_CONV_DECLARE(ndarray_conv_f64_3d, double)
+
+#undef NDARRAY_CONV_DECLARE
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,132 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, Array1, ArrayBase, ArrayView, Data, Dim, IntoDimension, Ix, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem, Zip,
+};
+use num::traits::NumAssign;
+
+use crate::{padding::PaddingExt, ConvExt, ConvMode, PaddingMode};
+
+pub trait ConvBankExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+{
+    /// `conv` with every kernel of a filter bank, one output per kernel.
+    /// the input is padded once for the largest padding and the kernels run in parallel.
+    #[allow(clippy::type_complexity)]
+    fn conv_bank(
+        &self,
+        kernels: &[ArrayView<T, Dim<[Ix; N]>>],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Vec<Array<T, Dim<[Ix; N]>>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> ConvBankExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + Debug + Send + Sync + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn conv_bank(
+        &self,
+        kernels: &[ArrayView<T, Dim<[Ix; N]>>],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Vec<Array<T, Dim<[Ix; N]>>>, crate::Error<N>> {
+        let input_dim: [usize; N] = std::array::from_fn(|i| self.shape()[i]);
+
+        // unfolding validates every kernel before any work is done
+        let cms = kernels
+            .iter()
+            .map(|kernel| {
+                conv_mode.unfold_with_dim(std::array::from_fn(|i| kernel.shape()[i]), input_dim)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let padding: [[usize; 2]; N] = std::array::from_fn(|i| {
+            std::array::from_fn(|side| cms.iter().map(|cm| cm.padding[i][side]).max().unwrap_or(0))
+        });
+        let padded = self.padding(conv_mode.padding_mode(padding_mode), padding);
+
+        let jobs = Array1::from_iter(kernels.iter().zip(cms));
+        let outputs = Zip::from(&jobs).par_map_collect(|(kernel, cm)| {
+            // the padding of each kernel is a window of the shared padded input
+            let view = padded.slice(unsafe {
+                SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
+                    start: (padding[i][0] - cm.padding[i][0]) as isize,
+                    end: Some((padding[i][0] + input_dim[i] + cm.padding[i][1]) as isize),
+                    step: 1,
+                }))
+                .unwrap()
+            });
+
+            view.conv(
+                *kernel,
+                ConvMode::Custom {
+                    padding: [0; N],
+                    strides: cm.strides,
+                },
+                PaddingMode::Zeros,
+            )
+        });
+
+        outputs.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::BorderType;
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((7, 9), |(i, j)| ((i * 9 + j) % 11) as i32 - 5);
+        let kernels = [
+            array![[1, 0, -1], [2, 0, -2], [1, 0, -1]],
+            array![[1, 2], [-3, 4]],
+            array![[2, 1, 0, -1, -2]],
+        ];
+        let views = kernels.iter().map(|k| k.view()).collect::<Vec<_>>();
+
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Full, PaddingMode::Zeros),
+            (ConvMode::Same, PaddingMode::Reflect),
+            (ConvMode::Valid, PaddingMode::Replicate),
+            (
+                ConvMode::Custom {
+                    padding: [1, 2],
+                    strides: [2, 3],
+                },
+                PaddingMode::Custom([BorderType::Circular, BorderType::Const(3)]),
+            ),
+        ] {
+            let outputs = arr.conv_bank(&views, conv_mode, padding_mode).unwrap();
+            assert_eq!(outputs.len(), kernels.len());
+            for (output, kernel) in outputs.iter().zip(kernels.iter()) {
+                assert_eq!(output, arr.conv(kernel, conv_mode, padding_mode).unwrap());
+            }
+        }
+
+        assert!(arr
+            .conv_bank(&[], ConvMode::Same, PaddingMode::Zeros)
+            .unwrap()
+            .is_empty());
+        let large = Array2::<i32>::ones((8, 1));
+        assert!(arr
+            .conv_bank(
+                &[views[0], large.view()],
+                ConvMode::Valid,
+                PaddingMode::Zeros
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem, Zip,
+};
+use num::traits::{Float, NumAssign};
+
+use crate::{
+    padding::PaddingExt, separable::SeparableConvExt, window::Windows, ConvMode, PaddingMode,
+};
+
+// kernel radius in sigmas, as in the gaussian module
+const TRUNCATE: f64 = 4.0;
+
+pub trait BilateralExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// edge-preserving smoothing: every element becomes the mean of its window weighted by a
+    /// gaussian of the distance (`sigma_spatial`, in elements, truncated at 4 sigmas) times
+    /// a gaussian of the difference of values (`sigma_range`). the output has the input's
+    /// shape, padded elements count like the others.
+    fn bilateral_filter(
+        &self,
+        sigma_spatial: f64,
+        sigma_range: f64,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// `bilateral_filter` approximated from `levels` gaussian blurs spread over the range of
+    /// the input (Durand & Dorsey), each element interpolated between the two levels around
+    /// its value. costs two separable blurs per level whatever `sigma_spatial`, so it pays off
+    /// for large windows; the error shrinks as the levels get closer than `sigma_range`.
+    fn bilateral_filter_approx(
+        &self,
+        sigma_spatial: f64,
+        sigma_range: f64,
+        levels: usize,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> BilateralExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn bilateral_filter(
+        &self,
+        sigma_spatial: f64,
+        sigma_range: f64,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        check_sigmas(sigma_spatial, sigma_range)?;
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+
+        let spatial = crate::kernels::gaussian::<T>(sigma_spatial, TRUNCATE).unwrap();
+        let window_shape = [spatial.len(); N];
+
+        let conv_mode = ConvMode::Same;
+        let cm =
+            conv_mode.unfold_with_dim(window_shape, std::array::from_fn(|i| self.shape()[i]))?;
+        let windows = Windows::new(self, window_shape, &cm, padding_mode)
+            .ok_or(crate::Error::MismatchShape(conv_mode, window_shape))?;
+
+        // (offset, spatial weight) of every tap, the center one in the middle
+        let taps = windows
+            .offsets(window_shape, [1; N])
+            .into_iter()
+            .zip(ndarray::indices(window_shape))
+            .map(|(offset, index)| {
+                let index = index.into_dimension();
+                (offset, (0..N).fold(T::one(), |w, i| w * spatial[index[i]]))
+            })
+            .collect::<Vec<_>>();
+        let center = taps[taps.len() / 2].0;
+        let range = T::from(-0.5 / (sigma_range * sigma_range)).unwrap();
+
+        Ok(windows.origins().map(|cur| {
+            let cur = cur as *const T;
+            let c = unsafe { *cur.offset(center) };
+            let (sum, norm) =
+                taps.iter()
+                    .fold((T::zero(), T::zero()), |(sum, norm), &(offset, w)| {
+                        let v = unsafe { *cur.offset(offset) };
+                        let w = w * (range * (v - c) * (v - c)).exp();
+                        (sum + w * v, norm + w)
+                    });
+            sum / norm
+        }))
+    }
+
+    fn bilateral_filter_approx(
+        &self,
+        sigma_spatial: f64,
+        sigma_range: f64,
+        levels: usize,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        check_sigmas(sigma_spatial, sigma_range)?;
+        if levels < 2 {
+            return Err(crate::Error::InvalidParameter(format!(
+                "at least 2 levels are needed, got {levels}"
+            )));
+        }
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+
+        // padded once so the weights of the padded elements are the range weights of their
+        // values, as in bilateral_filter, then every blur keeps its valid part
+        let spatial = crate::kernels::gaussian::<T>(sigma_spatial, TRUNCATE).unwrap();
+        let padded = self.padding(padding_mode, [[spatial.len() / 2; 2]; N]);
+
+        let (min, max) = padded
+            .iter()
+            .fold((T::infinity(), T::neg_infinity()), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            });
+        if min == max {
+            return Ok(self.to_owned());
+        }
+
+        let step = (max - min) / T::from(levels - 1).unwrap();
+        let range = T::from(-0.5 / (sigma_range * sigma_range)).unwrap();
+        let blur = |arr: &Array<T, Dim<[Ix; N]>>| {
+            arr.conv_separable(
+                std::array::from_fn(|_| spatial.view()),
+                ConvMode::Valid,
+                PaddingMode::Zeros,
+            )
+        };
+
+        // every level is weighted by a hat of one step around it, the hats summing to one
+        let mut output = Array::zeros(self.raw_dim());
+        for k in 0..levels {
+            let level = min + T::from(k).unwrap() * step;
+            let weights = padded.mapv(|v| (range * (v - level) * (v - level)).exp());
+            let norm = blur(&weights)?;
+            let sum = blur(&(&weights * &padded))?;
+
+            Zip::from(&mut output)
+                .and(self)
+                .and(&sum)
+                .and(&norm)
+                .for_each(|out, &v, &sum, &norm| {
+                    let hat = T::one() - ((v - level) / step).abs();
+                    if hat > T::zero() {
+                        *out += hat * if norm > T::zero() { sum / norm } else { v };
+                    }
+                });
+        }
+
+        Ok(output)
+    }
+}
+
+fn check_sigmas<const N: usize>(
+    sigma_spatial: f64,
+    sigma_range: f64,
+) -> Result<(), crate::Error<N>> {
+    if sigma_spatial.is_finite()
+        && sigma_spatial >= 0.
+        && sigma_range.is_finite()
+        && sigma_range > 0.
+    {
+        Ok(())
+    } else {
+        Err(crate::Error::InvalidParameter(format!(
+            "sigmas must be finite, spatial non-negative and range positive, got {sigma_spatial} and {sigma_range}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{Array2, Array3};
+
+    use super::*;
+    use crate::{reference::assert_close, GaussianExt};
+
+    #[test]
+    fn bilateral_filter() {
+        let arr = Array2::from_shape_fn((9, 11), |(i, j)| ((i * 11 + j) * 37 % 23) as f64 / 4.);
+
+        // brute force over the zero padded input
+        let (sigma_spatial, sigma_range) = (1., 2.);
+        let spatial = crate::kernels::gaussian::<f64>(sigma_spatial, TRUNCATE).unwrap();
+        let r = spatial.len() as isize / 2;
+        let expected = Array2::from_shape_fn(arr.raw_dim(), |(i, j)| {
+            let c = arr[[i, j]];
+            let (mut sum, mut norm) = (0., 0.);
+            for di in -r..=r {
+                for dj in -r..=r {
+                    let (y, x) = (i as isize + di, j as isize + dj);
+                    let v = if (0..9).contains(&y) && (0..11).contains(&x) {
+                        arr[[y as usize, x as usize]]
+                    } else {
+                        0.
+                    };
+                    let w = spatial[(di + r) as usize]
+                        * spatial[(dj + r) as usize]
+                        * (-0.5 * ((v - c) / sigma_range).powi(2)).exp();
+                    sum += w * v;
+                    norm += w;
+                }
+            }
+            sum / norm
+        });
+        let res = arr
+            .bilateral_filter(sigma_spatial, sigma_range, PaddingMode::Zeros)
+            .unwrap();
+        assert_close(&res, &expected, 1e-12);
+
+        // a wide range is a gaussian blur
+        let res = arr
+            .bilateral_filter(1.5, 1e9, PaddingMode::Reflect)
+            .unwrap();
+        let blurred = arr.gaussian_blur([1.5; 2], PaddingMode::Reflect).unwrap();
+        assert_close(&res, &blurred, 1e-9);
+
+        // a step survives, the flat sides stay flat
+        let step = Array3::from_shape_fn((5, 6, 12), |(_, _, k)| if k < 6 { 0. } else { 10. });
+        for res in [
+            step.bilateral_filter(2., 1., PaddingMode::Replicate)
+                .unwrap(),
+            step.bilateral_filter_approx(2., 1., 8, PaddingMode::Replicate)
+                .unwrap(),
+        ] {
+            assert_close(&res, &step, 1e-6);
+        }
+
+        assert!(arr.bilateral_filter(1., 0., PaddingMode::Zeros).is_err());
+        assert!(arr.bilateral_filter(-1., 1., PaddingMode::Zeros).is_err());
+        assert!(arr
+            .bilateral_filter_approx(1., 1., 1, PaddingMode::Zeros)
+            .is_err());
+    }
+
+    #[test]
+    fn approx_close_to_exact() {
+        let arr = Array2::from_shape_fn((20, 24), |(i, j)| {
+            (i as f64 / 3.).sin() * 4. + (j as f64 / 5.).cos() * 3. + if j < 12 { 0. } else { 8. }
+        });
+        let exact = arr.bilateral_filter(2., 2., PaddingMode::Reflect).unwrap();
+        let approx = arr
+            .bilateral_filter_approx(2., 2., 32, PaddingMode::Reflect)
+            .unwrap();
+        assert_close(&approx, &exact, 0.05);
+
+        // constants outside the input, far from its values at the corners
+        let arr = Array2::from_shape_fn((24, 24), |(i, j)| ((i * 24 + j) * 37 % 29) as f64 / 28.);
+        for padding_mode in [PaddingMode::Zeros, PaddingMode::Const(5.)] {
+            let exact = arr.bilateral_filter(2., 0.3, padding_mode).unwrap();
+            let approx = arr
+                .bilateral_filter_approx(2., 0.3, 64, padding_mode)
+                .unwrap();
+            assert_close(&approx, &exact, 0.05);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use ndarray::{Array, ArrayBase, Data, Dim, Dimension, IntoDimension, Ix, RawData};
+
+use crate::{
+    dilation::{dilated, IntoKernelWithDilation},
+    ConvMode,
+};
+
+pub trait BitConvExt<'a, S, SK, const N: usize>
+where
+    S: RawData,
+    SK: RawData,
+{
+    /// conv of boolean arrays, every output counts the `true` elements
+    /// overlapping the kernel. the input is padded with `false`.
+    ///
+    /// rows along the last axis are packed into `u64` words, so the inner loop
+    /// handles 64 kernel taps per popcount.
+    fn conv(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+    ) -> Result<Array<u32, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<'a, S, SK, const N: usize> BitConvExt<'a, S, SK, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    S: Data<Elem = bool> + 'a,
+    SK: Data<Elem = bool> + 'a,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    fn conv(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+    ) -> Result<Array<u32, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+
+        let data_raw_dim = self.raw_dim();
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(data_raw_dim));
+        }
+
+        let kernel_raw_dim = kwd.kernel.raw_dim();
+        if kwd.kernel.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(kernel_raw_dim));
+        }
+
+        let kernel_raw_dim_with_dilation =
+            dilated(std::array::from_fn(|i| kernel_raw_dim[i]), kwd.dilation)?;
+
+        let cm = conv_mode.unfold(&kwd, std::array::from_fn(|i| data_raw_dim[i]))?;
+
+        let pds_raw_dim: [usize; N] =
+            std::array::from_fn(|i| data_raw_dim[i] + cm.padding[i][0] + cm.padding[i][1]);
+        if !(0..N).all(|i| kernel_raw_dim_with_dilation[i] <= pds_raw_dim[i]) {
+            return Err(crate::Error::MismatchShape(
+                conv_mode,
+                kernel_raw_dim_with_dilation,
+            ));
+        }
+
+        // rows of the zero padded input, each one followed by a spare word
+        // so a 64 bits window can always be read from two words
+        let row_words = pds_raw_dim[N - 1] / 64 + 2;
+        let row_strides = row_strides(&pds_raw_dim);
+        let rows = pds_raw_dim[..N - 1].iter().product::<usize>();
+
+        let mut bits = vec![0u64; rows * row_words];
+        self.indexed_iter().for_each(|(index, &v)| {
+            if v {
+                let index = index.into_dimension();
+                let row = (0..N - 1)
+                    .map(|i| (index[i] + cm.padding[i][0]) * row_strides[i])
+                    .sum::<usize>();
+                let col = index[N - 1] + cm.padding[N - 1][0];
+                bits[row * row_words + col / 64] |= 1 << (col % 64);
+            }
+        });
+
+        // kernel rows with at least one tap: (row offset, packed bits)
+        let kernel_words = (kernel_raw_dim_with_dilation[N - 1] - 1) / 64 + 1;
+        let mut kernel_rows: Vec<(usize, Vec<u64>)> = vec![];
+        kwd.kernel.indexed_iter().for_each(|(index, &v)| {
+            if v {
+                let index = index.into_dimension();
+                let row = (0..N - 1)
+                    .map(|i| index[i] * kwd.dilation[i] * row_strides[i])
+                    .sum::<usize>();
+                let col = index[N - 1] * kwd.dilation[N - 1];
+
+                let pos = match kernel_rows.iter().position(|(r, _)| *r == row) {
+                    Some(pos) => pos,
+                    None => {
+                        kernel_rows.push((row, vec![0; kernel_words]));
+                        kernel_rows.len() - 1
+                    }
+                };
+                kernel_rows[pos].1[col / 64] |= 1 << (col % 64);
+            }
+        });
+
+        let output_shape: [usize; N] = std::array::from_fn(|i| {
+            (pds_raw_dim[i] - kernel_raw_dim_with_dilation[i]) / cm.strides[i] + 1
+        });
+
+        Ok(Array::from_shape_fn(output_shape, |index| {
+            let index = index.into_dimension();
+            let row = (0..N - 1)
+                .map(|i| index[i] * cm.strides[i] * row_strides[i])
+                .sum::<usize>();
+            let col = index[N - 1] * cm.strides[N - 1];
+
+            kernel_rows
+                .iter()
+                .map(|(kernel_row, kernel_bits)| {
+                    let input = &bits[(row + kernel_row) * row_words..][..row_words];
+                    kernel_bits
+                        .iter()
+                        .enumerate()
+                        .map(|(w, k)| (read_word(input, col + w * 64) & k).count_ones())
+                        .sum::<u32>()
+                })
+                .sum()
+        }))
+    }
+}
+
+// row major strides of the rows (every axis except the last one)
+fn row_strides<const N: usize>(shape: &[usize; N]) -> [usize; N] {
+    let mut strides = [1; N];
+    for i in (0..N.saturating_sub(2)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
+
+// 64 bits starting at bit `start`, bits past the row are zero
+#[inline]
+fn read_word(bits: &[u64], start: usize) -> u64 {
+    let (word, shift) = (start / 64, start % 64);
+    let low = bits.get(word).copied().unwrap_or(0);
+    if shift == 0 {
+        low
+    } else {
+        (low >> shift) | (bits.get(word + 1).copied().unwrap_or(0) << (64 - shift))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{Array1, Array3};
+
+    use super::*;
+    use crate::{dilation::WithDilation, ConvExt, PaddingMode};
+
+    #[test]
+    fn same_as_u32_conv() {
+        let arr = Array3::from_shape_fn((4, 9, 150), |(i, j, k)| (i * 7 + j * 13 + k * 29) % 5 < 2);
+        let kernel = Array3::from_shape_fn((2, 3, 70), |(i, j, k)| (i + j * 3 + k) % 3 == 0);
+
+        let arr_u32 = arr.map(|&v| v as u32);
+        let kernel_u32 = kernel.map(|&v| v as u32);
+
+        for conv_mode in [
+            ConvMode::Full,
+            ConvMode::Same,
+            ConvMode::Valid,
+            ConvMode::Custom {
+                padding: [1, 2, 3],
+                strides: [2, 1, 3],
+            },
+        ] {
+            assert_eq!(
+                arr.conv(&kernel, conv_mode).unwrap(),
+                arr_u32
+                    .conv(&kernel_u32, conv_mode, PaddingMode::Zeros)
+                    .unwrap()
+            );
+            assert_eq!(
+                arr.conv(kernel.with_dilation([1, 2, 2]), conv_mode)
+                    .unwrap(),
+                arr_u32
+                    .conv(
+                        kernel_u32.with_dilation([1, 2, 2]),
+                        conv_mode,
+                        PaddingMode::Zeros
+                    )
+                    .unwrap()
+            );
+        }
+
+        let arr = Array1::from_shape_fn(1000, |i| i % 3 == 0);
+        let kernel = Array1::from_elem(129, true);
+        assert_eq!(
+            arr.conv(&kernel, ConvMode::Valid).unwrap(),
+            Array1::from_elem(872, 43)
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use alloc::vec::Vec;
+
+use ndarray::{Array, Dimension};
+
+// the elements of an array in standard layout or in its transpose (column major), in
+// memory order. the buffer is moved when the elements fill it, else they are copied out.
+pub(crate) fn into_memory_order<T: Copy, D: Dimension>(array: Array<T, D>) -> Vec<T> {
+    assert!(array.is_standard_layout() || array.t().is_standard_layout());
+    let len = array.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let first = array.as_ptr();
+    let vec = array.into_raw_vec();
+    // SAFETY: the strides are positive, so `first` is the lowest address of the elements,
+    // inside the buffer the array owned and that `vec` now holds: both pointers are in one
+    // allocation and `first` is not before its start.
+    let offset = unsafe { first.offset_from(vec.as_ptr()) } as usize;
+    if offset == 0 && vec.len() == len {
+        vec
+    } else {
+        vec[offset..offset + len].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array2};
+
+    #[test]
+    fn into_memory_order() {
+        let arr = Array2::from_shape_fn((3, 4), |(i, j)| i * 4 + j);
+        assert_eq!(
+            super::into_memory_order(arr.clone()),
+            (0..12).collect::<Vec<_>>()
+        );
+        // the rows left by the slice start inside the buffer
+        assert_eq!(
+            super::into_memory_order(arr.clone().slice_move(s![1.., ..])),
+            (4..12).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            super::into_memory_order(arr.reversed_axes()),
+            (0..12).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, ArrayView, Data, Dim, Dimension, IntoDimension, Ix, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{
+    dilation::{dilated, IntoDilation, WithDilation},
+    plan::{factorize, im2col},
+    Algorithm, ConvExt, ConvFFTExt, ConvMode, ConvPlan, PaddingMode, SeparableConvExt,
+};
+
+/// a conv set up option by option, e.g.
+/// `Conv::with(&kernel).dilation(2).stride([2, 1]).algo(Algorithm::Fft).run(&input)`.
+/// the mode defaults to `ConvMode::Same`, the padding to zeros and the algorithm to direct.
+#[derive(Debug, Clone, Copy)]
+pub struct Conv<'k, T, const N: usize>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: Dimension,
+{
+    kernel: ArrayView<'k, T, Dim<[Ix; N]>>,
+    dilation: [usize; N],
+    strides: Option<[usize; N]>,
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+    algorithm: Algorithm,
+}
+
+impl<'k, T, const N: usize> Conv<'k, T, N>
+where
+    T: ConvElement,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    pub fn with<S: Data<Elem = T>>(kernel: &'k ArrayBase<S, Dim<[Ix; N]>>) -> Self {
+        Self {
+            kernel: kernel.view(),
+            dilation: [1; N],
+            strides: None,
+            conv_mode: ConvMode::Same,
+            padding_mode: PaddingMode::Zeros,
+            algorithm: Algorithm::Direct,
+        }
+    }
+
+    pub fn dilation(mut self, dilation: impl IntoDilation<N>) -> Self {
+        self.dilation = dilation.into_dilation();
+        self
+    }
+
+    /// strides replacing the ones of the mode, the padding of the mode is kept.
+    pub fn stride(mut self, strides: impl IntoDilation<N>) -> Self {
+        self.strides = Some(strides.into_dilation());
+        self
+    }
+
+    pub fn mode(mut self, conv_mode: ConvMode<N>) -> Self {
+        self.conv_mode = conv_mode;
+        self
+    }
+
+    pub fn padding(mut self, padding_mode: PaddingMode<N, T>) -> Self {
+        self.padding_mode = padding_mode;
+        self
+    }
+
+    /// `Algorithm::Fft`, `Algorithm::Separable` and `Algorithm::Im2col` need float
+    /// elements, separable also a rank one kernel without dilation.
+    pub fn algo(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// the shapes and the mode, strides included, of the conv of an input of `input_shape`.
+    pub fn plan(&self, input_shape: [usize; N]) -> Result<ConvPlan<N>, crate::Error<N>> {
+        let plan = ConvPlan::new(
+            input_shape,
+            std::array::from_fn(|i| self.kernel.shape()[i]),
+            self.dilation,
+            self.conv_mode(input_shape)?,
+        );
+        // validates the shapes
+        plan.output_shape()?;
+
+        Ok(plan)
+    }
+
+    pub fn run<S: Data<Elem = T>>(
+        &self,
+        input: &ArrayBase<S, Dim<[Ix; N]>>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let input_shape = std::array::from_fn(|i| input.shape()[i]);
+        T::conv_with(
+            self.algorithm,
+            input.view(),
+            self.kernel,
+            self.dilation,
+            self.conv_mode(input_shape)?,
+            self.conv_mode.padding_mode(self.padding_mode),
+        )
+    }
+
+    // the mode with the strides set, as explicit padding
+    fn conv_mode(&self, input_shape: [usize; N]) -> Result<ConvMode<N>, crate::Error<N>> {
+        let Some(strides) = self.strides else {
+            return Ok(self.conv_mode);
+        };
+
+        if let Some(axis) = strides.iter().position(|&s| s == 0) {
+            return Err(crate::Error::ZeroStride(axis));
+        }
+
+        let kernel_dim = dilated(
+            std::array::from_fn(|i| self.kernel.shape()[i]),
+            self.dilation,
+        )?;
+        let cm = self.conv_mode.unfold_with_dim(kernel_dim, input_shape)?;
+
+        Ok(ConvMode::Explicit {
+            padding: cm.padding,
+            strides,
+        })
+    }
+}
+
+/// element types a `Conv` runs on: floats take every `Algorithm`, integers the direct one.
+pub trait ConvElement: NumAssign + Copy + Debug {
+    fn conv_with<const N: usize>(
+        algorithm: Algorithm,
+        input: ArrayView<Self, Dim<[Ix; N]>>,
+        kernel: ArrayView<Self, Dim<[Ix; N]>>,
+        dilation: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, Self>,
+    ) -> Result<Array<Self, Dim<[Ix; N]>>, crate::Error<N>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>;
+}
+
+macro_rules! conv_element {
+    (float: $($t:ty),*) => {
+        $(
+            impl ConvElement for $t {
+                fn conv_with<const N: usize>(
+                    algorithm: Algorithm,
+                    input: ArrayView<$t, Dim<[Ix; N]>>,
+                    kernel: ArrayView<$t, Dim<[Ix; N]>>,
+                    dilation: [usize; N],
+                    conv_mode: ConvMode<N>,
+                    padding_mode: PaddingMode<N, $t>,
+                ) -> Result<Array<$t, Dim<[Ix; N]>>, crate::Error<N>>
+                where
+                    Dim<[Ix; N]>: RemoveAxis,
+                    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+                    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+                        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+                {
+                    match algorithm {
+                        Algorithm::Direct => {
+                            input.conv(kernel.with_dilation(dilation), conv_mode, padding_mode)
+                        }
+                        Algorithm::Fft => {
+                            input.conv_fft(kernel.with_dilation(dilation), conv_mode, padding_mode)
+                        }
+                        Algorithm::Separable => {
+                            if dilation != [1; N] {
+                                return Err(crate::Error::InvalidParameter(
+                                    "the separable algorithm doesn't take a dilation".to_string(),
+                                ));
+                            }
+                            let kernels = factorize(&kernel).ok_or_else(|| {
+                                crate::Error::InvalidParameter(
+                                    "the separable algorithm needs a rank one kernel".to_string(),
+                                )
+                            })?;
+                            let views = std::array::from_fn(|i| kernels[i].view());
+                            input.conv_separable(views, conv_mode, padding_mode)
+                        }
+                        Algorithm::Im2col => {
+                            im2col(&input, &kernel, dilation, conv_mode, padding_mode)
+                        }
+                    }
+                }
+            }
+        )*
+    };
+    (int: $($t:ty),*) => {
+        $(
+            impl ConvElement for $t {
+                fn conv_with<const N: usize>(
+                    algorithm: Algorithm,
+                    input: ArrayView<$t, Dim<[Ix; N]>>,
+                    kernel: ArrayView<$t, Dim<[Ix; N]>>,
+                    dilation: [usize; N],
+                    conv_mode: ConvMode<N>,
+                    padding_mode: PaddingMode<N, $t>,
+                ) -> Result<Array<$t, Dim<[Ix; N]>>, crate::Error<N>>
+                where
+                    Dim<[Ix; N]>: RemoveAxis,
+                    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+                    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+                        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+                {
+                    match algorithm {
+                        Algorithm::Direct => {
+                            input.conv(kernel.with_dilation(dilation), conv_mode, padding_mode)
+                        }
+                        _ => Err(crate::Error::InvalidParameter(format!(
+                            "the {} algorithm needs float elements, not {}",
+                            algorithm,
+                            stringify!($t)
+                        ))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+conv_element!(float: f32, f64);
+conv_element!(int: i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((8, 9), |(i, j)| ((i * 9 + j) % 7) as f64 - 3.);
+        let separable = array![[1., 2., 1.], [2., 4., 2.]];
+
+        let conv = Conv::with(&separable)
+            .mode(ConvMode::Full)
+            .padding(PaddingMode::Replicate)
+            .stride([2, 3]);
+        let expected = arr
+            .conv(
+                &separable,
+                ConvMode::Custom {
+                    padding: [1, 2],
+                    strides: [2, 3],
+                },
+                PaddingMode::Replicate,
+            )
+            .unwrap();
+        assert_eq!(
+            conv.plan([8, 9]).unwrap().output_shape().unwrap(),
+            [expected.nrows(), expected.ncols()]
+        );
+
+        for algorithm in [
+            Algorithm::Direct,
+            Algorithm::Separable,
+            Algorithm::Fft,
+            Algorithm::Im2col,
+        ] {
+            assert_close(&conv.algo(algorithm).run(&arr).unwrap(), &expected, 1e-9);
+        }
+
+        let dilated = Conv::with(&separable)
+            .dilation([1, 2])
+            .mode(ConvMode::Valid);
+        assert_eq!(
+            dilated.run(&arr).unwrap(),
+            arr.conv(
+                separable.with_dilation([1, 2]),
+                ConvMode::Valid,
+                PaddingMode::Zeros
+            )
+            .unwrap()
+        );
+        assert!(dilated.algo(Algorithm::Separable).run(&arr).is_err());
+        assert_close(
+            &dilated.algo(Algorithm::Im2col).run(&arr).unwrap(),
+            &dilated.run(&arr).unwrap(),
+            1e-9,
+        );
+
+        // integers run direct only
+        let arr = arr.mapv(|v| v as i32);
+        let kernel = separable.mapv(|v| v as i32);
+        assert_eq!(
+            Conv::with(&kernel).run(&arr).unwrap(),
+            arr.conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap()
+        );
+        assert!(Conv::with(&kernel).algo(Algorithm::Fft).run(&arr).is_err());
+        assert!(Conv::with(&kernel).stride(0).plan([8, 9]).is_err());
+    }
+}
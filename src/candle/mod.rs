@@ -0,0 +1,205 @@
+//! conv of candle tensors. tensors on the cpu are read in place through their layout,
+//! tensors on other devices are copied to the cpu and the output back to their device.
+//!
+//! the orphan rule rules out `From` between `Tensor` and `Array`, the conversions
+//! are `to_array` and `from_array`.
+
+use std::fmt::Debug;
+
+use candle_core::{Device, Storage, Tensor, WithDType};
+use ndarray::{
+    Array, ArrayView, Dim, IntoDimension, Ix, RemoveAxis, ShapeBuilder, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+use rustfft::FftNum;
+
+use crate::{ConvExt, ConvFFTExt, ConvMode, PaddingMode};
+
+pub trait TensorConvExt {
+    /// `conv` of a tensor of rank `N` and element type `T`, returning a tensor on the same device.
+    fn conv<T, const N: usize>(
+        &self,
+        kernel: &Tensor,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Tensor, crate::Error<N>>
+    where
+        T: WithDType + NumAssign + Debug,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>;
+
+    fn conv_fft<T, const N: usize>(
+        &self,
+        kernel: &Tensor,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Tensor, crate::Error<N>>
+    where
+        T: WithDType + FftNum + NumAssign,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>;
+}
+
+impl TensorConvExt for Tensor {
+    fn conv<T, const N: usize>(
+        &self,
+        kernel: &Tensor,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Tensor, crate::Error<N>>
+    where
+        T: WithDType + NumAssign + Debug,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+    {
+        let ret = with_view(self, |input| {
+            with_view(kernel, |kernel| {
+                input.conv(&kernel, conv_mode, padding_mode)
+            })
+        })???;
+        from_array(ret, self.device())
+    }
+
+    fn conv_fft<T, const N: usize>(
+        &self,
+        kernel: &Tensor,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Tensor, crate::Error<N>>
+    where
+        T: WithDType + FftNum + NumAssign,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+    {
+        let ret = with_view(self, |input| {
+            with_view(kernel, |kernel| {
+                input.conv_fft(&kernel, conv_mode, padding_mode)
+            })
+        })???;
+        from_array(ret, self.device())
+    }
+}
+
+/// copy of a tensor of rank `N` and element type `T` as an array.
+pub fn to_array<T: WithDType, const N: usize>(
+    tensor: &Tensor,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    with_view(tensor, |view| view.to_owned())
+}
+
+/// tensor of an array on `device`, the buffer of a standard layout array on the cpu is moved.
+pub fn from_array<T: WithDType, const N: usize>(
+    array: Array<T, Dim<[Ix; N]>>,
+    device: &Device,
+) -> Result<Tensor, crate::Error<N>>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    let shape = array.shape().to_vec();
+
+    let vec = if array.is_standard_layout() {
+        crate::buffer::into_memory_order(array)
+    } else {
+        array.iter().copied().collect()
+    };
+
+    Tensor::from_vec(vec, shape, device).map_err(candle_error)
+}
+
+// runs f on a view of the cpu storage of the tensor
+fn with_view<T: WithDType, R, const N: usize>(
+    tensor: &Tensor,
+    f: impl FnOnce(ArrayView<T, Dim<[Ix; N]>>) -> R,
+) -> Result<R, crate::Error<N>>
+where
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    if tensor.rank() != N {
+        return Err(crate::Error::InvalidParameter(format!(
+            "tensor of rank {} for a conv of {} dimensions",
+            tensor.rank(),
+            N
+        )));
+    }
+
+    if !tensor.device().is_cpu() {
+        let tensor = tensor.to_device(&Device::Cpu).map_err(candle_error)?;
+        return with_view(&tensor, f);
+    }
+
+    let (storage, layout) = tensor.storage_and_layout();
+    let Storage::Cpu(storage) = &*storage else {
+        unreachable!("the tensor is on the cpu")
+    };
+    let data = T::cpu_storage_as_slice(storage).map_err(candle_error)?;
+
+    let shape: [usize; N] = std::array::from_fn(|i| layout.dims()[i]);
+    let strides: [usize; N] = std::array::from_fn(|i| layout.stride()[i]);
+    let view = ArrayView::from_shape(shape.strides(strides), &data[layout.start_offset()..])
+        .map_err(|err| crate::Error::InvalidParameter(err.to_string()))?;
+
+    Ok(f(view))
+}
+
+fn candle_error<const N: usize>(err: candle_core::Error) -> crate::Error<N> {
+    crate::Error::InvalidParameter(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array2};
+
+    use super::*;
+
+    #[test]
+    fn layouts() {
+        let arr = Array2::from_shape_fn((6, 8), |(i, j)| (i * 8 + j) as f32 % 7. - 3.);
+        let kernel = Array2::from_shape_fn((2, 3), |(i, j)| (i * 3 + j) as f32 - 2.);
+
+        let tensor = from_array(arr.clone(), &Device::Cpu).unwrap();
+        let tensor_kernel = from_array(kernel.clone(), &Device::Cpu).unwrap();
+        assert_eq!(to_array::<f32, 2>(&tensor).unwrap(), arr);
+
+        // transposed and narrowed tensors are read through their layout
+        let view = tensor.t().unwrap().narrow(0, 2, 5).unwrap();
+        let ret = view
+            .conv(
+                &tensor_kernel,
+                ConvMode::<2>::Same,
+                PaddingMode::<2, f32>::Replicate,
+            )
+            .unwrap();
+        let expected = arr
+            .t()
+            .slice(s![2..7, ..])
+            .conv(&kernel, ConvMode::Same, PaddingMode::Replicate)
+            .unwrap();
+        assert_eq!(to_array::<f32, 2>(&ret).unwrap(), expected);
+
+        // element type and rank have to match the tensor
+        assert!(tensor
+            .conv(&tensor_kernel, ConvMode::Same, PaddingMode::<2, f64>::Zeros)
+            .is_err());
+        assert!(tensor
+            .conv(
+                &tensor_kernel,
+                ConvMode::<1>::Same,
+                PaddingMode::<1, f32>::Zeros
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use alloc::vec::Vec;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{
+    dilation::{dilated, IntoKernelWithDilation},
+    padding::PaddingExt,
+    BorderType, ConvMode, PaddingMode,
+};
+
+pub trait ConvClonedExt<'a, T, S, SK, const N: usize>
+where
+    T: NumAssign + Clone,
+    S: RawData,
+    SK: RawData,
+{
+    /// `conv` of elements that are `Clone` but not `Copy`, e.g. `num::BigInt` or
+    /// `num::BigRational`, for exact results. indexes the padded input instead of
+    /// walking raw pointers, so it's much slower than `conv`.
+    fn conv_cloned(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<'a, T, S, SK, const N: usize> ConvClonedExt<'a, T, S, SK, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Clone,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T> + 'a,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn conv_cloned(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+        if kwd.kernel.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(kwd.kernel.raw_dim()));
+        }
+
+        let kernel_dim = dilated(
+            core::array::from_fn(|i| kwd.kernel.shape()[i]),
+            kwd.dilation,
+        )?;
+        let cm = conv_mode.unfold(&kwd, core::array::from_fn(|i| self.shape()[i]))?;
+
+        // the padding runs on the indices of the elements, with the constants of the
+        // padding mode as negative indices into a table, so every mode pads as in `conv`
+        let mut elements: Vec<T> = self.iter().cloned().collect();
+        let len = elements.len() as isize;
+        let padding_mode =
+            conv_mode.padding_mode(index_padding(padding_mode, &mut elements, len as usize));
+        let padded = Array::from_shape_vec(self.raw_dim(), (0..len).collect::<Vec<_>>())
+            .unwrap()
+            .padding(padding_mode, cm.padding);
+        let element = |index: isize| {
+            if index < 0 {
+                &elements[(len - index - 1) as usize]
+            } else {
+                &elements[index as usize]
+            }
+        };
+
+        let taps: Vec<([usize; N], &T)> = kwd
+            .kernel
+            .indexed_iter()
+            .filter(|(_, k)| kwd.zero_taps || !k.is_zero())
+            .map(|(index, k)| {
+                let index = index.into_dimension();
+                (core::array::from_fn(|i| index[i] * kwd.dilation[i]), k)
+            })
+            .collect();
+
+        let output_shape: [usize; N] =
+            core::array::from_fn(|i| (padded.shape()[i] - kernel_dim[i]) / cm.strides[i] + 1);
+
+        Ok(Array::from_shape_fn(output_shape, |index| {
+            let index = index.into_dimension();
+            taps.iter().fold(T::zero(), |mut sum, (tap, k)| {
+                let at: [usize; N] = core::array::from_fn(|i| index[i] * cm.strides[i] + tap[i]);
+                sum += element(padded[at.into_dimension()]).clone() * (*k).clone();
+                sum
+            })
+        }))
+    }
+}
+
+// the padding mode on indices, its constants appended to `elements` after the `len`
+// input elements, the i-th one at index -(i + 1)
+fn index_padding<T: NumAssign + Clone, const N: usize>(
+    padding_mode: PaddingMode<N, T>,
+    elements: &mut Vec<T>,
+    len: usize,
+) -> PaddingMode<N, isize> {
+    let mut constant = |c: T| {
+        elements.push(c);
+        len as isize - elements.len() as isize
+    };
+    let mut border = |border: BorderType<T>| match border {
+        BorderType::Zeros => BorderType::Const(constant(T::zero())),
+        BorderType::Const(c) => BorderType::Const(constant(c)),
+        BorderType::Reflect => BorderType::Reflect,
+        BorderType::Symmetric => BorderType::Symmetric,
+        BorderType::Replicate => BorderType::Replicate,
+        BorderType::Circular => BorderType::Circular,
+    };
+
+    match padding_mode {
+        PaddingMode::Zeros => PaddingMode::Const(constant(T::zero())),
+        PaddingMode::Const(c) => PaddingMode::Const(constant(c)),
+        PaddingMode::Reflect => PaddingMode::Reflect,
+        PaddingMode::Symmetric => PaddingMode::Symmetric,
+        PaddingMode::Replicate => PaddingMode::Replicate,
+        PaddingMode::Circular => PaddingMode::Circular,
+        PaddingMode::Custom(borders) => PaddingMode::Custom(borders.map(border)),
+        PaddingMode::Explicit(borders) => {
+            PaddingMode::Explicit(borders.map(|[before, after]| [border(before), border(after)]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array1, Array2};
+    use num::{BigInt, BigRational};
+
+    use super::*;
+    use crate::{ConvExt, WithDilation};
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((5, 6), |(i, j)| (i * 6 + j) as i64 % 7 - 3);
+        let kernel = array![[2i64, 0, -1], [1, 3, 0]];
+        let big = |v: &i64| BigInt::from(*v);
+
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Same, PaddingMode::Zeros),
+            (ConvMode::Full, PaddingMode::Const(5)),
+            (ConvMode::Circular, PaddingMode::Zeros),
+            (
+                ConvMode::Custom {
+                    padding: [2, 3],
+                    strides: [2, 1],
+                },
+                PaddingMode::Explicit([
+                    [BorderType::Const(-4), BorderType::Reflect],
+                    [BorderType::Symmetric, BorderType::Const(7)],
+                ]),
+            ),
+            (
+                ConvMode::Full,
+                PaddingMode::Custom([BorderType::Zeros, BorderType::Replicate]),
+            ),
+        ] {
+            let big_padding = match padding_mode {
+                PaddingMode::Zeros => PaddingMode::Zeros,
+                PaddingMode::Const(c) => PaddingMode::Const(big(&c)),
+                PaddingMode::Custom(borders) => {
+                    PaddingMode::Custom(borders.map(|b| border(b, big)))
+                }
+                PaddingMode::Explicit(borders) => {
+                    PaddingMode::Explicit(borders.map(|b| b.map(|b| border(b, big))))
+                }
+                _ => unreachable!(),
+            };
+
+            assert_eq!(
+                arr.map(big)
+                    .conv_cloned(
+                        kernel.map(big).with_dilation([1, 2]),
+                        conv_mode,
+                        big_padding
+                    )
+                    .unwrap(),
+                arr.conv(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+                    .unwrap()
+                    .map(big)
+            );
+        }
+
+        // exact past the range of the primitive types
+        let huge = BigInt::from(10).pow(30);
+        assert_eq!(
+            array![huge.clone(), BigInt::from(1)]
+                .conv_cloned(&array![huge.clone()], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![BigInt::from(10).pow(60), huge]
+        );
+        let third = BigRational::new(1.into(), 3.into());
+        assert_eq!(
+            array![third.clone(), third.clone()]
+                .conv_cloned(
+                    &Array1::from_elem(2, BigRational::from_integer(3.into())),
+                    ConvMode::Full,
+                    PaddingMode::Const(third)
+                )
+                .unwrap(),
+            Array1::from_elem(3, BigRational::from_integer(2.into()))
+        );
+    }
+
+    fn border(border: BorderType<i64>, f: impl Fn(&i64) -> BigInt) -> BorderType<BigInt> {
+        match border {
+            BorderType::Const(c) => BorderType::Const(f(&c)),
+            BorderType::Zeros => BorderType::Zeros,
+            BorderType::Reflect => BorderType::Reflect,
+            BorderType::Symmetric => BorderType::Symmetric,
+            BorderType::Replicate => BorderType::Replicate,
+            BorderType::Circular => BorderType::Circular,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! differential checks against libtorch. random shapes, strides, dilations and
+//! paddings are run through `conv` and `tch` conv1d/2d/3d and compared.
+
+use ndarray::{Array, Dim, IntoDimension, Ix, RemoveAxis, SliceArg, SliceInfo, SliceInfoElem};
+use ndarray_rand::{
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    rand_distr::Uniform,
+    RandomExt,
+};
+
+use tch::Kind;
+
+use crate::{
+    dilation::WithDilation,
+    interop::tch::{from_tensor, to_tensor},
+    ConvExt, ConvMode, PaddingMode,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Case<const N: usize> {
+    pub input_shape: [usize; N],
+    pub kernel_shape: [usize; N],
+    pub dilation: [usize; N],
+    pub strides: [usize; N],
+    pub padding: [usize; N],
+    pub padding_mode: PaddingMode<N, f64>,
+}
+
+impl<const N: usize> Case<N> {
+    /// a random case torch accepts, e.g. the padding of reflect stays below the input size.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let mut input_shape: [usize; N] = std::array::from_fn(|_| rng.gen_range(1..=9));
+        let kernel_shape: [usize; N] = std::array::from_fn(|_| rng.gen_range(1..=4));
+        let dilation: [usize; N] = std::array::from_fn(|_| rng.gen_range(1..=3));
+        let strides = std::array::from_fn(|_| rng.gen_range(1..=3));
+        let padding: [usize; N] = std::array::from_fn(|i| rng.gen_range(0..input_shape[i]));
+        let padding_mode = match rng.gen_range(0..5) {
+            0 => PaddingMode::Zeros,
+            1 => PaddingMode::Const(rng.gen_range(-2..=2) as f64),
+            2 => PaddingMode::Reflect,
+            3 => PaddingMode::Replicate,
+            _ => PaddingMode::Circular,
+        };
+
+        // the input holds the dilated kernel even without padding
+        for i in 0..N {
+            input_shape[i] = input_shape[i].max((kernel_shape[i] - 1) * dilation[i] + 1);
+        }
+
+        Self {
+            input_shape,
+            kernel_shape,
+            dilation,
+            strides,
+            padding,
+            padding_mode,
+        }
+    }
+}
+
+/// run `cases` random cases of every dimension from `seed`, panics on the first mismatch.
+pub fn run(cases: usize, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..cases {
+        check(&Case::<1>::random(&mut rng), &mut rng);
+        check(&Case::<2>::random(&mut rng), &mut rng);
+        check(&Case::<3>::random(&mut rng), &mut rng);
+    }
+}
+
+/// conv and torch on random integer data of the case's shapes, which have to agree.
+/// returns false without checking when torch has no counterpart for the case, i.e.
+/// symmetric or custom padding or more than 3 dimensions.
+pub fn check<const N: usize>(case: &Case<N>, rng: &mut impl Rng) -> bool
+where
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let input =
+        Array::random_using(case.input_shape, Uniform::new_inclusive(-4, 4), rng).mapv(f64::from);
+    let kernel =
+        Array::random_using(case.kernel_shape, Uniform::new_inclusive(-4, 4), rng).mapv(f64::from);
+
+    let Some(expected) = torch(&input, &kernel, case) else {
+        return false;
+    };
+    let res = input
+        .conv(
+            kernel.with_dilation(case.dilation),
+            ConvMode::Custom {
+                padding: case.padding,
+                strides: case.strides,
+            },
+            case.padding_mode,
+        )
+        .unwrap();
+
+    crate::reference::assert_close(&res, &expected, 1e-9);
+    true
+}
+
+fn torch<const N: usize>(
+    input: &Array<f64, Dim<[Ix; N]>>,
+    kernel: &Array<f64, Dim<[Ix; N]>>,
+    case: &Case<N>,
+) -> Option<Array<f64, Dim<[Ix; N]>>>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let pad = match case.padding_mode {
+        PaddingMode::Zeros => None,
+        PaddingMode::Const(c) => Some(("constant", Some(c))),
+        PaddingMode::Reflect => Some(("reflect", None)),
+        PaddingMode::Replicate => Some(("replicate", None)),
+        PaddingMode::Circular => Some(("circular", None)),
+        _ => return None,
+    };
+    if N > 3 {
+        return None;
+    }
+
+    let mut x = to_tensor(input, Kind::Double);
+    let mut padding = case.padding.map(|p| p as i64).to_vec();
+    if let Some((mode, value)) = pad {
+        // torch lists the padding from the last axis
+        let pad = case
+            .padding
+            .iter()
+            .rev()
+            .flat_map(|&p| [p as i64; 2])
+            .collect::<Vec<_>>();
+        x = x.pad(pad, mode, value);
+        padding = vec![0; N];
+    }
+
+    let weight = to_tensor(kernel, Kind::Double);
+    let strides = case.strides.map(|s| s as i64).to_vec();
+    let dilation = case.dilation.map(|d| d as i64).to_vec();
+    let out = match N {
+        1 => x.conv1d(&weight, None::<tch::Tensor>, strides, padding, dilation, 1),
+        2 => x.conv2d(&weight, None::<tch::Tensor>, strides, padding, dilation, 1),
+        3 => x.conv3d(&weight, None::<tch::Tensor>, strides, padding, dilation, 1),
+        _ => unreachable!("torch has conv1d, conv2d and conv3d only"),
+    };
+
+    Some(from_tensor(&out).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_with_libtorch() {
+        run(200, 0);
+
+        // torch has no symmetric padding, the case is skipped
+        let mut rng = StdRng::seed_from_u64(0);
+        let case = Case {
+            padding_mode: PaddingMode::Symmetric,
+            ..Case::<2>::random(&mut rng)
+        };
+        assert!(!check(&case, &mut rng));
+    }
+}
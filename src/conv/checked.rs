@@ -0,0 +1,139 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use alloc::vec::Vec;
+
+use ndarray::{
+    Array, ArrayBase, ArrayViewMut, Data, Dim, IntoDimension, Ix, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use super::ExplicitConv;
+use crate::{dilation::KernelWithDilation, padding::PaddingExt, PaddingMode};
+
+const UNSET: u8 = 0;
+const OFF: u8 = 1;
+const ON: u8 = 2;
+
+static CHECKED: AtomicU8 = AtomicU8::new(UNSET);
+
+/// run `conv`, `conv_generic`, `conv_at`, `conv_roi`, `conv_tiles`, `conv_into` and
+/// `conv_with_progress` through a plain bounds checked loop over a padded copy instead of
+/// the raw pointer paths. the results are the same, a bad index panics instead of reading
+/// garbage. on by default in debug builds, off in release builds until set.
+/// the window-based filters (pooling, rank, morphology, bilateral, unfold) are not covered
+/// by the checked mode and always run their raw pointer loops.
+pub fn set_checked(checked: bool) {
+    CHECKED.store(if checked { ON } else { OFF }, Ordering::Relaxed);
+}
+
+pub(super) fn enabled() -> bool {
+    match CHECKED.load(Ordering::Relaxed) {
+        UNSET => cfg!(debug_assertions),
+        state => state == ON,
+    }
+}
+
+// conv over the padded input, every element read through an indexing that checks bounds
+pub(super) fn conv<T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    padding_mode: PaddingMode<N, T>,
+    ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+) where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let output = output(
+        data.padding(padding_mode, cm.padding),
+        kwd,
+        cm.strides,
+        [0; N],
+        |x, k| x * k,
+        |a, b| a + b,
+        T::zero(),
+    );
+
+    ret.indexed_iter_mut().for_each(|(index, out)| {
+        let index = index.into_dimension();
+        *out = output(core::array::from_fn(|i| index[i]));
+    });
+}
+
+// conv_generic over the padded input, with its own product and sum
+#[allow(clippy::too_many_arguments)]
+pub(super) fn generic<T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    padding_mode: PaddingMode<N, T>,
+    output_shape: [usize; N],
+    mul: impl Fn(T, T) -> T,
+    add: impl Fn(T, T) -> T,
+    identity: T,
+) -> Array<T, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let output = output(
+        data.padding(padding_mode, cm.padding),
+        kwd,
+        cm.strides,
+        [0; N],
+        mul,
+        add,
+        identity,
+    );
+
+    Array::from_shape_fn(output_shape, |index| {
+        let index = index.into_dimension();
+        output(core::array::from_fn(|i| index[i]))
+    })
+}
+
+// the output at an index, read from padded, the padded input under the outputs from start on
+pub(super) fn output<'d, T, SK, const N: usize>(
+    padded: Array<T, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    strides: [usize; N],
+    start: [usize; N],
+    mul: impl Fn(T, T) -> T + 'd,
+    add: impl Fn(T, T) -> T + 'd,
+    identity: T,
+) -> impl Fn([usize; N]) -> T + 'd
+where
+    T: NumAssign + Copy + 'd,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let taps: Vec<([usize; N], T)> = kwd
+        .kernel
+        .indexed_iter()
+        .filter(|(_, v)| kwd.zero_taps || **v != T::zero())
+        .map(|(k, &v)| {
+            let k = k.into_dimension();
+            (core::array::from_fn(|i| k[i] * kwd.dilation[i]), v)
+        })
+        .collect();
+
+    move |index| {
+        taps.iter().fold(identity, |acc, &(k, v)| {
+            let pos: [usize; N] =
+                core::array::from_fn(|i| (index[i] - start[i]) * strides[i] + k[i]);
+            add(acc, mul(padded[pos.into_dimension()], v))
+        })
+    }
+}
@@ -0,0 +1,16 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+/// keep every reduction in one fixed order, so outputs are bit identical from run to run
+/// whatever the thread count or the cpu: every output is summed tap by tap in kernel
+/// order, and a `Planner` runs the direct conv instead of the fastest measured algorithm.
+/// off by default.
+pub fn set_deterministic(deterministic: bool) {
+    DETERMINISTIC.store(deterministic, Ordering::Relaxed);
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn enabled() -> bool {
+    DETERMINISTIC.load(Ordering::Relaxed)
+}
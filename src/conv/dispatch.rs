@@ -0,0 +1,270 @@
+use core::any::TypeId;
+#[cfg(target_arch = "aarch64")]
+use core::arch::aarch64::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use ndarray::{ArrayView, ArrayViewMut, Axis, Dim, Ix, RemoveAxis};
+
+// f32 and f64 inner loops for the vector unit of the cpu running the program, picked at
+// runtime so a portable build still gets them. like the wasm loop, neighbouring outputs
+// of a row are summed at once, tap by tap in the scalar order and without fused
+// multiply-adds, so the result is the same as the scalar loop on every cpu.
+macro_rules! lanes {
+    ($name:ident, $feature:literal, $t:ty, $w:literal, $splat:ident, $load:ident, $store:ident, $add:ident, $mul:ident) => {
+        #[target_feature(enable = $feature)]
+        unsafe fn $name<T, const N: usize>(
+            origins: &ArrayView<T, Dim<[Ix; N]>>,
+            offset_list: &[(isize, T)],
+            ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+        ) where
+            Dim<[Ix; N]>: RemoveAxis,
+        {
+            // T is $t, see enabled
+            let taps = core::slice::from_raw_parts(
+                offset_list.as_ptr() as *const (isize, $t),
+                offset_list.len(),
+            );
+
+            let last = Axis(N - 1);
+            for (origins, mut out) in origins.lanes(last).into_iter().zip(ret.lanes_mut(last)) {
+                let cur = origins.as_ptr() as *const $t;
+                let stride = origins.strides()[0];
+                let p = out.as_mut_ptr() as *mut $t;
+                let len = out.len();
+
+                let mut j = 0;
+                while j + $w <= len {
+                    let base = cur.offset(j as isize * stride);
+                    let mut sum = $splat(0.);
+                    for &(offset, k) in taps {
+                        let x = base.offset(offset);
+                        let x = if stride == 1 {
+                            $load(x)
+                        } else {
+                            let mut lanes = [0.; $w];
+                            for (l, lane) in lanes.iter_mut().enumerate() {
+                                *lane = *x.offset(l as isize * stride);
+                            }
+                            $load(lanes.as_ptr())
+                        };
+                        sum = $add(sum, $mul(x, $splat(k)));
+                    }
+                    $store(p.add(j), sum);
+                    j += $w;
+                }
+
+                for j in j..len {
+                    let base = cur.offset(j as isize * stride);
+                    let mut sum = 0.;
+                    for &(offset, k) in taps {
+                        sum += *base.offset(offset) * k;
+                    }
+                    *p.add(j) = sum;
+                }
+            }
+        }
+    };
+}
+
+#[cfg(target_arch = "x86_64")]
+lanes!(
+    avx512_f32,
+    "avx512f",
+    f32,
+    16,
+    _mm512_set1_ps,
+    _mm512_loadu_ps,
+    _mm512_storeu_ps,
+    _mm512_add_ps,
+    _mm512_mul_ps
+);
+#[cfg(target_arch = "x86_64")]
+lanes!(
+    avx512_f64,
+    "avx512f",
+    f64,
+    8,
+    _mm512_set1_pd,
+    _mm512_loadu_pd,
+    _mm512_storeu_pd,
+    _mm512_add_pd,
+    _mm512_mul_pd
+);
+#[cfg(target_arch = "x86_64")]
+lanes!(
+    avx2_f32,
+    "avx2",
+    f32,
+    8,
+    _mm256_set1_ps,
+    _mm256_loadu_ps,
+    _mm256_storeu_ps,
+    _mm256_add_ps,
+    _mm256_mul_ps
+);
+#[cfg(target_arch = "x86_64")]
+lanes!(
+    avx2_f64,
+    "avx2",
+    f64,
+    4,
+    _mm256_set1_pd,
+    _mm256_loadu_pd,
+    _mm256_storeu_pd,
+    _mm256_add_pd,
+    _mm256_mul_pd
+);
+#[cfg(target_arch = "aarch64")]
+lanes!(
+    neon_f32,
+    "neon",
+    f32,
+    4,
+    vdupq_n_f32,
+    vld1q_f32,
+    vst1q_f32,
+    vaddq_f32,
+    vmulq_f32
+);
+#[cfg(target_arch = "aarch64")]
+lanes!(
+    neon_f64,
+    "neon",
+    f64,
+    2,
+    vdupq_n_f64,
+    vld1q_f64,
+    vst1q_f64,
+    vaddq_f64,
+    vmulq_f64
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Isa {
+    #[cfg(target_arch = "x86_64")]
+    Avx512,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    #[cfg(target_arch = "aarch64")]
+    Neon,
+}
+
+// the widest vector unit of the cpu, detected once by std
+fn isa() -> Option<Isa> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx512f") {
+            return Some(Isa::Avx512);
+        }
+        if std::is_x86_feature_detected!("avx2") {
+            return Some(Isa::Avx2);
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        return Some(Isa::Neon);
+    }
+
+    None
+}
+
+fn is<T: 'static, U: 'static>() -> bool {
+    TypeId::of::<T>() == TypeId::of::<U>()
+}
+
+// the elements are f32 or f64 and the cpu has a vector unit for them
+pub(crate) fn enabled<T: 'static>() -> bool {
+    (is::<T, f32>() || is::<T, f64>()) && isa().is_some()
+}
+
+// T must be f32 or f64, see enabled
+pub(super) fn conv<T: 'static, const N: usize>(
+    origins: &ArrayView<T, Dim<[Ix; N]>>,
+    offset_list: &[(isize, T)],
+    ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+) where
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    let f32 = is::<T, f32>();
+    match isa() {
+        #[cfg(target_arch = "x86_64")]
+        Some(Isa::Avx512) if f32 => unsafe { avx512_f32(origins, offset_list, ret) },
+        #[cfg(target_arch = "x86_64")]
+        Some(Isa::Avx512) => unsafe { avx512_f64(origins, offset_list, ret) },
+        #[cfg(target_arch = "x86_64")]
+        Some(Isa::Avx2) if f32 => unsafe { avx2_f32(origins, offset_list, ret) },
+        #[cfg(target_arch = "x86_64")]
+        Some(Isa::Avx2) => unsafe { avx2_f64(origins, offset_list, ret) },
+        #[cfg(target_arch = "aarch64")]
+        Some(Isa::Neon) if f32 => unsafe { neon_f32(origins, offset_list, ret) },
+        #[cfg(target_arch = "aarch64")]
+        Some(Isa::Neon) => unsafe { neon_f64(origins, offset_list, ret) },
+        None => unreachable!("no vector unit, see enabled"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array;
+
+    use crate::{dilation::WithDilation, ConvExt, ConvMode, PaddingMode};
+
+    #[test]
+    fn same_as_scalar() {
+        // the vector loops are the raw pointer path, which debug builds replace by default
+        crate::set_checked(false);
+
+        // odd widths leave a scalar tail after the vectors, strides gather the lanes
+        let arr = Array::from_shape_fn((11, 37), |(i, j)| ((i * 37 + j) % 13) as f64 * 0.1 - 0.7);
+        let kernel = Array::from_shape_fn((3, 4), |(i, j)| (i * 4 + j) as f64 * 0.3 - 1.45);
+
+        for conv_mode in [
+            ConvMode::Full,
+            ConvMode::Valid,
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [1, 3],
+            },
+        ] {
+            let expected = arr
+                .conv_generic(
+                    kernel.with_dilation([1, 2]),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                    |x, k| x * k,
+                    |a, b| a + b,
+                    0.,
+                )
+                .unwrap();
+            let ret = arr
+                .conv(
+                    kernel.with_dilation([1, 2]),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                )
+                .unwrap();
+            assert_eq!(ret, expected);
+
+            let (arr, kernel) = (arr.mapv(|v| v as f32), kernel.mapv(|v| v as f32));
+            let expected = arr
+                .conv_generic(
+                    kernel.with_dilation([1, 2]),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                    |x, k| x * k,
+                    |a, b| a + b,
+                    0.,
+                )
+                .unwrap();
+            let ret = arr
+                .conv(
+                    kernel.with_dilation([1, 2]),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                )
+                .unwrap();
+            assert_eq!(ret, expected);
+        }
+    }
+}
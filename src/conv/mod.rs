@@ -1,19 +1,53 @@
-use std::fmt::Debug;
+use core::{fmt::Debug, ops::ControlFlow};
+
+use alloc::{boxed::Box, format, vec::Vec};
 
 use ndarray::{
-    Array, ArrayBase, ArrayView, Data, Dim, Dimension, IntoDimension, Ix, RawData, RemoveAxis,
-    SliceArg, SliceInfo, SliceInfoElem,
+    Array, ArrayBase, ArrayViewMut, Axis, Data, DataMut, Dim, Dimension, IntoDimension, Ix,
+    RawData, RemoveAxis, SliceArg, SliceInfo, SliceInfoElem,
 };
 use num::traits::NumAssign;
 
 use crate::{
-    dilation::{IntoKernelWithDilation, KernelWithDilation},
-    padding::PaddingExt,
-    ConvMode, PaddingMode,
+    dilation::{dilated, IntoKernelWithDilation, KernelRef, KernelWithDilation},
+    padding::padding_region,
+    window::Windows,
+    ConvMode, PaddingMode, SamePolicy,
 };
 
+mod checked;
+mod deterministic;
+#[cfg(all(feature = "std", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod dispatch;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod simd;
 #[cfg(test)]
 mod tests;
+mod tiled;
+mod tiles;
+mod virtual_padding;
+
+#[cfg(all(feature = "std", any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub(crate) use dispatch::enabled as simd_enabled;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub(crate) use simd::enabled as simd_enabled;
+#[cfg(not(any(
+    all(feature = "std", any(target_arch = "x86_64", target_arch = "aarch64")),
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+#[allow(clippy::extra_unused_type_parameters)]
+pub(crate) fn simd_enabled<T: 'static>() -> bool {
+    false
+}
+
+pub use checked::set_checked;
+#[cfg(feature = "std")]
+pub(crate) use deterministic::enabled as deterministic;
+pub use deterministic::set_deterministic;
+pub use tiles::ConvTiles;
+
+// outputs between two calls of the progress callback
+const PROGRESS_SLAB: usize = 1 << 16;
 
 pub struct ExplicitConv<const N: usize> {
     pub padding: [[usize; 2]; N],
@@ -21,23 +55,112 @@ pub struct ExplicitConv<const N: usize> {
 }
 
 impl<const N: usize> ConvMode<N> {
-    pub(crate) fn unfold<S>(self, kernel: &KernelWithDilation<S, N>) -> ExplicitConv<N>
+    pub(crate) fn unfold<S>(
+        self,
+        kernel: &KernelWithDilation<S, N>,
+        input_dim: [usize; N],
+    ) -> Result<ExplicitConv<N>, crate::Error<N>>
     where
         S: ndarray::RawData,
         Dim<[Ix; N]>: Dimension,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
     {
-        let kernel_dim = kernel.kernel.raw_dim();
-        let kernel_dim: [usize; N] = std::array::from_fn(|i|
-                // k + (k - 1) * (d - 1)
-                kernel_dim[i] * kernel.dilation[i] - kernel.dilation[i] + 1);
+        let kernel_shape = core::array::from_fn(|i| kernel.kernel.shape()[i]);
+        let kernel_dim = dilated(kernel_shape, kernel.dilation)?;
+
+        let mut cm = self.unfold_with_dim(kernel_dim, input_dim)?;
+
+        // the origin moves padding from one side to the other
+        for (padding, &origin) in cm.padding.iter_mut().zip(kernel.origin.iter()) {
+            let before = padding[0] as isize + origin;
+            let after = padding[1] as isize - origin;
+            if before < 0 || after < 0 {
+                return Err(crate::Error::InvalidParameter(format!(
+                    "origin {:?} needs a negative padding with {:?}",
+                    kernel.origin, self
+                )));
+            }
+            *padding = [before as usize, after as usize];
+        }
+
+        Ok(cm)
+    }
+
+    // the same mode with the axes in reverse order
+    pub(crate) fn reversed(self) -> Self {
+        match self {
+            ConvMode::Custom { padding, strides } => ConvMode::Custom {
+                padding: reversed(padding),
+                strides: reversed(strides),
+            },
+            ConvMode::Explicit { padding, strides } => ConvMode::Explicit {
+                padding: reversed(padding),
+                strides: reversed(strides),
+            },
+            ConvMode::OutputSize { shape, strides } => ConvMode::OutputSize {
+                shape: reversed(shape),
+                strides: reversed(strides),
+            },
+            ConvMode::Causal { axis } if axis < N => ConvMode::Causal { axis: N - 1 - axis },
+            conv_mode => conv_mode,
+        }
+    }
 
+    // circular conv wraps around the input whatever the padding mode
+    pub(crate) fn padding_mode<T: NumAssign + Copy>(
+        self,
+        padding_mode: PaddingMode<N, T>,
+    ) -> PaddingMode<N, T> {
         match self {
+            ConvMode::Circular => PaddingMode::Circular,
+            _ => padding_mode,
+        }
+    }
+
+    // kernel_dim is the kernel size with dilation.
+    // every entry point unfolds its mode here, so the shapes are validated here too.
+    pub(crate) fn unfold_with_dim(
+        self,
+        kernel_dim: [usize; N],
+        input_dim: [usize; N],
+    ) -> Result<ExplicitConv<N>, crate::Error<N>>
+    where
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    {
+        if input_dim.contains(&0) {
+            return Err(crate::Error::DataShape(input_dim.into_dimension()));
+        }
+        if kernel_dim.contains(&0) {
+            return Err(crate::Error::KernelShape(kernel_dim.into_dimension()));
+        }
+
+        let cm = self.padding_with_dim(kernel_dim, input_dim)?;
+
+        for i in 0..N {
+            if cm.strides[i] == 0 {
+                return Err(crate::Error::ZeroStride(i));
+            }
+            let padded = input_dim[i] + cm.padding[i][0] + cm.padding[i][1];
+            if kernel_dim[i] > padded {
+                return Err(crate::Error::KernelTooLarge(i, kernel_dim[i], padded));
+            }
+        }
+
+        Ok(cm)
+    }
+
+    fn padding_with_dim(
+        self,
+        kernel_dim: [usize; N],
+        input_dim: [usize; N],
+    ) -> Result<ExplicitConv<N>, crate::Error<N>> {
+        Ok(match self {
             ConvMode::Full => ExplicitConv {
-                padding: std::array::from_fn(|i| [kernel_dim[i] - 1; 2]),
+                padding: core::array::from_fn(|i| [kernel_dim[i] - 1; 2]),
                 strides: [1; N],
             },
             ConvMode::Same => ExplicitConv {
-                padding: std::array::from_fn(|i| {
+                padding: core::array::from_fn(|i| {
                     let k_size = kernel_dim[i];
                     if k_size % 2 == 0 {
                         [(k_size - 1) / 2 + 1, (k_size - 1) / 2]
@@ -47,16 +170,55 @@ impl<const N: usize> ConvMode<N> {
                 }),
                 strides: [1; N],
             },
+            ConvMode::SameAs(SamePolicy::Scipy) | ConvMode::Circular => {
+                ConvMode::Same.padding_with_dim(kernel_dim, input_dim)?
+            }
+            ConvMode::SameAs(SamePolicy::PyTorch | SamePolicy::TensorFlow) => ExplicitConv {
+                padding: core::array::from_fn(|i| [(kernel_dim[i] - 1) / 2, kernel_dim[i] / 2]),
+                strides: [1; N],
+            },
             ConvMode::Valid => ExplicitConv {
                 padding: [[0; 2]; N],
                 strides: [1; N],
             },
+            ConvMode::Causal { axis } => {
+                if axis >= N {
+                    return Err(crate::Error::InvalidParameter(format!(
+                        "causal axis {} of a {}D conv",
+                        axis, N
+                    )));
+                }
+                let mut cm = ConvMode::Same.padding_with_dim(kernel_dim, input_dim)?;
+                cm.padding[axis] = [kernel_dim[axis] - 1, 0];
+                cm
+            }
             ConvMode::Custom { padding, strides } => ExplicitConv {
                 padding: padding.map(|pad| [pad; 2]),
                 strides,
             },
             ConvMode::Explicit { padding, strides } => ExplicitConv { padding, strides },
-        }
+            ConvMode::OutputSize { shape, strides } => {
+                let mut padding = [[0; 2]; N];
+                for i in 0..N {
+                    if strides[i] == 0 {
+                        return Err(crate::Error::ZeroStride(i));
+                    }
+                    // the least padding reaching the shape, split like SamePolicy::TensorFlow
+                    let needed = (shape[i].max(1) - 1) * strides[i] + kernel_dim[i];
+                    let total = needed.saturating_sub(input_dim[i]);
+                    if shape[i] == 0
+                        || (input_dim[i] + total - kernel_dim[i]) / strides[i] + 1 != shape[i]
+                    {
+                        return Err(crate::Error::InvalidParameter(format!(
+                            "output shape {:?} is unreachable from input {:?} with kernel {:?} and strides {:?}",
+                            shape, input_dim, kernel_dim, strides
+                        )));
+                    }
+                    padding[i] = [total / 2, total - total / 2];
+                }
+                ExplicitConv { padding, strides }
+            }
+        })
     }
 }
 
@@ -72,11 +234,78 @@ where
         conv_mode: ConvMode<N>,
         padding_mode: PaddingMode<N, T>,
     ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// conv over a custom semiring: every output is `identity` folded with
+    /// `add` over `mul(input, kernel)` of all the taps, zero weights included.
+    /// e.g. min-plus with `identity = inf` for distance transforms.
+    fn conv_generic(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        mul: impl Fn(T, T) -> T,
+        add: impl Fn(T, T) -> T,
+        identity: T,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// the outputs of `conv` at the given indices only, in the same order.
+    fn conv_at(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        points: &[[usize; N]],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Vec<T>, crate::Error<N>>;
+
+    /// the outputs of `conv` over `roi`, `(start, end)` output indices per axis, end
+    /// excluded. only the input under the region and its halo is read, and padded when
+    /// the padding is larger than the input.
+    fn conv_roi(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        roi: [(usize, usize); N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// the output of `conv` in tiles of `tile_shape`, cut at the far edges, as
+    /// `(origin, tile)` in row major order of the tiles. a tile is only computed when
+    /// the iterator yields it, so skipped tiles (`nth`, `skip`) cost nothing.
+    fn conv_tiles<'s>(
+        &'s self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        tile_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<ConvTiles<'s, T, N>, crate::Error<N>>
+    where
+        T: 's;
+
+    /// `conv` written into `output` of the output shape, without allocating.
+    /// the padding can't be larger than the input, as no padded copy is made.
+    fn conv_into<SO: DataMut<Elem = T>>(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        output: &mut ArrayBase<SO, Dim<[Ix; N]>>,
+    ) -> Result<(), crate::Error<N>>;
+
+    /// `conv` computed in slabs along the first axis, each by the engine of `conv` over
+    /// the input rows under it, calling `progress(done, total)` with the outputs computed
+    /// so far after every slab. `ControlFlow::Break` stops there with `Error::Cancelled`.
+    fn conv_with_progress(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        progress: impl FnMut(usize, usize) -> ControlFlow<()>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
 }
 
 impl<'a, T, S, SK, const N: usize> ConvExt<'a, T, S, SK, N> for ArrayBase<S, Dim<[Ix; N]>>
 where
-    T: NumAssign + Copy + Debug,
+    T: NumAssign + Copy + Debug + 'static,
     S: Data<Elem = T> + 'a,
     SK: Data<Elem = T> + 'a,
     Dim<[Ix; N]>: RemoveAxis,
@@ -91,70 +320,553 @@ where
         padding_mode: PaddingMode<N, T>,
     ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
         let kwd = kernel.into_kernel_with_dilation();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        crate::trace::span!(
+            "conv",
+            input = ?self.shape(),
+            kernel = ?kwd.kernel.shape(),
+            dilation = ?kwd.dilation,
+            conv_mode = ?conv_mode,
+            padding_mode = ?padding_mode,
+        );
 
-        let self_raw_dim = self.raw_dim();
-        if self.shape().iter().product::<usize>() == 0 {
-            return Err(crate::Error::DataShape(self_raw_dim));
-        }
+        // column major input: the reversed axes are row major, so running on them
+        // pads and traverses the input in its memory order instead of transposing it.
+        // per axis borders fill the corners in axis order, so they keep the original axes
+        if is_column_major(self)
+            && !matches!(
+                padding_mode,
+                PaddingMode::Custom(_) | PaddingMode::Explicit(_)
+            )
+        {
+            // validate with the original axes, so errors name the caller's axes
+            conv_mode.unfold(&kwd, core::array::from_fn(|i| self.shape()[i]))?;
 
-        let kernel_raw_dim = kwd.kernel.raw_dim();
-        if kwd.kernel.shape().iter().product::<usize>() == 0 {
-            return Err(crate::Error::DataShape(kernel_raw_dim));
+            let kernel = kwd.kernel.view().reversed_axes();
+            let kwd = KernelWithDilation {
+                kernel: KernelRef::Borrowed(&kernel),
+                dilation: reversed(kwd.dilation),
+                origin: reversed(kwd.origin),
+                zero_taps: kwd.zero_taps,
+            };
+
+            return self
+                .view()
+                .reversed_axes()
+                .conv(kwd, conv_mode.reversed(), padding_mode)
+                .map(|ret| ret.reversed_axes())
+                .map_err(|err| match err {
+                    crate::Error::DataShape(dim) => crate::Error::DataShape(reversed_dim(dim)),
+                    crate::Error::KernelShape(dim) => crate::Error::KernelShape(reversed_dim(dim)),
+                    crate::Error::MismatchShape(_, dim) => {
+                        crate::Error::MismatchShape(conv_mode, reversed(dim))
+                    }
+                    err => err,
+                });
         }
 
-        let kernel_raw_dim_with_dilation: [usize; N] =
-            std::array::from_fn(|i| kernel_raw_dim[i] * kwd.dilation[i] - kwd.dilation[i] + 1);
+        let (cm, kernel_dim, output_shape) = explicit(self, &kwd, conv_mode)?;
 
-        let cm = conv_mode.unfold(&kwd);
-        let pds = self.padding(padding_mode, cm.padding);
+        let mut ret = Array::zeros(output_shape);
+        execute(
+            self,
+            &kwd,
+            &cm,
+            kernel_dim,
+            conv_mode,
+            padding_mode,
+            &mut ret.view_mut(),
+        )?;
 
-        let pds_raw_dim = pds.raw_dim();
-        if !(0..N).all(|i| kernel_raw_dim_with_dilation[i] <= pds_raw_dim[i]) {
-            return Err(crate::Error::MismatchShape(
-                conv_mode,
-                kernel_raw_dim_with_dilation,
+        Ok(ret)
+    }
+
+    fn conv_generic(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        mul: impl Fn(T, T) -> T,
+        add: impl Fn(T, T) -> T,
+        identity: T,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        // zero is not the additive identity of every semiring, keep all the taps
+        let kwd = kernel.into_kernel_with_dilation().with_zero_taps();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        if checked::enabled() {
+            let (cm, _, output_shape) = explicit(self, &kwd, conv_mode)?;
+            return Ok(checked::generic(
+                self,
+                &kwd,
+                &cm,
+                padding_mode,
+                output_shape,
+                mul,
+                add,
+                identity,
             ));
         }
 
-        let offset_list = kwd.gen_offset_list(pds.strides());
+        let windows = windows(self, &kwd, conv_mode, padding_mode)?;
+
+        let offset_list = kwd.gen_offset_list(windows.padded_strides());
+
+        Ok(windows.origins().map(|cur| {
+            offset_list
+                .iter()
+                .fold(identity, |acc, &(tmp_offset, tmp_kernel)| {
+                    add(
+                        acc,
+                        mul(unsafe { *(cur as *const T).offset(tmp_offset) }, tmp_kernel),
+                    )
+                })
+        }))
+    }
+
+    fn conv_at(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        points: &[[usize; N]],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Vec<T>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        let (cm, kernel_dim, output_shape) = explicit(self, &kwd, conv_mode)?;
+
+        if let Some(point) = points
+            .iter()
+            .find(|point| (0..N).any(|i| point[i] >= output_shape[i]))
+        {
+            return Err(crate::Error::InvalidParameter(format!(
+                "point {:?} is outside the output of shape {:?}",
+                point, output_shape
+            )));
+        }
+
+        if points.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        let output_shape: [usize; N] = std::array::from_fn(|i| {
-            (cm.padding[i][0] + cm.padding[i][1] + self_raw_dim[i]
-                - kernel_raw_dim_with_dilation[i])
-                / cm.strides[i]
-                + 1
+        // the box around the points
+        let region = core::array::from_fn(|i| {
+            points.iter().fold((usize::MAX, 0), |(start, end), point| {
+                (start.min(point[i]), end.max(point[i] + 1))
+            })
         });
+        let output = evaluator(self, &kwd, &cm, kernel_dim, padding_mode, region);
+
+        Ok(points.iter().map(|&point| output(point)).collect())
+    }
+
+    fn conv_roi(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        roi: [(usize, usize); N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        let (cm, kernel_dim, output_shape) = explicit(self, &kwd, conv_mode)?;
+
+        if (0..N).any(|i| roi[i].0 > roi[i].1 || roi[i].1 > output_shape[i]) {
+            return Err(crate::Error::InvalidParameter(format!(
+                "roi {:?} is outside the output of shape {:?}",
+                roi, output_shape
+            )));
+        }
+        let roi_shape: [usize; N] = core::array::from_fn(|i| roi[i].1 - roi[i].0);
+        if roi_shape.contains(&0) {
+            return Ok(Array::zeros(roi_shape));
+        }
+
+        let output = evaluator(self, &kwd, &cm, kernel_dim, padding_mode, roi);
+
+        Ok(Array::from_shape_fn(roi_shape, |index| {
+            let index = index.into_dimension();
+            output(core::array::from_fn(|i| roi[i].0 + index[i]))
+        }))
+    }
+
+    fn conv_tiles<'s>(
+        &'s self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        tile_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<ConvTiles<'s, T, N>, crate::Error<N>>
+    where
+        T: 's,
+    {
+        let kwd = kernel.into_kernel_with_dilation();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        let (cm, kernel_dim, output_shape) = explicit(self, &kwd, conv_mode)?;
+
+        if tile_shape.contains(&0) {
+            return Err(crate::Error::InvalidParameter(format!(
+                "tile shape {:?} shouldn't have ZERO",
+                tile_shape
+            )));
+        }
+
+        let output = evaluator(
+            self,
+            &kwd,
+            &cm,
+            kernel_dim,
+            padding_mode,
+            output_shape.map(|n| (0, n)),
+        );
+
+        Ok(ConvTiles::new(output, output_shape, tile_shape))
+    }
+
+    fn conv_into<SO: DataMut<Elem = T>>(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        output: &mut ArrayBase<SO, Dim<[Ix; N]>>,
+    ) -> Result<(), crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        let (cm, _, output_shape) = explicit(self, &kwd, conv_mode)?;
+
+        if output.shape() != output_shape {
+            return Err(crate::Error::InvalidParameter(format!(
+                "output of shape {:?} for an output shape of {:?}",
+                output.shape(),
+                output_shape
+            )));
+        }
+
+        let input_dim = core::array::from_fn(|i| self.shape()[i]);
+        if !virtual_padding::padding_fits(padding_mode, &cm, input_dim) {
+            return Err(crate::Error::InvalidParameter(format!(
+                "padding {:?} is larger than the input {:?}",
+                cm.padding,
+                self.shape()
+            )));
+        }
+
+        if checked::enabled() {
+            // the checked loop reads a padded copy, so this allocates
+            checked::conv(self, &kwd, &cm, padding_mode, &mut output.view_mut());
+        } else {
+            virtual_padding::conv_into(self, &kwd, &cm, padding_mode, output);
+        }
+
+        Ok(())
+    }
+
+    fn conv_with_progress(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        mut progress: impl FnMut(usize, usize) -> ControlFlow<()>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+        let padding_mode = conv_mode.padding_mode(padding_mode);
+        let (cm, kernel_dim, output_shape) = explicit(self, &kwd, conv_mode)?;
+
+        // slabs of whole rows along the first axis, of about PROGRESS_SLAB outputs
+        let row = output_shape[1..].iter().product::<usize>().max(1);
+        let slab = (PROGRESS_SLAB / row).max(1);
+
+        // every slab runs the engine of conv over the input rows under it. those are padded
+        // along the first axis at the borders only, the engine pads the other axes
+        let mut slab_cm = ExplicitConv {
+            padding: cm.padding,
+            strides: cm.strides,
+        };
+        slab_cm.padding[0] = [0, 0];
+        let (n, before) = (self.shape()[0], cm.padding[0][0]);
+
         let mut ret = Array::zeros(output_shape);
+        let total = ret.len();
+        let mut done = 0;
+        for start in (0..output_shape[0]).step_by(slab) {
+            let end = (start + slab).min(output_shape[0]);
+            let mut out = ret.slice_axis_mut(Axis(0), (start..end).into());
 
-        let shape: [usize; N] = std::array::from_fn(|i| ret.raw_dim()[i]);
-        let strides: [usize; N] =
-            std::array::from_fn(|i| cm.strides[i] * pds.strides()[i] as usize);
+            // the input rows under the slab, in padded coordinates
+            let rows = (
+                start * cm.strides[0],
+                (end - 1) * cm.strides[0] + kernel_dim[0],
+            );
+            if rows.0 >= before && rows.1 - before <= n {
+                let input = self.slice_axis(Axis(0), (rows.0 - before..rows.1 - before).into());
+                execute(
+                    &input,
+                    &kwd,
+                    &slab_cm,
+                    kernel_dim,
+                    conv_mode,
+                    padding_mode,
+                    &mut out,
+                )?;
+            } else {
+                let mut region = core::array::from_fn(|i| (0, self.shape()[i]));
+                region[0] = rows;
+                let mut padding = [[0; 2]; N];
+                padding[0] = cm.padding[0];
+                let input = padding_region(self, padding_mode, padding, region);
+                execute(
+                    &input,
+                    &kwd,
+                    &slab_cm,
+                    kernel_dim,
+                    conv_mode,
+                    padding_mode,
+                    &mut out,
+                )?;
+            }
 
-        // dbg!(&offset_list);
-        // dbg!(strides);
+            done += out.len();
+            if progress(done, total).is_break() {
+                return Err(crate::Error::Cancelled);
+            }
+        }
 
-        unsafe {
-            // use raw pointer to improve performance.
-            let p: *mut T = ret.as_mut_ptr();
+        Ok(ret)
+    }
+}
 
-            // use ArrayView's iter without handle strides
-            let view = ArrayView::from_shape(
-                ndarray::ShapeBuilder::strides(shape, strides),
-                pds.as_slice().unwrap(),
-            )
-            .unwrap();
+// the engine of conv, writing the output into ret of the output shape, in standard layout
+fn execute<T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    kernel_dim: [usize; N],
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+    ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+) -> Result<(), crate::Error<N>>
+where
+    T: NumAssign + Copy + 'static,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    debug_assert!(ret.is_standard_layout());
+    let output_shape: [usize; N] = core::array::from_fn(|i| ret.shape()[i]);
 
-            view.iter().enumerate().for_each(|(i, cur)| {
-                let mut tmp_res = T::zero();
+    if checked::enabled() {
+        crate::trace::span!("execute", algorithm = "checked", output = ?output_shape);
+        checked::conv(data, kwd, cm, padding_mode, ret);
+        return Ok(());
+    }
 
-                offset_list.iter().for_each(|(tmp_offset, tmp_kernel)| {
-                    tmp_res += *(cur as *const T).offset(*tmp_offset) * *tmp_kernel
-                });
+    // the simd loop runs on the padded copy, it outpaces reading the input in place
+    let simd = simd_enabled::<T>();
+
+    // skip the padded copy when most windows lie inside the input
+    let input_dim = core::array::from_fn(|i| data.shape()[i]);
+    if !simd && virtual_padding::preferred(input_dim, kernel_dim, cm, output_shape) {
+        crate::trace::span!(
+            "execute",
+            algorithm = "virtual_padding",
+            output = ?output_shape
+        );
+        if virtual_padding::conv(data, kwd, cm, padding_mode, ret) {
+            return Ok(());
+        }
+    }
 
-                *p.add(i) = tmp_res;
+    let windows = {
+        crate::trace::span!("padding", padding = ?cm.padding);
+        Windows::new(data, kernel_dim, cm, padding_mode)
+            .ok_or(crate::Error::MismatchShape(conv_mode, kernel_dim))?
+    };
+
+    let offset_list = kwd.gen_offset_list(windows.padded_strides());
+
+    // dbg!(&offset_list);
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    if simd {
+        crate::trace::span!("execute", algorithm = "simd", output = ?output_shape);
+        simd::conv(&windows.origins(), &offset_list, ret);
+        return Ok(());
+    }
+
+    #[cfg(all(feature = "std", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    if simd {
+        crate::trace::span!("execute", algorithm = "simd", output = ?output_shape);
+        dispatch::conv(&windows.origins(), &offset_list, ret);
+        return Ok(());
+    }
+
+    if tiled::preferred(offset_list.len(), output_shape) {
+        crate::trace::span!(
+            "execute",
+            algorithm = "tiled",
+            output = ?output_shape,
+            taps = offset_list.len()
+        );
+        let origins = windows.origins();
+        tiled::fill(ret, |point| {
+            let cur = &origins[point.into_dimension()] as *const T;
+            offset_list.iter().fold(T::zero(), |acc, &(offset, k)| {
+                acc + unsafe { *cur.offset(offset) } * k
+            })
+        });
+        return Ok(());
+    }
+
+    crate::trace::span!(
+        "execute",
+        algorithm = "direct",
+        output = ?output_shape,
+        taps = offset_list.len()
+    );
+    unsafe {
+        // use raw pointer to improve performance.
+        let p: *mut T = ret.as_mut_ptr();
+
+        windows.origins().iter().enumerate().for_each(|(i, cur)| {
+            let mut tmp_res = T::zero();
+
+            offset_list.iter().for_each(|(tmp_offset, tmp_kernel)| {
+                tmp_res += *(cur as *const T).offset(*tmp_offset) * *tmp_kernel
             });
+
+            *p.add(i) = tmp_res;
+        });
+    }
+
+    Ok(())
+}
+
+// the output of conv at an index of the non-empty output `region`, `(start, end)` per axis.
+// reads the input in place when the padding allows, else a padded copy of the input
+// under the region and its halo only
+fn evaluator<'d, T, S, SK, const N: usize>(
+    data: &'d ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    kernel_dim: [usize; N],
+    padding_mode: PaddingMode<N, T>,
+    region: [(usize, usize); N],
+) -> Box<dyn Fn([usize; N]) -> T + 'd>
+where
+    T: NumAssign + Copy + 'd,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let checked = checked::enabled();
+    if !checked {
+        if let Some(output) = virtual_padding::output(data, kwd, cm, padding_mode) {
+            return Box::new(output);
         }
+    }
 
-        Ok(ret)
+    let strides = cm.strides;
+    let halo = core::array::from_fn(|i| {
+        (
+            region[i].0 * strides[i],
+            (region[i].1 - 1) * strides[i] + kernel_dim[i],
+        )
+    });
+    let padded = padding_region(data, padding_mode, cm.padding, halo);
+    let start: [usize; N] = core::array::from_fn(|i| region[i].0);
+    if checked {
+        return Box::new(checked::output(
+            padded,
+            kwd,
+            strides,
+            start,
+            |x, k| x * k,
+            |a, b| a + b,
+            T::zero(),
+        ));
     }
+
+    let offset_list = kwd.gen_offset_list(padded.strides());
+
+    Box::new(move |index: [usize; N]| {
+        let origin: [usize; N] = core::array::from_fn(|i| (index[i] - start[i]) * strides[i]);
+        let cur = &padded[origin.into_dimension()] as *const T;
+        offset_list.iter().fold(T::zero(), |acc, &(offset, k)| {
+            acc + unsafe { *cur.offset(offset) } * k
+        })
+    })
+}
+
+fn windows<'a, T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<'a, SK, N>,
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+) -> Result<Windows<T, N>, crate::Error<N>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let (cm, kernel_dim, _) = explicit(data, kwd, conv_mode)?;
+    Windows::new(data, kernel_dim, &cm, padding_mode)
+        .ok_or(crate::Error::MismatchShape(conv_mode, kernel_dim))
+}
+
+// validates the shapes, returns the unfolded mode, the kernel size with dilation
+// and the output shape
+#[allow(clippy::type_complexity)]
+fn explicit<'a, T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<'a, SK, N>,
+    conv_mode: ConvMode<N>,
+) -> Result<(ExplicitConv<N>, [usize; N], [usize; N]), crate::Error<N>>
+where
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let input_dim = core::array::from_fn(|i| data.shape()[i]);
+    crate::trace::span!("plan");
+
+    // unfolding validates the shapes
+    let cm = conv_mode.unfold(kwd, input_dim)?;
+    let kernel_dim = dilated(
+        core::array::from_fn(|i| kwd.kernel.shape()[i]),
+        kwd.dilation,
+    )?;
+
+    let output_shape = core::array::from_fn(|i| {
+        (input_dim[i] + cm.padding[i][0] + cm.padding[i][1] - kernel_dim[i]) / cm.strides[i] + 1
+    });
+
+    Ok((cm, kernel_dim, output_shape))
+}
+
+fn is_column_major<S: Data, const N: usize>(data: &ArrayBase<S, Dim<[Ix; N]>>) -> bool
+where
+    Dim<[Ix; N]>: Dimension,
+{
+    N > 1 && !data.is_standard_layout() && data.t().is_standard_layout()
+}
+
+fn reversed<A, const N: usize>(mut arr: [A; N]) -> [A; N] {
+    arr.reverse();
+    arr
+}
+
+fn reversed_dim<const N: usize>(mut dim: Dim<[Ix; N]>) -> Dim<[Ix; N]>
+where
+    Dim<[Ix; N]>: Dimension,
+{
+    dim.slice_mut().reverse();
+    dim
 }
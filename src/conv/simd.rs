@@ -0,0 +1,110 @@
+use core::{any::TypeId, arch::wasm32::*};
+
+use alloc::vec::Vec;
+
+use ndarray::{ArrayView, ArrayViewMut, Axis, Dim, Ix, RemoveAxis};
+
+// f32 inner loop on the wasm simd128 unit: four neighbouring outputs of a row are summed
+// at once, tap by tap in the scalar order, so the result is the same as the scalar loop.
+// T must be f32, see enabled.
+pub(super) fn conv<T, const N: usize>(
+    origins: &ArrayView<T, Dim<[Ix; N]>>,
+    offset_list: &[(isize, T)],
+    ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+) where
+    T: Copy,
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    let taps: Vec<(isize, f32)> = offset_list
+        .iter()
+        .map(|(offset, k)| (*offset, unsafe { *(k as *const T as *const f32) }))
+        .collect();
+
+    let last = Axis(N - 1);
+    origins
+        .lanes(last)
+        .into_iter()
+        .zip(ret.lanes_mut(last))
+        .for_each(|(origins, mut out)| unsafe {
+            let cur = origins.as_ptr() as *const f32;
+            let stride = origins.strides()[0];
+            let p = out.as_mut_ptr() as *mut f32;
+            let len = out.len();
+
+            let mut j = 0;
+            while j + 4 <= len {
+                let base = cur.offset(j as isize * stride);
+                let mut sum = f32x4_splat(0.);
+                taps.iter().for_each(|&(offset, k)| {
+                    let x = base.offset(offset);
+                    let x = if stride == 1 {
+                        v128_load(x as *const v128)
+                    } else {
+                        f32x4(
+                            *x,
+                            *x.offset(stride),
+                            *x.offset(2 * stride),
+                            *x.offset(3 * stride),
+                        )
+                    };
+                    sum = f32x4_add(sum, f32x4_mul(x, f32x4_splat(k)));
+                });
+                v128_store(p.add(j) as *mut v128, sum);
+                j += 4;
+            }
+
+            for j in j..len {
+                let base = cur.offset(j as isize * stride);
+                *p.add(j) = taps
+                    .iter()
+                    .fold(0., |acc, &(offset, k)| acc + *base.offset(offset) * k);
+            }
+        });
+}
+
+// the elements are f32
+pub(crate) fn enabled<T: 'static>() -> bool {
+    TypeId::of::<T>() == TypeId::of::<f32>()
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array;
+
+    use crate::{dilation::WithDilation, ConvExt, ConvMode, PaddingMode};
+
+    #[test]
+    fn same_as_scalar() {
+        // the vector loops are the raw pointer path, which debug builds replace by default
+        crate::set_checked(false);
+
+        let arr = Array::from_shape_fn((11, 13), |(i, j)| ((i * 13 + j) % 7) as f32 - 3.);
+        let kernel = Array::from_shape_fn((3, 2), |(i, j)| (i * 2 + j) as f32 * 0.5 - 1.);
+
+        for conv_mode in [
+            ConvMode::Full,
+            ConvMode::Valid,
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [1, 3],
+            },
+        ] {
+            let ret = arr
+                .conv(
+                    kernel.with_dilation([1, 2]),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                )
+                .unwrap();
+            let expected = arr
+                .mapv(|v| v as f64)
+                .conv(
+                    kernel.mapv(|v| v as f64).with_dilation([1, 2]),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                )
+                .unwrap();
+            assert_eq!(ret.mapv(|v| v as f64), expected);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
-use crate::dilation::WithDilation;
+use crate::dilation::{WithDilation, WithOrigin};
+use crate::{reference::assert_close, BorderType, ConvFFTExt};
 use ndarray::prelude::*;
 
 #[test]
@@ -195,3 +196,1039 @@ fn aligned_with_libtorch() {
     assert_eq!(res, array![[[1, 2]], [[5, 6]], [[1, 2]], [[5, 6]]]);
     dbg!(res);
 }
+
+#[test]
+fn conv_generic() {
+    // min-plus: distance to the nearest zero
+    let arr = array![
+        0.,
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::INFINITY,
+        0.,
+        f64::INFINITY
+    ];
+    let kernel = array![2., 1., 0., 1., 2.];
+
+    let res = arr
+        .conv_generic(
+            &kernel,
+            ConvMode::Same,
+            PaddingMode::Const(f64::INFINITY),
+            |a, b| a + b,
+            f64::min,
+            f64::INFINITY,
+        )
+        .unwrap();
+    assert_eq!(res, array![0., 1., 2., 1., 0., 1.]);
+
+    // multiply-add with zero taps is the same as conv
+    let arr = array![[1, 2, 3], [4, 5, 6]];
+    let kernel = array![[1, 0], [0, 2]];
+    assert_eq!(
+        arr.conv_generic(
+            kernel.with_dilation(2),
+            ConvMode::Full,
+            PaddingMode::Replicate,
+            |a, b| a * b,
+            |a, b| a + b,
+            0,
+        )
+        .unwrap(),
+        arr.conv(
+            kernel.with_dilation(2),
+            ConvMode::Full,
+            PaddingMode::Replicate
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn strided_work() {
+    // a decimating fir only evaluates the kept outputs, about 1/M of the unstrided work
+    let arr = Array1::from_shape_fn(64, |i| ((i * 7) % 13) as i64 - 6);
+    let kernel = Array1::from_shape_fn(15, |i| (i % 5) as i64 - 2);
+    let taps = std::cell::Cell::new(0);
+
+    for stride in [1, 2, 4] {
+        let conv_mode = ConvMode::Custom {
+            padding: [7],
+            strides: [stride],
+        };
+        taps.set(0);
+        let res = arr
+            .conv_generic(
+                &kernel,
+                conv_mode,
+                PaddingMode::Reflect,
+                |a, b| {
+                    taps.set(taps.get() + 1);
+                    a * b
+                },
+                |a, b| a + b,
+                0,
+            )
+            .unwrap();
+        assert_eq!(res.len(), 63 / stride + 1);
+        assert_eq!(taps.get(), res.len() * kernel.len());
+
+        let full = arr
+            .conv(&kernel, ConvMode::Same, PaddingMode::Reflect)
+            .unwrap();
+        assert_eq!(res, full.slice(s![..;stride]));
+        assert_eq!(
+            arr.conv(&kernel, conv_mode, PaddingMode::Reflect).unwrap(),
+            res
+        );
+    }
+}
+
+#[test]
+fn circular() {
+    let arr = array![1, 2, 3, 4, 5];
+    assert_eq!(
+        arr.conv(&array![1, 2, 3], ConvMode::Circular, PaddingMode::Zeros)
+            .unwrap(),
+        array![13, 14, 20, 26, 17]
+    );
+
+    // longer than the input, the kernel wraps around it several times
+    let arr = array![1, 2];
+    assert_eq!(
+        arr.conv(
+            &array![1, 1, 1, 1, 1],
+            ConvMode::Circular,
+            PaddingMode::Zeros
+        )
+        .unwrap(),
+        array![7, 8]
+    );
+
+    let arr = Array2::from_shape_fn((5, 6), |(i, j)| ((i * 6 + j * 5) % 7) as f64 - 3.);
+    for (kernel, dilation) in [
+        (array![[1., 2., 0.], [-1., 3., 1.]], [1, 1]),
+        (array![[1., 2.], [-1., 3.]], [2, 3]),
+        (
+            Array2::from_shape_fn((7, 4), |(i, j)| (i + 2 * j) as f64),
+            [1, 2],
+        ),
+    ] {
+        // every output sums the taps over the input indices modulo its shape,
+        // centered like ConvMode::Same
+        let before = [
+            (kernel.nrows() - 1) * dilation[0],
+            (kernel.ncols() - 1) * dilation[1],
+        ]
+        .map(|span| span.div_ceil(2) as isize);
+        let expected = Array2::from_shape_fn(arr.dim(), |(i, j)| {
+            kernel
+                .indexed_iter()
+                .map(|((ki, kj), &k)| {
+                    let y = (i + ki * dilation[0]) as isize - before[0];
+                    let x = (j + kj * dilation[1]) as isize - before[1];
+                    k * arr[[y.rem_euclid(5) as usize, x.rem_euclid(6) as usize]]
+                })
+                .sum::<f64>()
+        });
+
+        let res = arr
+            .conv(
+                kernel.with_dilation(dilation),
+                ConvMode::Circular,
+                PaddingMode::Zeros,
+            )
+            .unwrap();
+        assert_eq!(res, expected);
+
+        let res = arr
+            .conv_fft(
+                kernel.with_dilation(dilation),
+                ConvMode::Circular,
+                PaddingMode::Zeros,
+            )
+            .unwrap();
+        assert_close(&res, &expected, 1e-9);
+    }
+}
+
+#[test]
+fn same_policy() {
+    let arr = array![1, 2, 3, 4, 5];
+    let kernel = array![1, 2, 3, 4];
+
+    let res = |conv_mode| arr.conv(&kernel, conv_mode, PaddingMode::Zeros).unwrap();
+
+    assert_eq!(
+        res(ConvMode::SameAs(SamePolicy::PyTorch)),
+        array![20, 30, 40, 26, 14]
+    );
+    assert_eq!(
+        res(ConvMode::SameAs(SamePolicy::TensorFlow)),
+        array![20, 30, 40, 26, 14]
+    );
+    assert_eq!(
+        res(ConvMode::SameAs(SamePolicy::Scipy)),
+        array![11, 20, 30, 40, 26]
+    );
+    assert_eq!(
+        res(ConvMode::Same),
+        res(ConvMode::SameAs(SamePolicy::Scipy))
+    );
+
+    // odd kernels are the same for every policy
+    let kernel = array![[1, 2, 3], [4, 5, 6]];
+    let arr = Array::from_shape_fn((4, 5), |(i, j)| (i * 5 + j) as i32);
+    for policy in [
+        SamePolicy::PyTorch,
+        SamePolicy::TensorFlow,
+        SamePolicy::Scipy,
+    ] {
+        assert_eq!(
+            arr.conv(
+                kernel.with_dilation([2, 1]),
+                ConvMode::SameAs(policy),
+                PaddingMode::Zeros
+            )
+            .unwrap(),
+            arr.conv(
+                kernel.with_dilation([2, 1]),
+                ConvMode::Same,
+                PaddingMode::Zeros
+            )
+            .unwrap()
+        );
+    }
+}
+
+#[test]
+fn causal() {
+    let arr = array![1, 2, 3, 4, 5];
+    let kernel = array![1, 2, 3];
+
+    let res = arr
+        .conv(&kernel, ConvMode::Causal { axis: 0 }, PaddingMode::Zeros)
+        .unwrap();
+    assert_eq!(res, array![3, 8, 14, 20, 26]);
+    // a later sample doesn't change the earlier outputs
+    let later = array![1, 2, 3, 9, 5]
+        .conv(&kernel, ConvMode::Causal { axis: 0 }, PaddingMode::Zeros)
+        .unwrap();
+    assert_eq!(later.slice(s![..3]), res.slice(s![..3]));
+
+    // the time axis of a 2D input, the other one padded like same
+    let arr = Array::from_shape_fn((4, 9), |(i, j)| (i * 9 + j) as f64);
+    let kernel = array![[1., 2., 3.], [4., 5., 6.]];
+    let expected = arr
+        .conv(
+            kernel.with_dilation([1, 2]),
+            ConvMode::Explicit {
+                padding: [[1, 0], [4, 0]],
+                strides: [1, 1],
+            },
+            PaddingMode::Replicate,
+        )
+        .unwrap();
+    let res = arr
+        .conv(
+            kernel.with_dilation([1, 2]),
+            ConvMode::Causal { axis: 1 },
+            PaddingMode::Replicate,
+        )
+        .unwrap();
+    assert_eq!(res.shape(), arr.shape());
+    assert_eq!(res, expected);
+    assert_close(
+        &arr.conv_fft(
+            kernel.with_dilation([1, 2]),
+            ConvMode::Causal { axis: 1 },
+            PaddingMode::Replicate,
+        )
+        .unwrap(),
+        &expected,
+        1e-9,
+    );
+
+    assert!(arr
+        .conv(&kernel, ConvMode::Causal { axis: 2 }, PaddingMode::Zeros)
+        .is_err());
+}
+
+#[test]
+fn origin() {
+    let arr = array![1, 2, 3, 4, 5];
+    let kernel = array![1, 1, 1];
+
+    // causal, only looks backward
+    assert_eq!(
+        arr.conv(kernel.with_origin([1]), ConvMode::Same, PaddingMode::Zeros)
+            .unwrap(),
+        array![1, 3, 6, 9, 12]
+    );
+    assert_eq!(
+        arr.conv(kernel.with_origin([-1]), ConvMode::Same, PaddingMode::Zeros)
+            .unwrap(),
+        array![6, 9, 12, 9, 5]
+    );
+    assert!(arr
+        .conv(kernel.with_origin([2]), ConvMode::Same, PaddingMode::Zeros)
+        .is_err());
+    assert!(arr
+        .conv(kernel.with_origin([1]), ConvMode::Valid, PaddingMode::Zeros)
+        .is_err());
+
+    let arr = Array::from_shape_fn((6, 7), |(i, j)| (i * 7 + j) as f64);
+    let kernel = array![[1., 2., 3.], [4., 5., 6.]];
+    let res = arr.conv(
+        kernel.with_dilation([1, 2]).with_origin([0, -2]),
+        ConvMode::Same,
+        PaddingMode::Replicate,
+    );
+    let res_fft = arr.conv_fft(
+        kernel.with_dilation([1, 2]).with_origin([0, -2]),
+        ConvMode::Same,
+        PaddingMode::Replicate,
+    );
+    assert_close(&res.unwrap(), &res_fft.unwrap(), 1e-9);
+}
+
+#[test]
+fn output_size() {
+    let arr = Array::from_shape_fn((7, 10), |(i, j)| (i * 10 + j) as i32);
+    let kernel = array![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+
+    let conv_mode = ConvMode::OutputSize {
+        shape: [7, 10],
+        strides: [1, 1],
+    };
+    assert_eq!(
+        arr.conv(&kernel, conv_mode, PaddingMode::Zeros).unwrap(),
+        arr.conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+            .unwrap()
+    );
+
+    // 7 -> 4 needs a total padding of 2, 10 -> 3 needs 0
+    let conv_mode = ConvMode::OutputSize {
+        shape: [4, 3],
+        strides: [2, 3],
+    };
+    assert_eq!(
+        arr.conv(&kernel, conv_mode, PaddingMode::Zeros).unwrap(),
+        arr.conv(
+            &kernel,
+            ConvMode::Explicit {
+                padding: [[1, 1], [0, 0]],
+                strides: [2, 3]
+            },
+            PaddingMode::Zeros
+        )
+        .unwrap()
+    );
+
+    // asymmetric padding with an even kernel and dilation
+    let conv_mode = ConvMode::OutputSize {
+        shape: [8, 12],
+        strides: [1, 1],
+    };
+    let res = arr
+        .conv(kernel.with_dilation([1, 2]), conv_mode, PaddingMode::Zeros)
+        .unwrap();
+    assert_eq!(
+        res,
+        arr.conv(
+            kernel.with_dilation([1, 2]),
+            ConvMode::Explicit {
+                padding: [[1, 2], [3, 3]],
+                strides: [1, 1]
+            },
+            PaddingMode::Zeros
+        )
+        .unwrap()
+    );
+
+    // too small without negative padding, and empty
+    for shape in [[2, 3], [4, 2], [0, 3]] {
+        assert!(arr
+            .conv(
+                &kernel,
+                ConvMode::OutputSize {
+                    shape,
+                    strides: [2, 3]
+                },
+                PaddingMode::Zeros
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn strided_inputs() {
+    let arr = Array::from_shape_fn((9, 16), |(i, j)| ((i * 16 + j) % 13) as i32);
+    let kernel = array![[1, 2, 3], [4, 5, 6]];
+
+    let check = |input: ArrayView2<i32>| {
+        for conv_mode in [
+            ConvMode::Same,
+            ConvMode::Full,
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [2, 3],
+            },
+        ] {
+            for padding_mode in [PaddingMode::Zeros, PaddingMode::Reflect] {
+                assert_eq!(
+                    input
+                        .conv(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+                        .unwrap(),
+                    input
+                        .to_owned()
+                        .conv(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+                        .unwrap()
+                );
+            }
+        }
+    };
+
+    check(arr.slice(s![.., ..;2]));
+    check(arr.slice(s![1..;2, 3..]));
+    check(arr.t());
+
+    // strided kernels
+    let kernel_t = kernel.t();
+    assert_eq!(
+        arr.conv(&kernel_t, ConvMode::Same, PaddingMode::Zeros)
+            .unwrap(),
+        arr.conv(&kernel_t.to_owned(), ConvMode::Same, PaddingMode::Zeros)
+            .unwrap()
+    );
+}
+
+#[test]
+fn column_major() {
+    let arr = Array::from_shape_fn((7, 9, 5).f(), |(i, j, k)| {
+        ((i * 45 + j * 5 + k) % 11) as i32
+    });
+    let kernel = Array::from_shape_fn((3, 2, 4), |(i, j, k)| (i * 8 + j * 4 + k) as i32 - 9);
+    assert!(arr.t().is_standard_layout());
+
+    for conv_mode in [
+        ConvMode::Same,
+        ConvMode::Explicit {
+            padding: [[1, 1], [2, 1], [1, 3]],
+            strides: [2, 1, 3],
+        },
+        ConvMode::OutputSize {
+            shape: [4, 9, 2],
+            strides: [2, 1, 3],
+        },
+    ] {
+        for padding_mode in [
+            PaddingMode::Reflect,
+            PaddingMode::Custom([
+                BorderType::Replicate,
+                BorderType::Const(3),
+                BorderType::Circular,
+            ]),
+        ] {
+            let res = arr
+                .conv(
+                    kernel.with_dilation([1, 2, 1]).with_origin([1, 0, -1]),
+                    conv_mode,
+                    padding_mode,
+                )
+                .unwrap();
+
+            assert_eq!(
+                res,
+                arr.as_standard_layout()
+                    .conv(
+                        kernel.with_dilation([1, 2, 1]).with_origin([1, 0, -1]),
+                        conv_mode,
+                        padding_mode
+                    )
+                    .unwrap()
+            );
+        }
+    }
+
+    // the output keeps the column major layout
+    assert!(arr
+        .conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+        .unwrap()
+        .t()
+        .is_standard_layout());
+
+    assert!(matches!(
+        arr.conv(
+            &Array::zeros((8, 1, 1)),
+            ConvMode::Valid,
+            PaddingMode::Zeros
+        ),
+        Err(crate::Error::KernelTooLarge(0, 8, 7))
+    ));
+}
+
+#[test]
+fn negative_strides() {
+    let arr = Array::from_shape_fn((8, 11), |(i, j)| ((i * 11 + j) % 7) as f64);
+    let kernel = array![[1., 2., 3.], [4., 5., 6.]];
+
+    let mut inverted = arr.view();
+    inverted.invert_axis(Axis(1));
+    let mut kernel_inverted = kernel.view();
+    kernel_inverted.invert_axis(Axis(0));
+
+    for (input, kernel) in [
+        (inverted, kernel.view()),
+        (arr.slice(s![..;-2, ..]), kernel.view()),
+        (arr.view(), kernel_inverted),
+        (inverted.reversed_axes(), kernel_inverted.reversed_axes()),
+    ] {
+        for conv_mode in [
+            ConvMode::Same,
+            ConvMode::Custom {
+                padding: [2, 1],
+                strides: [1, 2],
+            },
+        ] {
+            let expected = input
+                .to_owned()
+                .conv(&kernel.to_owned(), conv_mode, PaddingMode::Replicate)
+                .unwrap();
+
+            assert_eq!(
+                input
+                    .conv(&kernel, conv_mode, PaddingMode::Replicate)
+                    .unwrap(),
+                expected
+            );
+            assert_close(
+                &input
+                    .conv_fft(&kernel, conv_mode, PaddingMode::Replicate)
+                    .unwrap(),
+                &expected,
+                1e-9,
+            );
+        }
+    }
+}
+
+#[test]
+fn zero_taps() {
+    let arr = array![1., f64::NAN, 3.];
+    let kernel = array![1., 0., 0.];
+
+    // zero taps are skipped by default, so only a nonzero tap spreads the nan
+    let res = arr
+        .conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+        .unwrap();
+    assert_eq!(res.slice(s![..2]), array![0., 1.]);
+    assert!(res[2].is_nan());
+
+    let res = arr
+        .conv(
+            kernel.with_dilation(1).with_zero_taps(),
+            ConvMode::Same,
+            PaddingMode::Zeros,
+        )
+        .unwrap();
+    assert!(res.iter().all(|v| v.is_nan()));
+}
+
+#[test]
+fn conv_at() {
+    let arr = Array2::from_shape_fn((5, 6), |(i, j)| (i * 6 + j) as i32 % 7 - 3);
+    let kernel = array![[1, -2, 0], [3, 0, 4]];
+
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Same, PaddingMode::Reflect),
+        (ConvMode::Full, PaddingMode::Const(2)),
+        (ConvMode::Valid, PaddingMode::Zeros),
+        // padding past the input, not mapped without the padded copy
+        (
+            ConvMode::Custom {
+                padding: [7, 2],
+                strides: [2, 1],
+            },
+            PaddingMode::Circular,
+        ),
+    ] {
+        let expected = arr
+            .conv(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+            .unwrap();
+        let mut points = expected
+            .indexed_iter()
+            .map(|((i, j), _)| [i, j])
+            .collect::<Vec<_>>();
+        points.reverse();
+
+        assert_eq!(
+            arr.conv_at(
+                kernel.with_dilation([1, 2]),
+                &points,
+                conv_mode,
+                padding_mode
+            )
+            .unwrap(),
+            points
+                .iter()
+                .map(|&[i, j]| expected[[i, j]])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    assert!(arr
+        .conv_at(
+            &kernel,
+            &[[0, 0], [4, 4]],
+            ConvMode::Valid,
+            PaddingMode::Zeros
+        )
+        .is_err());
+}
+
+#[test]
+fn conv_tiles() {
+    let arr = Array2::from_shape_fn((7, 9), |(i, j)| (i * 9 + j) as i32 % 11 - 5);
+    let kernel = array![[1, -2, 0], [3, 0, 4]];
+
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Same, PaddingMode::Reflect),
+        (ConvMode::Full, PaddingMode::Const(2)),
+        // padding past the input, read from the padded copy
+        (
+            ConvMode::Custom {
+                padding: [8, 2],
+                strides: [2, 1],
+            },
+            PaddingMode::Symmetric,
+        ),
+    ] {
+        let expected = arr.conv(&kernel, conv_mode, padding_mode).unwrap();
+        let tiles = arr
+            .conv_tiles(&kernel, [3, 4], conv_mode, padding_mode)
+            .unwrap();
+        assert_eq!(tiles.output_shape(), [expected.nrows(), expected.ncols()]);
+        assert_eq!(
+            tiles.len(),
+            expected.nrows().div_ceil(3) * expected.ncols().div_ceil(4)
+        );
+
+        let mut ret = Array2::zeros(expected.dim());
+        tiles.for_each(|([i, j], tile)| {
+            assert!(tile.nrows() <= 3 && tile.ncols() <= 4);
+            ret.slice_mut(s![i..i + tile.nrows(), j..j + tile.ncols()])
+                .assign(&tile);
+        });
+        assert_eq!(ret, expected);
+    }
+
+    // the center tile alone
+    let expected = arr
+        .conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+        .unwrap();
+    let (origin, tile) = arr
+        .conv_tiles(&kernel, [3, 3], ConvMode::Same, PaddingMode::Zeros)
+        .unwrap()
+        .nth(4)
+        .unwrap();
+    assert_eq!(origin, [3, 3]);
+    assert_eq!(tile, expected.slice(s![3..6, 3..6]));
+
+    assert!(arr
+        .conv_tiles(&kernel, [0, 3], ConvMode::Same, PaddingMode::Zeros)
+        .is_err());
+}
+
+#[test]
+fn conv_roi() {
+    let arr = Array2::from_shape_fn((12, 15), |(i, j)| (i * 15 + j) as i32 % 17 - 8);
+    let kernel = array![[2, 0, -1], [1, 3, 0], [0, -2, 1]];
+
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Same, PaddingMode::Reflect),
+        (ConvMode::Full, PaddingMode::Const(3)),
+        (ConvMode::Circular, PaddingMode::Zeros),
+        // padding past the input, read from the padded copy
+        (
+            ConvMode::Custom {
+                padding: [13, 2],
+                strides: [2, 1],
+            },
+            PaddingMode::Symmetric,
+        ),
+    ] {
+        let expected = arr
+            .conv(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+            .unwrap();
+        let (rows, cols) = expected.dim();
+        // at the corners, in the middle, one row and the whole output
+        for roi in [
+            [(0, 3), (0, 4)],
+            [(rows - 2, rows), (cols - 5, cols)],
+            [(4, 9), (3, 10)],
+            [(5, 6), (0, cols)],
+            [(0, rows), (0, cols)],
+        ] {
+            assert_eq!(
+                arr.conv_roi(kernel.with_dilation([1, 2]), roi, conv_mode, padding_mode)
+                    .unwrap(),
+                expected.slice(s![roi[0].0..roi[0].1, roi[1].0..roi[1].1])
+            );
+        }
+
+        assert!(arr
+            .conv_roi(&kernel, [(0, rows + 3), (0, 1)], conv_mode, padding_mode)
+            .is_err());
+    }
+    assert!(arr
+        .conv_roi(
+            &kernel,
+            [(3, 2), (0, 1)],
+            ConvMode::Same,
+            PaddingMode::Zeros
+        )
+        .is_err());
+}
+
+#[test]
+fn conv_with_progress() {
+    // 3 slabs of 218 rows
+    let arr = Array2::from_shape_fn((600, 300), |(i, j)| (i * 300 + j) as i32 % 13 - 6);
+    let kernel = array![[1, 0, -1], [2, 1, 0]];
+    let expected = arr
+        .conv(&kernel, ConvMode::Same, PaddingMode::Replicate)
+        .unwrap();
+
+    let mut calls = Vec::new();
+    let ret = arr
+        .conv_with_progress(
+            &kernel,
+            ConvMode::Same,
+            PaddingMode::Replicate,
+            |done, total| {
+                calls.push((done, total));
+                ControlFlow::Continue(())
+            },
+        )
+        .unwrap();
+    assert_eq!(ret, expected);
+    assert_eq!(
+        calls,
+        [(218 * 300, 180000), (436 * 300, 180000), (180000, 180000)]
+    );
+
+    // the slabs at the borders are padded like the whole input, strides and dilation
+    // included
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Full, PaddingMode::Reflect),
+        (
+            ConvMode::Custom {
+                padding: [5, 1],
+                strides: [3, 2],
+            },
+            PaddingMode::Circular,
+        ),
+        (ConvMode::Valid, PaddingMode::Zeros),
+    ] {
+        let expected = arr
+            .conv(kernel.with_dilation([3, 1]), conv_mode, padding_mode)
+            .unwrap();
+        let ret = arr
+            .conv_with_progress(
+                kernel.with_dilation([3, 1]),
+                conv_mode,
+                padding_mode,
+                |_, _| ControlFlow::Continue(()),
+            )
+            .unwrap();
+        assert_eq!(ret, expected);
+    }
+
+    let mut calls = 0;
+    let ret = arr.conv_with_progress(&kernel, ConvMode::Same, PaddingMode::Replicate, |_, _| {
+        calls += 1;
+        ControlFlow::Break(())
+    });
+    assert!(matches!(ret, Err(crate::Error::Cancelled)));
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn tiled() {
+    // a 9x9 kernel over outputs wider than a tile, through the virtual padding
+    // and the padded copy
+    let arr = Array2::from_shape_fn((70, 600), |(i, j)| ((i * 600 + j) * 7 % 23) as i64 - 11);
+    let kernel = Array2::from_shape_fn((9, 9), |(i, j)| ((i * 9 + j) % 5) as i64 - 2);
+
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Same, PaddingMode::Reflect),
+        (
+            ConvMode::Custom {
+                padding: [40, 300],
+                strides: [1, 1],
+            },
+            PaddingMode::Const(3),
+        ),
+        (
+            ConvMode::Custom {
+                padding: [60, 600],
+                strides: [2, 1],
+            },
+            PaddingMode::Zeros,
+        ),
+    ] {
+        let expected = arr
+            .conv_generic(
+                &kernel,
+                conv_mode,
+                padding_mode,
+                |x, k| x * k,
+                |a, b| a + b,
+                0,
+            )
+            .unwrap();
+        assert_eq!(
+            arr.conv(&kernel, conv_mode, padding_mode).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn slice_kernels() {
+    let arr = array![1., 4., 2., 8., 5., 7.];
+    let expected = arr
+        .conv(
+            &array![0.25, 0.5, 0.25],
+            ConvMode::Same,
+            PaddingMode::Reflect,
+        )
+        .unwrap();
+
+    let slice: &[f64] = &[0.25, 0.5, 0.25];
+    assert_eq!(
+        arr.conv(slice, ConvMode::Same, PaddingMode::Reflect)
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        arr.conv(&[0.25, 0.5, 0.25], ConvMode::Same, PaddingMode::Reflect)
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        arr.conv(vec![0.25, 0.5, 0.25], ConvMode::Same, PaddingMode::Reflect)
+            .unwrap(),
+        expected
+    );
+    assert_close(
+        &arr.conv_fft([0.25, 0.5, 0.25], ConvMode::Same, PaddingMode::Reflect)
+            .unwrap(),
+        &expected,
+        1e-9,
+    );
+}
+
+#[test]
+fn checked() {
+    let arr = Array2::from_shape_fn((7, 8), |(i, j)| (i * 8 + j) as i32 % 11 - 5);
+    let kernel = array![[1, 0, -2], [3, 1, 0]];
+
+    for input in [arr.view(), arr.slice(s![..;-1, 1..;2]), arr.t()] {
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Same, PaddingMode::Symmetric),
+            (ConvMode::Full, PaddingMode::Const(4)),
+            (
+                ConvMode::Custom {
+                    padding: [3, 1],
+                    strides: [2, 3],
+                },
+                PaddingMode::Circular,
+            ),
+        ] {
+            let kwd = kernel.with_dilation([2, 1]).with_origin([1, 0]);
+            let (cm, _, output_shape) = explicit(&input, &kwd, conv_mode).unwrap();
+
+            let mut ret = Array::zeros(output_shape);
+            checked::conv(&input, &kwd, &cm, padding_mode, &mut ret.view_mut());
+            assert_eq!(
+                ret,
+                input
+                    .conv(
+                        kernel.with_dilation([2, 1]).with_origin([1, 0]),
+                        conv_mode,
+                        padding_mode
+                    )
+                    .unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn set_checked() {
+    let arr = Array2::from_shape_fn((9, 10), |(i, j)| (i * 10 + j) as i64 % 13 - 6);
+    let kernel = array![[1, 0, -2], [3, 1, 0]];
+
+    // every entry point, with a padding larger than the input for the padded copies
+    let run = |conv_mode: ConvMode<2>, padding_mode: PaddingMode<2, i64>| {
+        let kwd = || kernel.with_dilation([2, 1]);
+        let ret = arr.conv(kwd(), conv_mode, padding_mode).unwrap();
+        let at = arr
+            .conv_at(kwd(), &[[0, 0], [2, 3], [4, 1]], conv_mode, padding_mode)
+            .unwrap();
+        let roi = arr
+            .conv_roi(kwd(), [(1, 4), (0, 3)], conv_mode, padding_mode)
+            .unwrap();
+        let tiles: Vec<_> = arr
+            .conv_tiles(kwd(), [2, 3], conv_mode, padding_mode)
+            .unwrap()
+            .collect();
+        let mut into = Array::zeros(ret.raw_dim());
+        let into = arr
+            .conv_into(kwd(), conv_mode, padding_mode, &mut into)
+            .map(|_| into);
+        let progress = arr
+            .conv_with_progress(kwd(), conv_mode, padding_mode, |_, _| {
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        let generic = arr
+            .conv_generic(
+                kwd(),
+                conv_mode,
+                padding_mode,
+                |x, k| x * k,
+                |a, b| a + b,
+                0,
+            )
+            .unwrap();
+        (ret, generic, at, roi, tiles, into.ok(), progress)
+    };
+
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Same, PaddingMode::Symmetric),
+        (ConvMode::Full, PaddingMode::Const(4)),
+        (
+            ConvMode::Custom {
+                padding: [12, 1],
+                strides: [2, 3],
+            },
+            PaddingMode::Reflect,
+        ),
+    ] {
+        // the results are the same either way, so tests running meanwhile are not affected
+        crate::set_checked(false);
+        assert!(!checked::enabled());
+        let expected = run(conv_mode, padding_mode);
+        crate::set_checked(true);
+        assert!(checked::enabled());
+        let ret = run(conv_mode, padding_mode);
+        assert_eq!(ret, expected);
+    }
+    crate::set_checked(false);
+}
+
+#[test]
+fn validation() {
+    let arr = Array2::<f64>::ones((3, 4));
+    let kernel = Array2::<f64>::ones((2, 3));
+
+    let res = arr.conv(
+        &kernel,
+        ConvMode::Custom {
+            padding: [0, 0],
+            strides: [1, 0],
+        },
+        PaddingMode::Zeros,
+    );
+    assert!(matches!(res, Err(crate::Error::ZeroStride(1))));
+
+    let res = arr.conv_fft(
+        kernel.with_dilation([0, 1]),
+        ConvMode::Same,
+        PaddingMode::Zeros,
+    );
+    assert!(matches!(res, Err(crate::Error::ZeroDilation(0))));
+
+    // (2 - 1) * 3 + 1 = 4 rows of kernel over 3 rows of input
+    let res = arr.conv(
+        kernel.with_dilation([3, 1]),
+        ConvMode::Valid,
+        PaddingMode::Zeros,
+    );
+    assert!(matches!(res, Err(crate::Error::KernelTooLarge(0, 4, 3))));
+
+    // reported on the caller's axes for column major inputs too
+    let res = arr.t().conv(
+        kernel.with_dilation([1, 4]),
+        ConvMode::Valid,
+        PaddingMode::Zeros,
+    );
+    assert!(matches!(res, Err(crate::Error::KernelTooLarge(1, 9, 3))));
+
+    let res = Array2::<f64>::ones((0, 4)).conv(&kernel, ConvMode::Same, PaddingMode::Zeros);
+    assert!(matches!(res, Err(crate::Error::DataShape(_))));
+    let res = arr.conv(
+        &Array2::<f64>::ones((2, 0)),
+        ConvMode::Same,
+        PaddingMode::Zeros,
+    );
+    assert!(matches!(res, Err(crate::Error::KernelShape(_))));
+}
+
+#[test]
+fn conv_into() {
+    let arr = Array::from_shape_fn((7, 9), |(i, j)| ((i * 9 + j) % 11) as i32 - 5);
+    let kernel = array![[1, -2, 0], [3, 0, 4]];
+
+    for (conv_mode, padding_mode) in [
+        (ConvMode::Same, PaddingMode::Reflect),
+        (ConvMode::Full, PaddingMode::Const(3)),
+        (ConvMode::Valid, PaddingMode::Zeros),
+        (
+            ConvMode::Custom {
+                padding: [2, 1],
+                strides: [2, 3],
+            },
+            PaddingMode::Circular,
+        ),
+    ] {
+        let expected = arr
+            .conv(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+            .unwrap();
+        let mut output = Array::zeros(expected.raw_dim());
+        arr.conv_into(
+            kernel.with_dilation([1, 2]),
+            conv_mode,
+            padding_mode,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(output, expected);
+    }
+
+    // into a view of a larger buffer
+    let mut buffer = Array2::zeros((9, 11));
+    arr.conv_into(
+        &kernel,
+        ConvMode::Same,
+        PaddingMode::Replicate,
+        &mut buffer.slice_mut(s![1..8, 1..10]),
+    )
+    .unwrap();
+    assert_eq!(
+        buffer.slice(s![1..8, 1..10]),
+        arr.conv(&kernel, ConvMode::Same, PaddingMode::Replicate)
+            .unwrap()
+    );
+
+    let res = arr.conv_into(
+        &kernel,
+        ConvMode::Same,
+        PaddingMode::Zeros,
+        &mut Array2::zeros((7, 8)),
+    );
+    assert!(matches!(res, Err(crate::Error::InvalidParameter(_))));
+}
@@ -0,0 +1,35 @@
+use ndarray::{ArrayViewMut, Dim, Dimension, IntoDimension, Ix};
+
+// outputs per tile: the (32 + kernel rows) input rows of (256 + kernel cols) elements
+// under a tile stay in L2 while it is computed
+const TILE: [usize; 2] = [32, 256];
+
+// large kernels over outputs wider than a tile, where a row by row sweep rereads the
+// input rows of every output row from memory
+pub(super) fn preferred<const N: usize>(taps: usize, output_shape: [usize; N]) -> bool {
+    N == 2 && taps >= 81 && output_shape[1] > TILE[1]
+}
+
+// every output of a 2D `ret` from its index, tile by tile. the tiles are independent
+// work units, and each output is computed alone so the result doesn't depend on the order
+pub(super) fn fill<T, const N: usize>(
+    ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+    output: impl Fn([usize; N]) -> T,
+) where
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    debug_assert_eq!(N, 2);
+    let (rows, cols) = (ret.shape()[0], ret.shape()[1]);
+
+    for r in (0..rows).step_by(TILE[0]) {
+        for c in (0..cols).step_by(TILE[1]) {
+            for i in r..(r + TILE[0]).min(rows) {
+                for j in c..(c + TILE[1]).min(cols) {
+                    let index = core::array::from_fn(|a| [i, j][a]);
+                    ret[index.into_dimension()] = output(index);
+                }
+            }
+        }
+    }
+}
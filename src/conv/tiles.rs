@@ -0,0 +1,89 @@
+use alloc::boxed::Box;
+
+use ndarray::{Array, Dim, Dimension, IntoDimension, Ix};
+
+/// tiles of a `conv` output, each computed when the iterator yields it.
+/// see `ConvExt::conv_tiles`.
+pub struct ConvTiles<'a, T, const N: usize> {
+    output: Box<dyn Fn([usize; N]) -> T + 'a>,
+    output_shape: [usize; N],
+    tile_shape: [usize; N],
+    tile_counts: [usize; N],
+    next: usize,
+    len: usize,
+}
+
+impl<'a, T, const N: usize> ConvTiles<'a, T, N> {
+    pub(super) fn new(
+        output: Box<dyn Fn([usize; N]) -> T + 'a>,
+        output_shape: [usize; N],
+        tile_shape: [usize; N],
+    ) -> Self {
+        let tile_counts = core::array::from_fn(|i| output_shape[i].div_ceil(tile_shape[i]));
+
+        Self {
+            output,
+            output_shape,
+            tile_shape,
+            tile_counts,
+            next: 0,
+            len: tile_counts.iter().product(),
+        }
+    }
+
+    /// shape of the whole output.
+    pub fn output_shape(&self) -> [usize; N] {
+        self.output_shape
+    }
+}
+
+impl<T, const N: usize> Iterator for ConvTiles<'_, T, N>
+where
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    type Item = ([usize; N], Array<T, Dim<[Ix; N]>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.len {
+            return None;
+        }
+
+        // the tile index in row major order
+        let mut rest = self.next;
+        let mut origin = [0; N];
+        for i in (0..N).rev() {
+            origin[i] = rest % self.tile_counts[i] * self.tile_shape[i];
+            rest /= self.tile_counts[i];
+        }
+        self.next += 1;
+
+        // tiles at the far edges are cut to the output
+        let shape: [usize; N] =
+            core::array::from_fn(|i| self.tile_shape[i].min(self.output_shape[i] - origin[i]));
+        let tile = Array::from_shape_fn(shape, |index| {
+            let index = index.into_dimension();
+            (self.output)(core::array::from_fn(|i| origin[i] + index[i]))
+        });
+
+        Some((origin, tile))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len - self.next;
+        (len, Some(len))
+    }
+
+    // skipped tiles are never computed
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.next = self.next.saturating_add(n).min(self.len);
+        self.next()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for ConvTiles<'_, T, N>
+where
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+}
@@ -0,0 +1,306 @@
+use alloc::vec::Vec;
+
+use ndarray::{ArrayBase, ArrayViewMut, Data, DataMut, Dim, Dimension, IntoDimension, Ix};
+use num::traits::NumAssign;
+
+use super::ExplicitConv;
+use crate::{dilation::KernelWithDilation, BorderType, PaddingMode};
+
+// the padded path is faster when most windows cross the border
+pub(super) fn preferred<const N: usize>(
+    input_dim: [usize; N],
+    kernel_dim: [usize; N],
+    cm: &ExplicitConv<N>,
+    output_shape: [usize; N],
+) -> bool {
+    let interior = (0..N)
+        .map(|i| {
+            interior(
+                output_shape[i],
+                cm.strides[i],
+                cm.padding[i][0],
+                kernel_dim[i],
+                input_dim[i],
+            )
+        })
+        .product::<usize>();
+
+    cm.padding == [[0; 2]; N] || interior * 2 >= output_shape.iter().product::<usize>()
+}
+
+// conv into ret without the padded copy: windows inside the input read it through its
+// own strides, windows over the border map every tap back into the input.
+// returns false when the padding is too large for the index mapping of its border.
+pub(super) fn conv<T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    padding_mode: PaddingMode<N, T>,
+    ret: &mut ArrayViewMut<T, Dim<[Ix; N]>>,
+) -> bool
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let output = match output(data, kwd, cm, padding_mode) {
+        Some(output) => output,
+        None => return false,
+    };
+
+    let output_shape: [usize; N] = core::array::from_fn(|i| ret.shape()[i]);
+    if super::tiled::preferred(kwd.kernel.len(), output_shape) {
+        super::tiled::fill(ret, output);
+        return true;
+    }
+
+    ret.indexed_iter_mut().for_each(|(index, out)| {
+        let index = index.into_dimension();
+        *out = output(core::array::from_fn(|i| index[i]));
+    });
+    true
+}
+
+// the output at an index, computed like conv
+pub(super) fn output<'d, T, S, SK, const N: usize>(
+    data: &'d ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    padding_mode: PaddingMode<N, T>,
+) -> Option<impl Fn([usize; N]) -> T + 'd>
+where
+    T: NumAssign + Copy + 'd,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let input_dim: [usize; N] = core::array::from_fn(|i| data.shape()[i]);
+    let kernel_dim: [usize; N] =
+        core::array::from_fn(|i| kwd.kernel.shape()[i] * kwd.dilation[i] - kwd.dilation[i] + 1);
+
+    let borders = padding_mode.borders();
+    if !fits_all(&borders, cm, input_dim) {
+        return None;
+    }
+
+    let data_strides = data.strides();
+    let taps: Vec<([usize; N], isize, T)> = kwd
+        .kernel
+        .indexed_iter()
+        .filter(|(_, v)| kwd.zero_taps || **v != T::zero())
+        .map(|(index, &v)| {
+            let index = index.into_dimension();
+            let pos: [usize; N] = core::array::from_fn(|i| index[i] * kwd.dilation[i]);
+            let offset = (0..N).map(|i| pos[i] as isize * data_strides[i]).sum();
+            (pos, offset, v)
+        })
+        .collect();
+
+    let data_strides: [isize; N] = core::array::from_fn(|i| data_strides[i]);
+    let padding = cm.padding;
+    let strides = cm.strides;
+
+    Some(move |index: [usize; N]| {
+        let ptr = data.as_ptr();
+        // first element of the window in the input coordinates, can be negative
+        let start: [isize; N] =
+            core::array::from_fn(|i| (index[i] * strides[i]) as isize - padding[i][0] as isize);
+
+        let inside =
+            (0..N).all(|i| start[i] >= 0 && start[i] as usize + kernel_dim[i] <= input_dim[i]);
+
+        let mut sum = T::zero();
+        if inside {
+            let origin = (0..N).map(|i| start[i] * data_strides[i]).sum::<isize>();
+            taps.iter()
+                .for_each(|(_, offset, v)| sum += unsafe { *ptr.offset(origin + offset) } * *v);
+        } else {
+            taps.iter().for_each(|(pos, _, v)| {
+                // axes are padded in order, so the constant of the last axis wins
+                let mut constant = None;
+                let mut offset = 0;
+                for i in 0..N {
+                    match map(start[i] + pos[i] as isize, input_dim[i], borders[i]) {
+                        Ok(j) => offset += j as isize * data_strides[i],
+                        Err(c) => constant = Some(c),
+                    }
+                }
+                sum += constant.unwrap_or_else(|| unsafe { *ptr.offset(offset) }) * *v;
+            });
+        }
+        sum
+    })
+}
+
+// the padding is small enough for the index mapping of its border
+pub(super) fn padding_fits<T: NumAssign + Copy, const N: usize>(
+    padding_mode: PaddingMode<N, T>,
+    cm: &ExplicitConv<N>,
+    input_dim: [usize; N],
+) -> bool {
+    fits_all(&padding_mode.borders(), cm, input_dim)
+}
+
+// conv written into output without allocating, the taps are read from the kernel on the fly.
+// the padding must fit, see padding_fits.
+pub(super) fn conv_into<T, S, SK, SO, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<SK, N>,
+    cm: &ExplicitConv<N>,
+    padding_mode: PaddingMode<N, T>,
+    output: &mut ArrayBase<SO, Dim<[Ix; N]>>,
+) where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    SO: DataMut<Elem = T>,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let input_dim: [usize; N] = core::array::from_fn(|i| data.shape()[i]);
+    let borders = padding_mode.borders();
+    let ptr = data.as_ptr();
+    let data_strides = data.strides();
+
+    output.indexed_iter_mut().for_each(|(index, out)| {
+        let index = index.into_dimension();
+        let mut sum = T::zero();
+        for (k, &v) in kwd.kernel.indexed_iter() {
+            if !kwd.zero_taps && v == T::zero() {
+                continue;
+            }
+            let k = k.into_dimension();
+
+            // axes are padded in order, so the constant of the last axis wins
+            let mut constant = None;
+            let mut offset = 0;
+            for i in 0..N {
+                let x = (index[i] * cm.strides[i] + k[i] * kwd.dilation[i]) as isize
+                    - cm.padding[i][0] as isize;
+                match map(x, input_dim[i], borders[i]) {
+                    Ok(j) => offset += j as isize * data_strides[i],
+                    Err(c) => constant = Some(c),
+                }
+            }
+            sum += constant.unwrap_or_else(|| unsafe { *ptr.offset(offset) }) * v;
+        }
+        *out = sum;
+    });
+}
+
+fn fits_all<T: NumAssign + Copy, const N: usize>(
+    borders: &[[BorderType<T>; 2]; N],
+    cm: &ExplicitConv<N>,
+    input_dim: [usize; N],
+) -> bool {
+    (0..N).all(|i| {
+        fits(borders[i][0], cm.padding[i][0], input_dim[i])
+            && fits(borders[i][1], cm.padding[i][1], input_dim[i])
+    })
+}
+
+// the padding reflects the input once at most, wrapping can go around several times
+fn fits<T: NumAssign + Copy>(border: BorderType<T>, padding: usize, n: usize) -> bool {
+    match border {
+        BorderType::Zeros | BorderType::Const(_) | BorderType::Replicate => true,
+        BorderType::Reflect => padding < n,
+        BorderType::Symmetric => padding <= n,
+        BorderType::Circular => true,
+    }
+}
+
+// index into the input of the coordinate x of an axis of length n,
+// or the constant of the border
+fn map<T: NumAssign + Copy>(x: isize, n: usize, borders: [BorderType<T>; 2]) -> Result<usize, T> {
+    let n = n as isize;
+    let border = match x {
+        x if x < 0 => borders[0],
+        x if x >= n => borders[1],
+        x => return Ok(x as usize),
+    };
+
+    let before = x < 0;
+    Ok(match border {
+        BorderType::Zeros => return Err(T::zero()),
+        BorderType::Const(c) => return Err(c),
+        BorderType::Replicate => x.clamp(0, n - 1),
+        BorderType::Reflect if before => -x,
+        BorderType::Reflect => 2 * (n - 1) - x,
+        BorderType::Symmetric if before => -x - 1,
+        BorderType::Symmetric => 2 * n - 1 - x,
+        BorderType::Circular => x.rem_euclid(n),
+    } as usize)
+}
+
+// number of outputs along an axis whose window lies inside the input
+fn interior(output: usize, stride: usize, before: usize, kernel: usize, n: usize) -> usize {
+    (0..output)
+        .filter(|o| {
+            let start = o * stride;
+            start >= before && start - before + kernel <= n
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array};
+
+    use super::*;
+    use crate::{dilation::WithDilation, window::Windows, ConvMode};
+
+    #[test]
+    fn same_as_padded() {
+        let arr = Array::from_shape_fn((9, 12), |(i, j)| ((i * 12 + j) % 17) as i32 - 8);
+        let kernel = array![[1, 0, 2], [-3, 4, 5]];
+        let kwd = kernel.with_dilation([2, 1]);
+
+        for padding_mode in [
+            PaddingMode::Zeros,
+            PaddingMode::Const(7),
+            PaddingMode::Reflect,
+            PaddingMode::Symmetric,
+            PaddingMode::Replicate,
+            PaddingMode::Circular,
+            PaddingMode::Explicit([
+                [BorderType::Const(-2), BorderType::Reflect],
+                [BorderType::Circular, BorderType::Const(3)],
+            ]),
+            PaddingMode::Custom([BorderType::Replicate, BorderType::Const(5)]),
+        ] {
+            for padding in [[[2, 1], [1, 3]], [[0, 0], [0, 0]], [[3, 3], [0, 2]]] {
+                let cm = ExplicitConv {
+                    padding,
+                    strides: [1, 2],
+                };
+                let windows = Windows::new(&arr, [3, 3], &cm, padding_mode).unwrap();
+                let expected = windows.map([2, 3], [2, 1], |w| (&w * &kernel).sum());
+
+                let mut res = Array::zeros(windows.output_shape());
+                assert!(conv(&arr, &kwd, &cm, padding_mode, &mut res.view_mut()));
+                assert_eq!(res, expected);
+            }
+        }
+
+        // windows mostly over the border, and a reflection past the input
+        let cm = ConvMode::Full.unfold_with_dim([3, 3], [2, 2]).unwrap();
+        assert!(!preferred([2, 2], [3, 3], &cm, [4, 4]));
+        let cm = ConvMode::Same.unfold_with_dim([3, 3], [9, 12]).unwrap();
+        assert!(preferred([9, 12], [3, 3], &cm, [9, 12]));
+        let cm = ExplicitConv {
+            padding: [[9, 0], [0, 0]],
+            strides: [1, 1],
+        };
+        let mut res = Array::zeros([16, 10]);
+        assert!(!conv(
+            &arr,
+            &kwd,
+            &cm,
+            PaddingMode::Reflect,
+            &mut res.view_mut()
+        ));
+    }
+}
@@ -236,9 +236,88 @@ impl<T: FftNum> Processor<T> {
     }
 }
 
+/// FFT provider of `conv_fft` and `hilbert`, real to complex over all the axes and back.
+/// the layout of the spectrum is up to the backend: `conv_fft` only multiplies two
+/// spectra of equally shaped inputs elementwise, then transforms the product back to
+/// `shape`, the shape of those inputs. `backward` includes the `1 / len` scaling.
+pub trait FftBackend<T: FftNum> {
+    fn forward<const N: usize>(
+        &mut self,
+        input: &mut Array<T, Dim<[Ix; N]>>,
+    ) -> Array<Complex<T>, Dim<[Ix; N]>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>;
+
+    fn backward<const N: usize>(
+        &mut self,
+        input: Array<Complex<T>, Dim<[Ix; N]>>,
+        shape: [usize; N],
+    ) -> Array<T, Dim<[Ix; N]>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>;
+
+    /// complex to complex transform of a 1D buffer in place, the bins in the usual order
+    /// of dc, the positive then the negative frequencies. `inverse` includes the `1 / len`
+    /// scaling. rustfft unless the backend has its own.
+    fn complex(&mut self, buffer: &mut [Complex<T>], inverse: bool) {
+        complex(&mut rustfft::FftPlanner::new(), buffer, inverse);
+    }
+}
+
+// the default backend, rustfft with realfft on the last axis
+impl<T: FftNum> FftBackend<T> for Processor<T> {
+    fn forward<const N: usize>(
+        &mut self,
+        input: &mut Array<T, Dim<[Ix; N]>>,
+    ) -> Array<Complex<T>, Dim<[Ix; N]>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    {
+        Processor::forward(self, input)
+    }
+
+    fn backward<const N: usize>(
+        &mut self,
+        input: Array<Complex<T>, Dim<[Ix; N]>>,
+        shape: [usize; N],
+    ) -> Array<T, Dim<[Ix; N]>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    {
+        // the half spectrum doesn't tell an even last axis from the odd one below it
+        self.rp_origin_len = shape[N - 1];
+        Processor::backward(self, input)
+    }
+
+    fn complex(&mut self, buffer: &mut [Complex<T>], inverse: bool) {
+        complex(&mut self.cp, buffer, inverse);
+    }
+}
+
+fn complex<T: FftNum>(
+    planner: &mut rustfft::FftPlanner<T>,
+    buffer: &mut [Complex<T>],
+    inverse: bool,
+) {
+    let n = buffer.len();
+    if !inverse {
+        planner.plan_fft_forward(n).process(buffer);
+        return;
+    }
+
+    planner.plan_fft_inverse(n).process(buffer);
+    let len = T::from_usize(n).unwrap();
+    buffer.iter_mut().for_each(|x| *x = *x / len);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::reference::assert_close;
     use ndarray::{array, Axis};
 
     #[test]
@@ -323,6 +402,20 @@ mod tests {
         dbg!(&a);
     }
 
+    #[test]
+    fn half_spectrum() {
+        // real inputs go through r2c on the last axis, so only n / 2 + 1 bins are kept
+        // and the remaining axes are transformed on the halved spectrum
+        let a = Array::from_shape_fn((4, 6, 10), |(i, j, k)| (i * 60 + j * 10 + k) as f64);
+        let mut p = Processor::default();
+
+        let a_fft = p.forward(&mut a.clone());
+        assert_eq!(a_fft.len(), 4 * 6 * (10 / 2 + 1));
+
+        let b = p.backward(a_fft);
+        assert_close(&b, &a, 1e-9);
+    }
+
     #[test]
     fn test_forward_backward_complex() {
         let mut arr = array![[1, 2, 3, 4], [1, 2, 3, 4], [1, 2, 3, 4], [1, 2, 3, 4],]
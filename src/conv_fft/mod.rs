@@ -7,13 +7,19 @@ use ndarray::{
 use num::{traits::NumAssign, Complex};
 use rustfft::FftNum;
 
-use crate::{conv::ExplicitConv, dilation::IntoKernelWithDilation, ConvMode, PaddingMode};
+use crate::{
+    conv::ExplicitConv,
+    dilation::{dilated, IntoKernelWithDilation, KernelWithDilation},
+    ConvMode, PaddingMode,
+};
 
 mod fft;
 mod good_size;
 mod padding;
+mod taper;
 
-pub use fft::Processor;
+pub use fft::{FftBackend, Processor};
+pub use taper::Taper;
 
 pub struct Baked<T, SK, const N: usize>
 where
@@ -44,12 +50,22 @@ where
         padding_mode: PaddingMode<N, T>,
     ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
 
+    /// `conv_fft` with a reusable `Processor`, or any other `FftBackend`.
     fn conv_fft_with_processor(
         &self,
         kernel: impl IntoKernelWithDilation<'a, SK, N>,
         conv_mode: ConvMode<N>,
         padding_mode: PaddingMode<N, T>,
-        fft_processor: &mut Processor<T>,
+        fft_processor: &mut impl FftBackend<T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// `conv_fft` of the input with its borders tapered first, see `Taper`.
+    fn conv_fft_tapered(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        taper: Taper,
     ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
 
     // fn conv_fft_bake(
@@ -175,12 +191,72 @@ where
         kernel: impl IntoKernelWithDilation<'a, SK, N>,
         conv_mode: ConvMode<N>,
         padding_mode: PaddingMode<N, T>,
-        fft_processor: &mut Processor<T>,
+        fft_processor: &mut impl FftBackend<T>,
     ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
-        let kwd = kernel.into_kernel_with_dilation();
+        execute(
+            self,
+            kernel.into_kernel_with_dilation(),
+            conv_mode,
+            padding_mode,
+            fft_processor,
+        )
+    }
 
-        let data_raw_dim = self.raw_dim();
+    fn conv_fft_tapered(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        taper: Taper,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
         if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+        if kwd.kernel.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(kwd.kernel.raw_dim()));
+        }
+
+        let mut p = Processor::default();
+        let tapered = taper::apply(self, &kwd, taper, &mut p)?;
+        execute(&tapered, kwd, conv_mode, padding_mode, &mut p)
+    }
+}
+
+fn execute<'a, T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: KernelWithDilation<'a, SK, N>,
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+    fft_processor: &mut impl FftBackend<T>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + Debug + FftNum,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T> + 'a,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    crate::trace::span!(
+        "conv_fft",
+        input = ?data.shape(),
+        kernel = ?kwd.kernel.shape(),
+        dilation = ?kwd.dilation,
+        conv_mode = ?conv_mode,
+        padding_mode = ?padding_mode,
+    );
+
+    if let ConvMode::Circular = conv_mode {
+        return circular(data, &kwd, fft_processor);
+    }
+
+    let (cm, kernel_raw_dim_with_dilation, pds_raw_dim, fft_size) = {
+        crate::trace::span!("plan");
+
+        let data_raw_dim = data.raw_dim();
+        if data.shape().iter().product::<usize>() == 0 {
             return Err(crate::Error::DataShape(data_raw_dim));
         }
 
@@ -189,10 +265,10 @@ where
             return Err(crate::Error::DataShape(kernel_raw_dim));
         }
 
-        let kernel_raw_dim_with_dilation: [usize; N] =
-            std::array::from_fn(|i| kernel_raw_dim[i] * kwd.dilation[i] - kwd.dilation[i] + 1);
+        let kernel_raw_dim_with_dilation =
+            dilated(std::array::from_fn(|i| kernel_raw_dim[i]), kwd.dilation)?;
 
-        let cm = conv_mode.unfold(&kwd);
+        let cm = conv_mode.unfold(&kwd, std::array::from_fn(|i| data_raw_dim[i]))?;
 
         let pds_raw_dim: [usize; N] =
             std::array::from_fn(|i| (data_raw_dim[i] + cm.padding[i][0] + cm.padding[i][1]));
@@ -207,35 +283,81 @@ where
             pds_raw_dim[i].max(kernel_raw_dim_with_dilation[i])
         }));
 
-        let mut data_pd = padding::data(self, padding_mode, cm.padding, fft_size);
-        let mut kernel_pd = padding::kernel(kwd, fft_size);
+        (cm, kernel_raw_dim_with_dilation, pds_raw_dim, fft_size)
+    };
 
-        let mut data_pd_fft = fft_processor.forward(&mut data_pd);
-        let kernel_pd_fft = fft_processor.forward(&mut kernel_pd);
+    let (mut data_pd, mut kernel_pd) = {
+        crate::trace::span!("padding", padding = ?cm.padding, fft_size = ?fft_size);
+        (
+            padding::data(data, padding_mode, cm.padding, fft_size),
+            padding::kernel(kwd, fft_size),
+        )
+    };
 
-        data_pd_fft.zip_mut_with(&kernel_pd_fft, |d, k| *d *= *k);
-        // let mul_spec = data_pd_fft * kernel_pd_fft;
+    crate::trace::span!("execute", algorithm = "fft", fft_size = ?fft_size);
+    let mut data_pd_fft = fft_processor.forward(&mut data_pd);
+    let kernel_pd_fft = fft_processor.forward(&mut kernel_pd);
 
-        let output = fft_processor.backward(data_pd_fft);
+    data_pd_fft.zip_mut_with(&kernel_pd_fft, |d, k| *d *= *k);
+    // let mul_spec = data_pd_fft * kernel_pd_fft;
 
-        let output = output.slice_move(unsafe {
-            SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
-                start: kernel_raw_dim_with_dilation[i] as isize - 1,
-                end: Some((pds_raw_dim[i]) as isize),
-                step: cm.strides[i] as isize,
-            }))
-            .unwrap()
+    let output = fft_processor.backward(data_pd_fft, fft_size);
+
+    let output = output.slice_move(unsafe {
+        SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
+            start: kernel_raw_dim_with_dilation[i] as isize - 1,
+            end: Some((pds_raw_dim[i]) as isize),
+            step: cm.strides[i] as isize,
+        }))
+        .unwrap()
+    });
+
+    Ok(output)
+}
+
+// circular conv at the size of the input: the kernel is folded onto the input's shape
+// around its center, so the product of the spectra wraps by itself without any padding
+fn circular<'a, T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<'a, SK, N>,
+    fft_processor: &mut impl FftBackend<T>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + FftNum,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let input_dim: [usize; N] = std::array::from_fn(|i| data.shape()[i]);
+    let cm = ConvMode::Circular.unfold(kwd, input_dim)?;
+
+    // the tap k reads the input at i + k * dilation - before, which is the convolution
+    // with the kernel moved to before - k * dilation
+    let mut kernel = Array::zeros(input_dim);
+    kwd.kernel.indexed_iter().for_each(|(k, &v)| {
+        let k = k.into_dimension();
+        let index: [usize; N] = std::array::from_fn(|i| {
+            (cm.padding[i][0] as isize - (k[i] * kwd.dilation[i]) as isize)
+                .rem_euclid(input_dim[i] as isize) as usize
         });
+        kernel[index.into_dimension()] += v;
+    });
 
-        Ok(output)
-    }
+    crate::trace::span!("execute", algorithm = "fft", fft_size = ?input_dim);
+    let mut data = data.as_standard_layout().into_owned();
+    let mut data_fft = fft_processor.forward(&mut data);
+    let kernel_fft = fft_processor.forward(&mut kernel);
+    data_fft.zip_mut_with(&kernel_fft, |d, k| *d *= *k);
+
+    Ok(fft_processor.backward(data_fft, input_dim))
 }
 
 #[cfg(test)]
 mod tests {
-    use ndarray::array;
+    use ndarray::{array, Array1};
 
-    use crate::{dilation::WithDilation, ConvExt};
+    use crate::{dilation::WithDilation, reference::assert_close, ConvExt};
 
     use super::*;
 
@@ -345,4 +467,154 @@ mod tests {
 
         assert_eq!(res_normal, res_fft);
     }
+
+    // a naive full complex dft, to check that conv_fft only relies on the FftBackend contract
+    struct Dft;
+
+    impl Dft {
+        // sum over the axes of a_i * b_i / len_i for the row major indices a and b
+        fn phase(shape: &[usize], mut a: usize, mut b: usize) -> f64 {
+            shape.iter().rev().fold(0., |phase, &len| {
+                let p = ((a % len) * (b % len)) as f64 / len as f64;
+                a /= len;
+                b /= len;
+                phase + p
+            })
+        }
+    }
+
+    impl FftBackend<f64> for Dft {
+        fn forward<const N: usize>(
+            &mut self,
+            input: &mut Array<f64, Dim<[Ix; N]>>,
+        ) -> Array<Complex<f64>, Dim<[Ix; N]>>
+        where
+            Dim<[Ix; N]>: RemoveAxis,
+            [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        {
+            let shape = input.shape().to_vec();
+            let mut output = input.map(|_| Complex::new(0., 0.));
+            output.iter_mut().enumerate().for_each(|(k, o)| {
+                *o = input
+                    .iter()
+                    .enumerate()
+                    .fold(Complex::new(0., 0.), |acc, (n, &x)| {
+                        acc + Complex::from_polar(
+                            x,
+                            -2. * std::f64::consts::PI * Self::phase(&shape, k, n),
+                        )
+                    });
+            });
+            output
+        }
+
+        fn backward<const N: usize>(
+            &mut self,
+            input: Array<Complex<f64>, Dim<[Ix; N]>>,
+            shape: [usize; N],
+        ) -> Array<f64, Dim<[Ix; N]>>
+        where
+            Dim<[Ix; N]>: RemoveAxis,
+            [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        {
+            // the full spectrum has the shape of the input
+            assert_eq!(input.shape(), shape);
+            let shape = shape.to_vec();
+            let mut output = input.map(|_| 0.);
+            output.iter_mut().enumerate().for_each(|(n, o)| {
+                *o = input.iter().enumerate().fold(0., |acc, (k, x)| {
+                    acc + (x * Complex::from_polar(
+                        1.,
+                        2. * std::f64::consts::PI * Self::phase(&shape, k, n),
+                    ))
+                    .re
+                }) / input.len() as f64;
+            });
+            output
+        }
+    }
+
+    #[test]
+    fn fft_backend() {
+        let arr = array![[1., 2., 0.], [3., 4., -1.], [5., -2., 6.], [7., 8., 1.]];
+        let kernel = array![[1., 0.], [3., 1.]];
+
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Same, PaddingMode::Reflect),
+            (
+                ConvMode::Custom {
+                    padding: [2, 1],
+                    strides: [2, 1],
+                },
+                PaddingMode::Zeros,
+            ),
+        ] {
+            let expected = arr.conv(&kernel, conv_mode, padding_mode).unwrap();
+            let res = arr
+                .conv_fft_with_processor(&kernel, conv_mode, padding_mode, &mut Dft)
+                .unwrap();
+            assert_close(&res, &expected, 1e-9);
+        }
+    }
+
+    #[test]
+    fn taper() {
+        let x = array![4f64, -1., 3., 0., 2., 5., -2., 1., 3., 6.];
+        let kernel = array![1., 1., 1.] / 3.;
+
+        // scipy.signal.windows.hann(10) and tukey(10, 0.5), to 4 decimals
+        let hann = [
+            0., 0.1170, 0.4132, 0.75, 0.9698, 0.9698, 0.75, 0.4132, 0.1170, 0.,
+        ];
+        let tukey = [0., 0.4132, 0.9698, 1., 1., 1., 1., 0.9698, 0.4132, 0.];
+        for (taper, window) in [(Taper::Hann, hann), (Taper::Tukey(0.5), tukey)] {
+            let expected = x
+                .iter()
+                .zip(window)
+                .map(|(x, w)| x * w)
+                .collect::<Array1<_>>()
+                .conv_fft(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap();
+            let res = x
+                .conv_fft_tapered(&kernel, ConvMode::Same, PaddingMode::Zeros, taper)
+                .unwrap();
+            assert_close(&res, &expected, 1e-3);
+        }
+        assert_eq!(
+            x.conv_fft_tapered(
+                &kernel,
+                ConvMode::Same,
+                PaddingMode::Zeros,
+                Taper::Tukey(0.)
+            )
+            .unwrap(),
+            x.conv_fft(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap()
+        );
+
+        // edgetaper: the box's autocorrelation over 9 samples is [3, 2, 1, 0, .., 0, 1, 2] / 9
+        let alpha = [0., 1. / 3., 2. / 3., 1., 1., 1., 1., 2. / 3., 1. / 3., 0.];
+        let n = x.len();
+        let blurred = (0..n).map(|i| (x[(i + n - 1) % n] + x[i] + x[(i + 1) % n]) / 3.);
+        let tapered = taper::apply(
+            &x,
+            &(&kernel).into(),
+            Taper::Edge,
+            &mut Processor::default(),
+        )
+        .unwrap();
+        tapered
+            .iter()
+            .zip(alpha.iter().zip(x.iter()).zip(blurred))
+            .for_each(|(t, ((a, x), b))| assert!((t - (a * x + (1. - a) * b)).abs() < 1e-12));
+
+        assert!(x
+            .conv_fft_tapered(
+                &kernel,
+                ConvMode::Same,
+                PaddingMode::Zeros,
+                Taper::Tukey(2.)
+            )
+            .is_err());
+    }
 }
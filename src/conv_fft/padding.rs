@@ -71,7 +71,7 @@ where
         .unwrap()
     });
 
-    buffer_slice.zip_mut_with(kernel, |b, &k| *b = k);
+    buffer_slice.zip_mut_with(&*kernel, |b, &k| *b = k);
 
     buffer
 }
@@ -92,7 +92,7 @@ mod tests {
         let kernel = array![[1, 1, 1], [1, 1, 1], [1, 1, 1]];
         let kernel = kernel.into_kernel_with_dilation();
 
-        let explicit_conv = ConvMode::Full.unfold(&kernel);
+        let explicit_conv = ConvMode::Full.unfold(&kernel, [2, 2]).unwrap();
         let explicit_padding = explicit_conv.padding;
 
         let arr_padded = data(
@@ -124,7 +124,7 @@ mod tests {
         let kernel = array![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
         let kernel = kernel.with_dilation([2, 3]).into_kernel_with_dilation();
 
-        let explicit_conv = ConvMode::Full.unfold(&kernel);
+        let explicit_conv = ConvMode::Full.unfold(&kernel, [2, 2]).unwrap();
         let _explicit_padding = explicit_conv.padding;
 
         let kernel_padded = super::kernel(kernel, [8, 8]);
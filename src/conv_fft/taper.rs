@@ -0,0 +1,139 @@
+use std::f64::consts::PI;
+
+use ndarray::{Array, ArrayBase, Data, Dim, IntoDimension, Ix, RemoveAxis};
+use num::traits::NumAssign;
+use rustfft::FftNum;
+
+use super::{circular, FftBackend};
+use crate::dilation::KernelWithDilation;
+
+/// how `conv_fft_tapered` smooths the borders of the input before the fft, against the
+/// ringing of non-periodic data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Taper {
+    /// a hann window on every axis, down to zero at the borders.
+    Hann,
+    /// a tukey window on every axis, the fraction in `[0, 1]` of every axis that is tapered
+    /// (0 keeps the input, 1 is `Hann`).
+    Tukey(f64),
+    /// MATLAB's `edgetaper`: the borders blend into the input circularly blurred by the
+    /// kernel, weighted by the kernel's autocorrelation, so the input wraps around smoothly
+    /// without going to zero.
+    Edge,
+}
+
+pub(super) fn apply<'a, T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<'a, SK, N>,
+    taper: Taper,
+    fft_processor: &mut impl FftBackend<T>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + FftNum,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let shape: [usize; N] = std::array::from_fn(|i| data.shape()[i]);
+
+    let alpha = match taper {
+        Taper::Hann => 1.,
+        Taper::Tukey(alpha) if (0. ..=1.).contains(&alpha) => alpha,
+        Taper::Tukey(alpha) => {
+            return Err(crate::Error::InvalidParameter(format!(
+                "the tukey fraction must be in [0, 1], got {alpha}"
+            )))
+        }
+        Taper::Edge => return edge(data, kwd, shape, fft_processor),
+    };
+
+    let windows = shape.map(|n| tukey::<T>(n, alpha));
+    Ok(Array::from_shape_fn(shape, |index| {
+        let index = index.into_dimension();
+        (0..N).fold(data[index], |v, i| v * windows[i][index[i]])
+    }))
+}
+
+// same as scipy.signal.windows.tukey(n, alpha, sym=True)
+fn tukey<T: FftNum>(n: usize, alpha: f64) -> Vec<T> {
+    if n == 1 || alpha == 0. {
+        return vec![T::one(); n];
+    }
+
+    (0..n)
+        .map(|i| {
+            let x = i as f64 / (n - 1) as f64;
+            let x = x.min(1. - x);
+            let w = if x < alpha / 2. {
+                0.5 * (1. - (2. * PI * x / alpha).cos())
+            } else {
+                1.
+            };
+            T::from_f64(w).unwrap()
+        })
+        .collect()
+}
+
+// alpha * data + (1 - alpha) * blurred, alpha the outer product over the axes of one minus
+// the normalized circular autocorrelation of the kernel projected on the axis
+fn edge<'a, T, S, SK, const N: usize>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    kwd: &KernelWithDilation<'a, SK, N>,
+    shape: [usize; N],
+    fft_processor: &mut impl FftBackend<T>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + FftNum,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let blurred = circular(data, kwd, fft_processor)?;
+
+    let weights: [Vec<T>; N] = std::array::from_fn(|axis| {
+        let n = shape[axis];
+        let mut projection =
+            vec![T::zero(); (kwd.kernel.shape()[axis] - 1) * kwd.dilation[axis] + 1];
+        kwd.kernel.indexed_iter().for_each(|(index, &v)| {
+            let index = index.into_dimension();
+            projection[index[axis] * kwd.dilation[axis]] += v;
+        });
+
+        // circular over n - 1 samples, as in the fft of that size, so that both ends get
+        // a weight of zero
+        let m = n.saturating_sub(1);
+        if m == 0 {
+            return vec![T::one(); n];
+        }
+        let mut folded = vec![T::zero(); m];
+        projection
+            .iter()
+            .enumerate()
+            .for_each(|(i, &v)| folded[i % m] += v);
+        let support = (0..m)
+            .filter(|&i| folded[i] != T::zero())
+            .collect::<Vec<_>>();
+        let autocorrelation = |lag: usize| {
+            support
+                .iter()
+                .fold(T::zero(), |sum, &i| sum + folded[i] * folded[(i + lag) % m])
+        };
+
+        let peak = autocorrelation(0);
+        if peak == T::zero() {
+            return vec![T::one(); n];
+        }
+
+        (0..n)
+            .map(|i| T::one() - autocorrelation(i % m) / peak)
+            .collect()
+    });
+
+    Ok(Array::from_shape_fn(shape, |index| {
+        let index = index.into_dimension();
+        let alpha = (0..N).fold(T::one(), |w, i| w * weights[i][index[i]]);
+        alpha * data[index] + (T::one() - alpha) * blurred[index]
+    }))
+}
@@ -0,0 +1,123 @@
+//! OpenCV compatible wrappers.
+
+use std::fmt::Debug;
+
+use ndarray::{Array2, ArrayBase, Data, Ix2};
+use num::traits::NumAssign;
+
+use crate::{ConvExt, ConvMode, Error, PaddingMode};
+
+/// OpenCV `BorderTypes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Border {
+    /// `iiiiii|abcdefgh|iiiiiii` with the given border value
+    Constant,
+    /// `aaaaaa|abcdefgh|hhhhhhh`
+    Replicate,
+    /// `fedcba|abcdefgh|hgfedcb`
+    Reflect,
+    /// `cdefgh|abcdefgh|abcdefg`
+    Wrap,
+    /// `gfedcb|abcdefgh|gfedcba`, same as `Default`
+    Reflect101,
+    Default,
+}
+
+/// `cv::filter2D(src, dst, -1, kernel, anchor, 0, border_type)`, a correlation
+/// with the same shape as `src`:
+/// `dst(x, y) = sum kernel(x', y') * src(x + x' - anchor.x, y + y' - anchor.y)`.
+///
+/// `anchor` is `(x, y)` in the kernel, `(-1, -1)` means the kernel center.
+/// `border_value` is only used with `Border::Constant`.
+pub fn filter2d<T, S, SK>(
+    src: &ArrayBase<S, Ix2>,
+    kernel: &ArrayBase<SK, Ix2>,
+    anchor: (isize, isize),
+    border_type: Border,
+    border_value: T,
+) -> Result<Array2<T>, Error<2>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+{
+    let (rows, cols) = kernel.dim();
+    let anchor = [anchor.1, anchor.0];
+
+    let mut padding = [[0; 2]; 2];
+    for (i, size) in [rows, cols].into_iter().enumerate() {
+        let before = if anchor[i] == -1 {
+            size as isize / 2
+        } else {
+            anchor[i]
+        };
+        if before < 0 || before >= size as isize {
+            return Err(Error::InvalidParameter(format!(
+                "anchor {:?} out of the kernel {:?}",
+                anchor,
+                kernel.dim()
+            )));
+        }
+        padding[i] = [before as usize, size - 1 - before as usize];
+    }
+
+    let padding_mode = match border_type {
+        Border::Constant => PaddingMode::Const(border_value),
+        Border::Replicate => PaddingMode::Replicate,
+        Border::Reflect => PaddingMode::Symmetric,
+        Border::Wrap => PaddingMode::Circular,
+        Border::Reflect101 | Border::Default => PaddingMode::Reflect,
+    };
+
+    src.conv(
+        kernel,
+        ConvMode::Explicit {
+            padding,
+            strides: [1; 2],
+        },
+        padding_mode,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn aligned_with_opencv() {
+        let src = array![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+
+        // dst(x, y) = src(x + 1, y)
+        let kernel = array![[0, 0, 0], [0, 0, 1], [0, 0, 0]];
+        for (border_type, last) in [
+            (Border::Constant, [0, 0, 0]),
+            (Border::Replicate, [3, 6, 9]),
+            (Border::Reflect, [3, 6, 9]),
+            (Border::Wrap, [1, 4, 7]),
+            (Border::Default, [2, 5, 8]),
+        ] {
+            assert_eq!(
+                filter2d(&src, &kernel, (-1, -1), border_type, 0).unwrap(),
+                array![[2, 3, last[0]], [5, 6, last[1]], [8, 9, last[2]]]
+            );
+        }
+
+        let kernel = array![[1, 1], [1, 1]];
+        assert_eq!(
+            filter2d(&src, &kernel, (-1, -1), Border::Constant, 0).unwrap(),
+            array![[1, 3, 5], [5, 12, 16], [11, 24, 28]]
+        );
+        assert_eq!(
+            filter2d(&src, &kernel, (0, 0), Border::Constant, 0).unwrap(),
+            array![[12, 16, 9], [24, 28, 15], [15, 17, 9]]
+        );
+        assert_eq!(
+            filter2d(&src, &kernel, (1, 0), Border::Constant, 1).unwrap(),
+            array![[7, 12, 16], [13, 24, 28], [10, 17, 19]]
+        );
+
+        assert!(filter2d(&src, &kernel, (2, 0), Border::Constant, 0).is_err());
+    }
+}
@@ -1,8 +1,53 @@
-use ndarray::{ArrayBase, Data, Dim, Dimension, IntoDimension, Ix, RawData};
+use core::ops::Deref;
+
+use alloc::vec::Vec;
+
+use ndarray::{
+    Array1, Array2, ArrayBase, ArrayView1, ArrayView2, Data, Dim, Dimension, IntoDimension, Ix,
+    OwnedRepr, RawData, ViewRepr,
+};
+
+// the kernel borrowed from the caller, or owned when it is built from a slice or a vec
+pub enum KernelRef<'a, S: RawData, const N: usize> {
+    Borrowed(&'a ArrayBase<S, Dim<[Ix; N]>>),
+    Owned(ArrayBase<S, Dim<[Ix; N]>>),
+}
+
+impl<'a, S: RawData, const N: usize> Deref for KernelRef<'a, S, N> {
+    type Target = ArrayBase<S, Dim<[Ix; N]>>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            KernelRef::Borrowed(kernel) => kernel,
+            KernelRef::Owned(kernel) => kernel,
+        }
+    }
+}
 
 pub struct KernelWithDilation<'a, S: RawData, const N: usize> {
-    pub kernel: &'a ArrayBase<S, Dim<[Ix; N]>>,
+    pub kernel: KernelRef<'a, S, N>,
     pub dilation: [usize; N],
+    // shifts the window against the output like scipy's origin,
+    // positive values move the window backward (e.g. causal filters)
+    pub origin: [isize; N],
+    // keep the zero taps so NaN and infinity in the input propagate like the fft path
+    pub zero_taps: bool,
+}
+
+impl<'a, S: RawData, const N: usize> KernelWithDilation<'a, S, N> {
+    #[inline]
+    pub fn with_origin(self, origin: [isize; N]) -> Self {
+        Self { origin, ..self }
+    }
+
+    #[inline]
+    pub fn with_zero_taps(self) -> Self {
+        Self {
+            zero_taps: true,
+            ..self
+        }
+    }
 }
 
 impl<'a, S: RawData, const N: usize, T> KernelWithDilation<'a, S, N>
@@ -13,11 +58,11 @@ where
 {
     pub fn gen_offset_list(&self, pds_strides: &[isize]) -> Vec<(isize, T)> {
         let strides: [isize; N] =
-            std::array::from_fn(|i| self.dilation[i] as isize * pds_strides[i]);
+            core::array::from_fn(|i| self.dilation[i] as isize * pds_strides[i]);
 
         self.kernel
             .indexed_iter()
-            .filter(|(_, v)| **v != T::zero())
+            .filter(|(_, v)| self.zero_taps || **v != T::zero())
             .map(|(index, v)| {
                 let index = index.into_dimension();
                 (
@@ -43,12 +88,34 @@ impl<'a, S: RawData, const N: usize> From<&'a ArrayBase<S, Dim<[Ix; N]>>>
 {
     fn from(kernel: &'a ArrayBase<S, Dim<[Ix; N]>>) -> Self {
         Self {
-            kernel,
+            kernel: KernelRef::Borrowed(kernel),
             dilation: [1; N],
+            origin: [0; N],
+            zero_taps: false,
         }
     }
 }
 
+// the kernel size with dilation, k + (k - 1) * (d - 1)
+pub(crate) fn dilated<const N: usize>(
+    kernel_shape: [usize; N],
+    dilation: [usize; N],
+) -> Result<[usize; N], crate::Error<N>>
+where
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    if kernel_shape.contains(&0) {
+        return Err(crate::Error::KernelShape(kernel_shape.into_dimension()));
+    }
+    if let Some(axis) = dilation.iter().position(|&d| d == 0) {
+        return Err(crate::Error::ZeroDilation(axis));
+    }
+
+    Ok(core::array::from_fn(|i| {
+        kernel_shape[i] * dilation[i] - dilation[i] + 1
+    }))
+}
+
 pub trait IntoDilation<const N: usize> {
     fn into_dilation(self) -> [usize; N];
 }
@@ -75,12 +142,25 @@ impl<S: RawData, const N: usize> WithDilation<S, N> for ArrayBase<S, Dim<[Ix; N]
     #[inline]
     fn with_dilation(&self, dilation: impl IntoDilation<N>) -> KernelWithDilation<S, N> {
         KernelWithDilation {
-            kernel: self,
+            kernel: KernelRef::Borrowed(self),
             dilation: dilation.into_dilation(),
+            origin: [0; N],
+            zero_taps: false,
         }
     }
 }
 
+pub trait WithOrigin<S: RawData, const N: usize> {
+    fn with_origin(&self, origin: [isize; N]) -> KernelWithDilation<'_, S, N>;
+}
+
+impl<S: RawData, const N: usize> WithOrigin<S, N> for ArrayBase<S, Dim<[Ix; N]>> {
+    #[inline]
+    fn with_origin(&self, origin: [isize; N]) -> KernelWithDilation<'_, S, N> {
+        self.with_dilation(1).with_origin(origin)
+    }
+}
+
 pub trait IntoKernelWithDilation<'a, S: RawData, const N: usize> {
     fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, S, N>;
 }
@@ -103,6 +183,64 @@ impl<'a, S: RawData, const N: usize> IntoKernelWithDilation<'a, S, N>
     }
 }
 
+impl<'a, T> IntoKernelWithDilation<'a, ViewRepr<&'a T>, 1> for &'a [T] {
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, ViewRepr<&'a T>, 1> {
+        owned(ArrayView1::from(self))
+    }
+}
+
+impl<'a, T, const K: usize> IntoKernelWithDilation<'a, ViewRepr<&'a T>, 1> for &'a [T; K] {
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, ViewRepr<&'a T>, 1> {
+        owned(ArrayView1::from(self))
+    }
+}
+
+impl<'a, T> IntoKernelWithDilation<'a, OwnedRepr<T>, 1> for Vec<T> {
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, OwnedRepr<T>, 1> {
+        owned(Array1::from(self))
+    }
+}
+
+impl<'a, T, const K: usize> IntoKernelWithDilation<'a, OwnedRepr<T>, 1> for [T; K] {
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, OwnedRepr<T>, 1> {
+        owned(Array1::from_iter(self))
+    }
+}
+
+impl<'a, T, const H: usize, const W: usize> IntoKernelWithDilation<'a, ViewRepr<&'a T>, 2>
+    for &'a [[T; W]; H]
+{
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, ViewRepr<&'a T>, 2> {
+        // the rows are contiguous, so this is a standard layout array of shape (H, W)
+        owned(unsafe { ArrayView2::from_shape_ptr((H, W), self.as_ptr() as *const T) })
+    }
+}
+
+impl<'a, T, const H: usize, const W: usize> IntoKernelWithDilation<'a, OwnedRepr<T>, 2>
+    for [[T; W]; H]
+{
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, OwnedRepr<T>, 2> {
+        owned(Array2::from_shape_vec((H, W), self.into_iter().flatten().collect()).unwrap())
+    }
+}
+
+fn owned<'a, S: RawData, const N: usize>(
+    kernel: ArrayBase<S, Dim<[Ix; N]>>,
+) -> KernelWithDilation<'a, S, N> {
+    KernelWithDilation {
+        kernel: KernelRef::Owned(kernel),
+        dilation: [1; N],
+        origin: [0; N],
+        zero_taps: false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use ndarray::array;
@@ -128,6 +266,10 @@ mod tests {
         let kernel = array![[1, 0, 1], [0, 1, 0]];
 
         conv_example(kernel.with_dilation([1, 2]));
+
+        conv_example(kernel.with_origin([1, 0]));
+
+        conv_example(kernel.with_dilation([1, 2]).with_origin([0, -1]));
     }
 
     #[test]
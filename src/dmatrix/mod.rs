@@ -0,0 +1,120 @@
+use std::fmt::Debug;
+
+use nalgebra::{DMatrix, Dyn, Matrix, RawStorage, Scalar};
+use ndarray::{Array2, ArrayView2, ShapeBuilder};
+use num::traits::NumAssign;
+use rustfft::FftNum;
+
+use crate::{ConvExt, ConvFFTExt, ConvMode, PaddingMode};
+
+pub trait DMatrixConvExt<T: Scalar + NumAssign + Copy> {
+    /// `conv` of a nalgebra matrix, the storage is read in place as a 2D array.
+    fn conv<SK: RawStorage<T, Dyn, Dyn>>(
+        &self,
+        kernel: &Matrix<T, Dyn, Dyn, SK>,
+        conv_mode: ConvMode<2>,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<DMatrix<T>, crate::Error<2>>;
+
+    fn conv_fft<SK: RawStorage<T, Dyn, Dyn>>(
+        &self,
+        kernel: &Matrix<T, Dyn, Dyn, SK>,
+        conv_mode: ConvMode<2>,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<DMatrix<T>, crate::Error<2>>
+    where
+        T: FftNum;
+}
+
+impl<T, S> DMatrixConvExt<T> for Matrix<T, Dyn, Dyn, S>
+where
+    T: Scalar + NumAssign + Copy + Debug,
+    S: RawStorage<T, Dyn, Dyn>,
+{
+    fn conv<SK: RawStorage<T, Dyn, Dyn>>(
+        &self,
+        kernel: &Matrix<T, Dyn, Dyn, SK>,
+        conv_mode: ConvMode<2>,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<DMatrix<T>, crate::Error<2>> {
+        view(self)
+            .conv(&view(kernel), conv_mode, padding_mode)
+            .map(into_dmatrix)
+    }
+
+    fn conv_fft<SK: RawStorage<T, Dyn, Dyn>>(
+        &self,
+        kernel: &Matrix<T, Dyn, Dyn, SK>,
+        conv_mode: ConvMode<2>,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<DMatrix<T>, crate::Error<2>>
+    where
+        T: FftNum,
+    {
+        view(self)
+            .conv_fft(&view(kernel), conv_mode, padding_mode)
+            .map(into_dmatrix)
+    }
+}
+
+// nalgebra is column major, its strides are those of (row, column)
+fn view<T: Scalar, S: RawStorage<T, Dyn, Dyn>>(
+    matrix: &Matrix<T, Dyn, Dyn, S>,
+) -> ArrayView2<'_, T> {
+    let (rows, cols) = matrix.shape();
+    let (row_stride, col_stride) = matrix.strides();
+    unsafe {
+        ArrayView2::from_shape_ptr(
+            (rows, cols).strides((row_stride, col_stride)),
+            matrix.as_ptr(),
+        )
+    }
+}
+
+// column major outputs, as conv returns for column major inputs, are moved without a copy
+fn into_dmatrix<T: Scalar + Copy>(arr: Array2<T>) -> DMatrix<T> {
+    let (rows, cols) = arr.dim();
+
+    if arr.t().is_standard_layout() {
+        return DMatrix::from_vec(rows, cols, crate::buffer::into_memory_order(arr));
+    }
+
+    DMatrix::from_iterator(rows, cols, arr.t().iter().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+
+    #[test]
+    fn column_major() {
+        let matrix = DMatrix::from_fn(6, 7, |i, j| (i * 7 + j) as f64 % 5. - 2.);
+        let kernel = DMatrix::from_row_slice(2, 3, &[1., 0., -2., 0.5, 3., 1.]);
+
+        let arr = Array2::from_shape_fn((6, 7), |(i, j)| matrix[(i, j)]);
+        let arr_kernel = Array2::from_shape_fn((2, 3), |(i, j)| kernel[(i, j)]);
+
+        // the storage is read in place and the column major output moved into the matrix
+        let expected = arr
+            .conv(&arr_kernel, ConvMode::Full, PaddingMode::Zeros)
+            .unwrap();
+        let ret = matrix
+            .conv(&kernel, ConvMode::Full, PaddingMode::Zeros)
+            .unwrap();
+        assert_eq!(ret.shape(), expected.dim());
+        assert!(ret.iter().eq(expected.t().iter()));
+
+        // a view with strides of its own
+        let ret = matrix
+            .view((1, 2), (4, 3))
+            .conv(&kernel, ConvMode::Valid, PaddingMode::Zeros)
+            .unwrap();
+        let expected = arr
+            .slice(ndarray::s![1..5, 2..5])
+            .conv(&arr_kernel, ConvMode::Valid, PaddingMode::Zeros)
+            .unwrap();
+        assert!(ret.iter().eq(expected.t().iter()));
+    }
+}
@@ -0,0 +1,619 @@
+//! pywt style discrete wavelet transform on top of `conv`. the analysis filters run with
+//! stride 2 and the synthesis filters on the zero stuffed coefficients, axis by axis, and
+//! the coefficients match `pywt.dwt` / `pywt.idwt` in the "symmetric" and "periodization"
+//! modes.
+
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, Array1, Array2, ArrayBase, ArrayView, Axis, Data, Dim, IntoDimension, Ix, Ix1, Ix2,
+    RemoveAxis, SliceArg, SliceInfo, SliceInfoElem,
+};
+use num::traits::{Float, NumAssign};
+
+use crate::{ConvExt, ConvMode, Error, PaddingMode};
+
+/// Orthogonal wavelet, named as in pywt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wavelet {
+    Haar,
+    Db2,
+    Db3,
+    Db4,
+}
+
+impl Wavelet {
+    // reconstruction lowpass, the coefficients of pywt
+    fn rec_lo(self) -> &'static [f64] {
+        match self {
+            Wavelet::Haar => &[
+                std::f64::consts::FRAC_1_SQRT_2,
+                std::f64::consts::FRAC_1_SQRT_2,
+            ],
+            Wavelet::Db2 => &[
+                0.48296291314469025,
+                0.836516303737469,
+                0.22414386804185735,
+                -0.12940952255092145,
+            ],
+            Wavelet::Db3 => &[
+                0.3326705529509569,
+                0.8068915093133388,
+                0.4598775021193313,
+                -0.13501102001039084,
+                -0.08544127388224149,
+                0.035226291882100656,
+            ],
+            Wavelet::Db4 => &[
+                0.23037781330885523,
+                0.7148465705525415,
+                0.6308807679295904,
+                -0.02798376941698385,
+                -0.18703481171888114,
+                0.030841381835986965,
+                0.032883011666982945,
+                -0.010597401784997278,
+            ],
+        }
+    }
+
+    /// `(dec_lo, dec_hi, rec_lo, rec_hi)`, same as `pywt.Wavelet(..).filter_bank`.
+    pub fn filter_bank<T: Float>(self) -> [Array1<T>; 4] {
+        let rec_lo = self.rec_lo();
+        let len = rec_lo.len();
+        let coefficient = |v: f64| T::from(v).unwrap();
+
+        [
+            rec_lo.iter().rev().map(|&v| coefficient(v)).collect(),
+            (0..len)
+                .map(|k| coefficient(if k % 2 == 0 { -rec_lo[k] } else { rec_lo[k] }))
+                .collect(),
+            rec_lo.iter().map(|&v| coefficient(v)).collect(),
+            (0..len)
+                .map(|k| {
+                    let v = rec_lo[len - 1 - k];
+                    coefficient(if k % 2 == 0 { v } else { -v })
+                })
+                .collect(),
+        ]
+    }
+}
+
+/// Signal extension, named as in pywt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // half sample symmetric, d c b a | a b c d | d c b a. `n` samples give
+    // `(n + taps - 1) / 2` coefficients
+    Symmetric,
+    // periodic, an odd length is extended by its last sample first. `n` samples give
+    // `ceil(n / 2)` coefficients
+    Periodization,
+}
+
+/// single level `pywt.dwt(x, wavelet, mode)`, returns `(cA, cD)`.
+pub fn dwt<T, S>(
+    x: &ArrayBase<S, Ix1>,
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<(Array1<T>, Array1<T>), Error<1>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    let [a, d] = decompose(x.view(), wavelet, mode)?.try_into().unwrap();
+    Ok((a, d))
+}
+
+/// single level `pywt.idwt(cA, cD, wavelet, mode)`.
+pub fn idwt<T, S, SD>(
+    ca: &ArrayBase<S, Ix1>,
+    cd: &ArrayBase<SD, Ix1>,
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<Array1<T>, Error<1>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+    SD: Data<Elem = T>,
+{
+    reconstruct(vec![ca.view(), cd.view()], wavelet, mode)
+}
+
+/// single level `pywt.dwt2(x, wavelet, mode)`, returns `(cA, [cH, cV, cD])`.
+#[allow(clippy::type_complexity)]
+pub fn dwt2<T, S>(
+    x: &ArrayBase<S, Ix2>,
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<(Array2<T>, [Array2<T>; 3]), Error<2>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    // the bands are ordered by the filter of axis 0, then axis 1
+    let [aa, ad, da, dd] = decompose(x.view(), wavelet, mode)?.try_into().unwrap();
+    Ok((aa, [da, ad, dd]))
+}
+
+/// single level `pywt.idwt2((cA, (cH, cV, cD)), wavelet, mode)`.
+pub fn idwt2<T, S>(
+    ca: &ArrayBase<S, Ix2>,
+    details: &[Array2<T>; 3],
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<Array2<T>, Error<2>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    let [ch, cv, cd] = details;
+    reconstruct(
+        vec![ca.view(), cv.view(), ch.view(), cd.view()],
+        wavelet,
+        mode,
+    )
+}
+
+/// `pywt.wavedec(x, wavelet, mode, level)`, returns `(cA_n, [cD_n, .., cD_1])`.
+#[allow(clippy::type_complexity)]
+pub fn wavedec<T, S>(
+    x: &ArrayBase<S, Ix1>,
+    wavelet: Wavelet,
+    mode: Mode,
+    level: usize,
+) -> Result<(Array1<T>, Vec<Array1<T>>), Error<1>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    let mut ca = x.to_owned();
+    let mut details = Vec::with_capacity(level);
+    for _ in 0..level {
+        let (a, d) = dwt(&ca, wavelet, mode)?;
+        ca = a;
+        details.push(d);
+    }
+    details.reverse();
+
+    Ok((ca, details))
+}
+
+/// `pywt.waverec([cA_n, cD_n, .., cD_1], wavelet, mode)`.
+pub fn waverec<T, S>(
+    ca: &ArrayBase<S, Ix1>,
+    details: &[Array1<T>],
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<Array1<T>, Error<1>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    let mut ca = ca.to_owned();
+    for cd in details {
+        ca = idwt(&matched(&ca, cd.raw_dim()), cd, wavelet, mode)?;
+    }
+
+    Ok(ca)
+}
+
+/// `pywt.wavedec2(x, wavelet, mode, level)`, returns `(cA_n, [(cH_n, cV_n, cD_n), ..])`
+/// with the coarsest details first.
+#[allow(clippy::type_complexity)]
+pub fn wavedec2<T, S>(
+    x: &ArrayBase<S, Ix2>,
+    wavelet: Wavelet,
+    mode: Mode,
+    level: usize,
+) -> Result<(Array2<T>, Vec<[Array2<T>; 3]>), Error<2>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    let mut ca = x.to_owned();
+    let mut details = Vec::with_capacity(level);
+    for _ in 0..level {
+        let (a, d) = dwt2(&ca, wavelet, mode)?;
+        ca = a;
+        details.push(d);
+    }
+    details.reverse();
+
+    Ok((ca, details))
+}
+
+/// `pywt.waverec2([cA_n, (cH_n, cV_n, cD_n), ..], wavelet, mode)`.
+pub fn waverec2<T, S>(
+    ca: &ArrayBase<S, Ix2>,
+    details: &[[Array2<T>; 3]],
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<Array2<T>, Error<2>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    let mut ca = ca.to_owned();
+    for d in details {
+        ca = idwt2(&matched(&ca, d[0].raw_dim()), d, wavelet, mode)?;
+    }
+
+    Ok(ca)
+}
+
+// an odd length reconstructs one sample too many, which is dropped like pywt does
+fn matched<T, const N: usize>(
+    ca: &Array<T, Dim<[Ix; N]>>,
+    dim: Dim<[Ix; N]>,
+) -> Array<T, Dim<[Ix; N]>>
+where
+    T: Copy,
+    Dim<[Ix; N]>: RemoveAxis,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let shape = ca.shape();
+    ca.slice(unsafe {
+        SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
+            start: 0,
+            end: Some(if shape[i] == dim[i] + 1 {
+                dim[i]
+            } else {
+                shape[i]
+            } as isize),
+            step: 1,
+        }))
+        .unwrap()
+    })
+    .to_owned()
+}
+
+// every combination of lowpass and highpass over the axes, the filter of the last axis
+// changing fastest
+#[allow(clippy::type_complexity)]
+fn decompose<T, const N: usize>(
+    x: ArrayView<T, Dim<[Ix; N]>>,
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<Vec<Array<T, Dim<[Ix; N]>>>, Error<N>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    if x.shape().contains(&0) {
+        return Err(Error::DataShape(x.raw_dim()));
+    }
+
+    let [dec_lo, dec_hi, _, _] = wavelet.filter_bank::<T>();
+
+    let mut bands = vec![x.to_owned()];
+    for axis in 0..N {
+        bands = bands
+            .iter()
+            .flat_map(|band| {
+                [&dec_lo, &dec_hi].map(|filter| analysis(band.view(), filter, axis, mode))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    Ok(bands)
+}
+
+// the inverse of `decompose`, merging the bands of the last axis first
+fn reconstruct<T, const N: usize>(
+    bands: Vec<ArrayView<T, Dim<[Ix; N]>>>,
+    wavelet: Wavelet,
+    mode: Mode,
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    if let Some(band) = bands.iter().find(|band| band.shape() != bands[0].shape()) {
+        return Err(Error::InvalidParameter(format!(
+            "coefficients of different shapes {:?} and {:?}",
+            bands[0].shape(),
+            band.shape()
+        )));
+    }
+
+    if bands[0].shape().contains(&0) {
+        return Err(Error::DataShape(bands[0].raw_dim()));
+    }
+
+    let [_, _, rec_lo, rec_hi] = wavelet.filter_bank::<T>();
+
+    let mut bands = bands.into_iter().map(|b| b.to_owned()).collect::<Vec<_>>();
+    for axis in (0..N).rev() {
+        bands = bands
+            .chunks(2)
+            .map(|pair| {
+                Ok(synthesis(pair[0].view(), &rec_lo, axis, mode)?
+                    + synthesis(pair[1].view(), &rec_hi, axis, mode)?)
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    Ok(bands.pop().unwrap())
+}
+
+// the filter along `axis`, reversed since conv is a correlation
+fn along<T: Copy, const N: usize>(filter: &Array1<T>, axis: usize) -> Array<T, Dim<[Ix; N]>>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let mut shape = [1; N];
+    shape[axis] = filter.len();
+    Array::from_shape_vec(shape, filter.iter().rev().copied().collect()).unwrap()
+}
+
+// filter and keep the odd samples of the full convolution (symmetric), or the samples
+// centered on the even inputs (periodization). the extension is gathered by index, so a
+// filter longer than the signal wraps around it as many times as pywt does
+fn analysis<T, const N: usize>(
+    x: ArrayView<T, Dim<[Ix; N]>>,
+    filter: &Array1<T>,
+    axis: usize,
+    mode: Mode,
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let n = x.shape()[axis] as isize;
+    let taps = filter.len() as isize;
+
+    let indices = match mode {
+        Mode::Symmetric => (2 - taps..n + taps - 1)
+            .map(|i| {
+                let i = i.rem_euclid(2 * n);
+                (if i < n { i } else { 2 * n - 1 - i }) as usize
+            })
+            .collect::<Vec<_>>(),
+        Mode::Periodization => {
+            let even = n + n % 2;
+            (1 - taps / 2..even + taps / 2 - 1)
+                .map(|i| i.rem_euclid(even).min(n - 1) as usize)
+                .collect()
+        }
+    };
+
+    let mut strides = [1; N];
+    strides[axis] = 2;
+    x.select(Axis(axis), &indices).conv(
+        &along(filter, axis),
+        ConvMode::Custom {
+            padding: [0; N],
+            strides,
+        },
+        PaddingMode::Zeros,
+    )
+}
+
+// filter the zero stuffed coefficients, `2n - taps + 2` samples (symmetric) or `2n`
+// samples (periodization)
+fn synthesis<T, const N: usize>(
+    c: ArrayView<T, Dim<[Ix; N]>>,
+    filter: &Array1<T>,
+    axis: usize,
+    mode: Mode,
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let kernel = along(filter, axis);
+
+    let mut shape: [usize; N] = std::array::from_fn(|i| c.shape()[i]);
+    shape[axis] *= 2;
+    let mut upsampled = Array::zeros(shape);
+    upsampled
+        .slice_mut(unsafe {
+            SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
+                start: 0,
+                end: None,
+                step: if i == axis { 2 } else { 1 },
+            }))
+            .unwrap()
+        })
+        .assign(&c);
+
+    match mode {
+        Mode::Symmetric => {
+            let mut padding = [[0; 2]; N];
+            padding[axis] = [1, 0];
+            upsampled.conv(
+                &kernel,
+                ConvMode::Explicit {
+                    padding,
+                    strides: [1; N],
+                },
+                PaddingMode::Zeros,
+            )
+        }
+        Mode::Periodization => {
+            let len = shape[axis] as isize;
+            let half = filter.len() as isize / 2;
+            let indices = (-half..len + half - 1)
+                .map(|i| i.rem_euclid(len) as usize)
+                .collect::<Vec<_>>();
+
+            upsampled.select(Axis(axis), &indices).conv(
+                &kernel,
+                ConvMode::Custom {
+                    padding: [0; N],
+                    strides: [1; N],
+                },
+                PaddingMode::Zeros,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn aligned_with_pywt() {
+        // pywt.dwt([1, 2, 3, 4, 5, 6], "db1")
+        let (ca, cd) = dwt(
+            &array![1., 2., 3., 4., 5., 6.],
+            Wavelet::Haar,
+            Mode::Symmetric,
+        )
+        .unwrap();
+        assert_close(
+            &ca,
+            &array![2.12132034356, 4.94974746831, 7.77817459305],
+            1e-9,
+        );
+        assert_close(
+            &cd,
+            &Array1::from_elem(3, -std::f64::consts::FRAC_1_SQRT_2),
+            1e-9,
+        );
+
+        let x = array![3., 7., 1., 1., -2., 5., 4.];
+
+        // pywt.dwt(x, "db2", "symmetric")
+        let (ca, cd) = dwt(&x, Wavelet::Db2, Mode::Symmetric).unwrap();
+        assert_close(
+            &ca,
+            &array![
+                5.65685424949436,
+                7.39923721108729,
+                0.2241438680438373,
+                3.595593074361708,
+                6.657455252839767
+            ],
+            1e-9,
+        );
+        assert_close(
+            &cd,
+            &array![
+                -2.449489742782447,
+                -1.603682253352987,
+                -4.441400563791168,
+                0.552313267263671,
+                3.73429378260561
+            ],
+            1e-9,
+        );
+
+        // pywt.dwt(x, "db2", "periodization")
+        let (ca, cd) = dwt(&x, Wavelet::Db2, Mode::Periodization).unwrap();
+        assert_close(
+            &ca,
+            &array![
+                5.880998117533248,
+                4.700219608894001,
+                -0.5869884443246467,
+                6.269226685187992
+            ],
+            1e-9,
+        );
+        assert_close(
+            &cd,
+            &array![
+                4.182581518688335,
+                0.672431604128542,
+                2.5696080796413767,
+                0.35355339059376867
+            ],
+            1e-9,
+        );
+
+        // pywt.wavedec([1, 2, 3, 4, 5, 6, 7, 8], "db1", level=2)
+        let (ca, details) = wavedec(
+            &array![1., 2., 3., 4., 5., 6., 7., 8.],
+            Wavelet::Haar,
+            Mode::Symmetric,
+            2,
+        )
+        .unwrap();
+        assert_close(&ca, &array![5., 13.], 1e-9);
+        assert_close(&details[0], &array![-2., -2.], 1e-9);
+        assert_close(
+            &details[1],
+            &Array1::from_elem(4, -std::f64::consts::FRAC_1_SQRT_2),
+            1e-9,
+        );
+
+        // haar on a 2x2 block, cA is the scaled sum, cH the difference of the rows
+        let (ca, [ch, cv, cd]) =
+            dwt2(&array![[1., 2.], [3., 5.]], Wavelet::Haar, Mode::Symmetric).unwrap();
+        assert_close(&ca, &array![[5.5]], 1e-9);
+        assert_close(&ch, &array![[-2.5]], 1e-9);
+        assert_close(&cv, &array![[-1.5]], 1e-9);
+        assert_close(&cd, &array![[0.5]], 1e-9);
+    }
+
+    #[test]
+    fn perfect_reconstruction() {
+        let x = Array1::from_shape_fn(29, |i| ((i * 7) % 11) as f64 - 4.5);
+        let image = Array2::from_shape_fn((19, 24), |(i, j)| ((i * 5 + j * 3) % 13) as f64);
+
+        for wavelet in [Wavelet::Haar, Wavelet::Db2, Wavelet::Db3, Wavelet::Db4] {
+            let [dec_lo, dec_hi, rec_lo, rec_hi] = wavelet.filter_bank::<f64>();
+            assert!((dec_lo.sum() - 2f64.sqrt()).abs() < 1e-9);
+            assert!(dec_hi.sum().abs() < 1e-9);
+            assert!((rec_lo.dot(&rec_lo) - 1.).abs() < 1e-9);
+            assert!((rec_hi.dot(&rec_hi) - 1.).abs() < 1e-9);
+
+            for mode in [Mode::Symmetric, Mode::Periodization] {
+                let (ca, cd) = dwt(&x, wavelet, mode).unwrap();
+                let y = idwt(&ca, &cd, wavelet, mode).unwrap();
+                assert_close(&y.slice_move(ndarray::s![..29]), &x, 1e-9);
+
+                let (ca, details) = wavedec(&x, wavelet, mode, 2).unwrap();
+                let y = waverec(&ca, &details, wavelet, mode).unwrap();
+                assert_close(&y.slice_move(ndarray::s![..29]), &x, 1e-9);
+
+                let (ca, details) = wavedec2(&image, wavelet, mode, 2).unwrap();
+                let y = waverec2(&ca, &details, wavelet, mode).unwrap();
+                assert_close(&y.slice_move(ndarray::s![..19, ..]), &image, 1e-9);
+            }
+        }
+
+        // a filter longer than the signal keeps reflecting, pywt.dwt([1, 2, 3], "db4")
+        let (ca, _) = dwt(&array![1., 2., 3.], Wavelet::Db4, Mode::Symmetric).unwrap();
+        assert_close(
+            &ca,
+            &array![
+                3.1372219164469706,
+                4.010955699625437,
+                1.3371037581661607,
+                3.1372219164469706,
+                4.010955699625437
+            ],
+            1e-9,
+        );
+        let (ca, cd) = dwt(&array![1., 2., 3.], Wavelet::Db4, Mode::Periodization).unwrap();
+        assert_close(&ca, &array![4.276460027933272, 2.087501002745654], 1e-9);
+        assert_close(
+            &idwt(&ca, &cd, Wavelet::Db4, Mode::Periodization).unwrap(),
+            &array![1., 2., 3., 3.],
+            1e-9,
+        );
+
+        let ca = array![1., 2., 3.];
+        assert!(idwt(&ca, &array![1., 2.], Wavelet::Haar, Mode::Symmetric).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt::Debug;
+
+use ndarray::{Array2, ArrayBase, Data, Ix2, RawData};
+use num::traits::{Float, NumAssign};
+
+use crate::{
+    kernels::{gaussian, gaussian_derivative},
+    separable::SeparableConvExt,
+    ConvMode, GaussianExt, PaddingMode,
+};
+
+// kernel radius in sigmas, as in the gaussian module
+const TRUNCATE: f64 = 4.0;
+
+pub trait StructureTensorExt<T, S>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// `[Arr, Arc, Acc]`, the products of the row and column derivatives at scale
+    /// `sigma_d` (gaussian derivatives), each smoothed by a gaussian of `sigma_i`.
+    /// a `sigma_d` of zero takes central differences.
+    fn structure_tensor(
+        &self,
+        sigma_d: f64,
+        sigma_i: f64,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<[Array2<T>; 3], crate::Error<2>>;
+
+    /// `det(A) - k * trace(A)^2` of the structure tensor, positive at corners,
+    /// negative along edges. `k` is usually 0.04 to 0.06.
+    fn harris_response(
+        &self,
+        sigma_d: f64,
+        sigma_i: f64,
+        k: T,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<Array2<T>, crate::Error<2>>;
+}
+
+impl<T, S> StructureTensorExt<T, S> for ArrayBase<S, Ix2>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+{
+    fn structure_tensor(
+        &self,
+        sigma_d: f64,
+        sigma_i: f64,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<[Array2<T>; 3], crate::Error<2>> {
+        let invalid = |_| {
+            crate::Error::InvalidParameter(format!(
+                "sigmas must be finite and non-negative, got {sigma_d} and {sigma_i}"
+            ))
+        };
+        let smoothing = gaussian::<T>(sigma_d, TRUNCATE).map_err(invalid)?;
+        let derivative = gaussian_derivative::<T>(sigma_d, TRUNCATE).map_err(invalid)?;
+
+        let rows = self.conv_separable(
+            [derivative.view(), smoothing.view()],
+            ConvMode::Same,
+            padding_mode,
+        )?;
+        let cols = self.conv_separable(
+            [smoothing.view(), derivative.view()],
+            ConvMode::Same,
+            padding_mode,
+        )?;
+
+        let integrate =
+            |a: &Array2<T>, b: &Array2<T>| (a * b).gaussian_blur([sigma_i; 2], padding_mode);
+        Ok([
+            integrate(&rows, &rows)?,
+            integrate(&rows, &cols)?,
+            integrate(&cols, &cols)?,
+        ])
+    }
+
+    fn harris_response(
+        &self,
+        sigma_d: f64,
+        sigma_i: f64,
+        k: T,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<Array2<T>, crate::Error<2>> {
+        let [rr, rc, cc] = self.structure_tensor(sigma_d, sigma_i, padding_mode)?;
+
+        let mut response = &rr * &cc - &rc * &rc;
+        response.zip_mut_with(&(&rr + &cc), |r, &trace| *r -= k * trace * trace);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array2};
+
+    use super::*;
+
+    #[test]
+    fn structure_tensor() {
+        let ramp = Array2::from_shape_fn((24, 25), |(r, c)| 2. * r as f64 + 3. * c as f64);
+
+        for sigma_d in [0., 1.] {
+            let [rr, rc, cc] = ramp
+                .structure_tensor(sigma_d, 1., PaddingMode::Zeros)
+                .unwrap();
+            // far enough from the borders for both gaussians, up to their truncated tails
+            let inner = s![10..14, 10..15];
+            for (a, expected) in [(rr, 4.), (rc, 6.), (cc, 9.)] {
+                a.slice(inner)
+                    .iter()
+                    .for_each(|v| assert!((v / expected - 1.).abs() < 1e-3, "{v} != {expected}"));
+            }
+        }
+
+        // a bright quadrant: a corner at its tip, edges along its sides
+        let quadrant = Array2::from_shape_fn((40, 40), |(r, c)| (r >= 20 && c >= 20) as u8 as f64);
+        let response = quadrant
+            .harris_response(1., 1.5, 0.05, PaddingMode::Replicate)
+            .unwrap();
+        let (peak, _) = response
+            .indexed_iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        assert!(
+            peak.0.abs_diff(20) <= 1 && peak.1.abs_diff(20) <= 1,
+            "{peak:?}"
+        );
+        assert!(response[[35, 20]] < 0.);
+        assert!(response[[5, 5]] == 0.);
+
+        assert!(ramp.structure_tensor(-1., 1., PaddingMode::Zeros).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+//! C interface of `ConvExt::conv`, see include/ndarray_conv.h. the ndarray-conv-ffi crate
+//! (ffi/ in the repository) exports it as a cdylib, `cargo build --release -p ndarray-conv-ffi`.
+//!
+//! every `ndarray_conv_{f32,f64}_{1,2,3}d` function reads the input and kernel through
+//! their shapes and strides (in elements, null for row major), writes the output shape
+//! to `output_shape` and the output in row major order to `output`.
+//! a null `output` only queries the output shape.
+
+use std::{
+    ffi::{c_char, CStr},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use ndarray::{
+    ArrayView, ArrayViewMut, Axis, Dim, IntoDimension, Ix, RemoveAxis, ShapeBuilder, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{conv_output_shape, ConvExt, ConvMode, PaddingMode};
+
+pub const NDARRAY_CONV_OK: i32 = 0;
+pub const NDARRAY_CONV_NULL_POINTER: i32 = 1;
+pub const NDARRAY_CONV_INVALID_ARGUMENT: i32 = 2;
+pub const NDARRAY_CONV_INVALID_SHAPE: i32 = 3;
+pub const NDARRAY_CONV_OUTPUT_TOO_SMALL: i32 = 4;
+pub const NDARRAY_CONV_PANIC: i32 = 5;
+
+pub const NDARRAY_CONV_MODE_FULL: i32 = 0;
+pub const NDARRAY_CONV_MODE_SAME: i32 = 1;
+pub const NDARRAY_CONV_MODE_VALID: i32 = 2;
+pub const NDARRAY_CONV_MODE_CIRCULAR: i32 = 3;
+pub const NDARRAY_CONV_MODE_CAUSAL: i32 = 4;
+
+pub const NDARRAY_CONV_PADDING_ZEROS: i32 = 0;
+pub const NDARRAY_CONV_PADDING_CONST: i32 = 1;
+pub const NDARRAY_CONV_PADDING_REFLECT: i32 = 2;
+pub const NDARRAY_CONV_PADDING_SYMMETRIC: i32 = 3;
+pub const NDARRAY_CONV_PADDING_REPLICATE: i32 = 4;
+pub const NDARRAY_CONV_PADDING_CIRCULAR: i32 = 5;
+
+/// description of a status code, as a static nul terminated string.
+#[no_mangle]
+pub extern "C" fn ndarray_conv_status_message(status: i32) -> *const c_char {
+    let message: &CStr = match status {
+        NDARRAY_CONV_OK => c"ok",
+        NDARRAY_CONV_NULL_POINTER => c"null pointer",
+        NDARRAY_CONV_INVALID_ARGUMENT => c"invalid mode, padding or strides",
+        NDARRAY_CONV_INVALID_SHAPE => c"shapes don't fit the mode",
+        NDARRAY_CONV_OUTPUT_TOO_SMALL => c"output buffer too small",
+        NDARRAY_CONV_PANIC => c"panic",
+        _ => c"unknown status",
+    };
+    message.as_ptr()
+}
+
+macro_rules! export {
+    ($($name:ident: $t:ty, $n:literal);* $(;)?) => {
+        $(
+            /// # Safety
+            ///
+            /// the shapes point to `N` elements, the strides to `N` elements or null,
+            /// input and kernel hold every element their shape and strides reach,
+            /// output holds `output_len` elements or is null.
+            #[no_mangle]
+            #[allow(clippy::too_many_arguments)]
+            pub unsafe extern "C" fn $name(
+                input: *const $t,
+                input_shape: *const usize,
+                input_strides: *const isize,
+                kernel: *const $t,
+                kernel_shape: *const usize,
+                kernel_strides: *const isize,
+                mode: i32,
+                padding: i32,
+                padding_value: $t,
+                output: *mut $t,
+                output_len: usize,
+                output_shape: *mut usize,
+            ) -> i32 {
+                catch_unwind(AssertUnwindSafe(|| {
+                    conv::<$t, $n>(
+                        (input, input_shape, input_strides),
+                        (kernel, kernel_shape, kernel_strides),
+                        mode,
+                        padding,
+                        padding_value,
+                        (output, output_len, output_shape),
+                    )
+                }))
+                .unwrap_or(NDARRAY_CONV_PANIC)
+            }
+        )*
+    };
+}
+
+export!(
+    ndarray_conv_f32_1d: f32, 1;
+    ndarray_conv_f32_2d: f32, 2;
+    ndarray_conv_f32_3d: f32, 3;
+    ndarray_conv_f64_1d: f64, 1;
+    ndarray_conv_f64_2d: f64, 2;
+    ndarray_conv_f64_3d: f64, 3;
+);
+
+unsafe fn conv<T, const N: usize>(
+    (input, input_shape, input_strides): (*const T, *const usize, *const isize),
+    (kernel, kernel_shape, kernel_strides): (*const T, *const usize, *const isize),
+    mode: i32,
+    padding: i32,
+    padding_value: T,
+    (output, output_len, output_shape): (*mut T, usize, *mut usize),
+) -> i32
+where
+    T: NumAssign + Copy + std::fmt::Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    if input.is_null()
+        || input_shape.is_null()
+        || kernel.is_null()
+        || kernel_shape.is_null()
+        || output_shape.is_null()
+    {
+        return NDARRAY_CONV_NULL_POINTER;
+    }
+
+    let conv_mode = match mode {
+        NDARRAY_CONV_MODE_FULL => ConvMode::Full,
+        NDARRAY_CONV_MODE_SAME => ConvMode::Same,
+        NDARRAY_CONV_MODE_VALID => ConvMode::Valid,
+        NDARRAY_CONV_MODE_CIRCULAR => ConvMode::Circular,
+        NDARRAY_CONV_MODE_CAUSAL => ConvMode::Causal { axis: N - 1 },
+        _ => return NDARRAY_CONV_INVALID_ARGUMENT,
+    };
+    let padding_mode = match padding {
+        NDARRAY_CONV_PADDING_ZEROS => PaddingMode::Zeros,
+        NDARRAY_CONV_PADDING_CONST => PaddingMode::Const(padding_value),
+        NDARRAY_CONV_PADDING_REFLECT => PaddingMode::Reflect,
+        NDARRAY_CONV_PADDING_SYMMETRIC => PaddingMode::Symmetric,
+        NDARRAY_CONV_PADDING_REPLICATE => PaddingMode::Replicate,
+        NDARRAY_CONV_PADDING_CIRCULAR => PaddingMode::Circular,
+        _ => return NDARRAY_CONV_INVALID_ARGUMENT,
+    };
+
+    let input_shape: [usize; N] = *(input_shape as *const [usize; N]);
+    let kernel_shape: [usize; N] = *(kernel_shape as *const [usize; N]);
+
+    let shape = match conv_output_shape(input_shape, kernel_shape, 1, conv_mode) {
+        Ok(shape) => shape,
+        Err(err) => return status(err),
+    };
+    (output_shape as *mut [usize; N]).write(shape);
+
+    if output.is_null() {
+        return NDARRAY_CONV_OK;
+    }
+    if output_len < shape.iter().product() {
+        return NDARRAY_CONV_OUTPUT_TOO_SMALL;
+    }
+
+    let input = view(input, input_shape, input_strides);
+    let kernel = view(kernel, kernel_shape, kernel_strides);
+
+    match input.conv(&kernel, conv_mode, padding_mode) {
+        Ok(ret) => {
+            ArrayViewMut::from_shape_ptr(shape, output).assign(&ret);
+            NDARRAY_CONV_OK
+        }
+        Err(err) => status(err),
+    }
+}
+
+// negative strides start from the lowest address and the axis is inverted back
+unsafe fn view<'a, T, const N: usize>(
+    ptr: *const T,
+    shape: [usize; N],
+    strides: *const isize,
+) -> ArrayView<'a, T, Dim<[Ix; N]>>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    if strides.is_null() {
+        return ArrayView::from_shape_ptr(shape, ptr);
+    }
+
+    let strides: [isize; N] = *(strides as *const [isize; N]);
+    let low = (0..N)
+        .filter(|&i| strides[i] < 0)
+        .map(|i| strides[i] * shape[i].saturating_sub(1) as isize)
+        .sum();
+
+    let mut view = ArrayView::from_shape_ptr(
+        shape.strides(strides.map(|s| s.unsigned_abs())),
+        ptr.wrapping_offset(low),
+    );
+    (0..N)
+        .filter(|&i| strides[i] < 0)
+        .for_each(|i| view.invert_axis(Axis(i)));
+    view
+}
+
+fn status<const N: usize>(err: crate::Error<N>) -> i32 {
+    match err {
+        crate::Error::InvalidParameter(_)
+        | crate::Error::ZeroStride(_)
+        | crate::Error::ZeroDilation(_) => NDARRAY_CONV_INVALID_ARGUMENT,
+        _ => NDARRAY_CONV_INVALID_SHAPE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr::{null, null_mut};
+
+    use ndarray::{array, s, Array};
+
+    use super::*;
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array::from_shape_fn((5, 6), |(i, j)| (i * 6 + j) as f32 - 7.);
+        let kernel = array![[1f32, -2., 0.5], [0., 3., 1.]];
+
+        // query the shape, then run
+        let mut shape = [0; 2];
+        let status = unsafe {
+            ndarray_conv_f32_2d(
+                arr.as_ptr(),
+                [5, 6].as_ptr(),
+                null(),
+                kernel.as_ptr(),
+                [2, 3].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_FULL,
+                NDARRAY_CONV_PADDING_CONST,
+                2.,
+                null_mut(),
+                0,
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!((status, shape), (NDARRAY_CONV_OK, [6, 8]));
+
+        let mut output = vec![0f32; 48];
+        let status = unsafe {
+            ndarray_conv_f32_2d(
+                arr.as_ptr(),
+                [5, 6].as_ptr(),
+                null(),
+                kernel.as_ptr(),
+                [2, 3].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_FULL,
+                NDARRAY_CONV_PADDING_CONST,
+                2.,
+                output.as_mut_ptr(),
+                output.len(),
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NDARRAY_CONV_OK);
+        assert_eq!(
+            Array::from_shape_vec((6, 8), output).unwrap(),
+            arr.conv(&kernel, ConvMode::Full, PaddingMode::Const(2.))
+                .unwrap()
+        );
+
+        // a flipped, strided view of the input
+        let view = arr.slice(s![..;-1, ..;2]);
+        let strides: Vec<isize> = view.strides().to_vec();
+        let mut output = vec![0f64; 15];
+        let status = unsafe {
+            ndarray_conv_f64_2d(
+                view.mapv(|v| v as f64).as_ptr(),
+                [5, 3].as_ptr(),
+                null(),
+                kernel.mapv(|v| v as f64).as_ptr(),
+                [2, 3].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_SAME,
+                NDARRAY_CONV_PADDING_REFLECT,
+                0.,
+                output.as_mut_ptr(),
+                output.len(),
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NDARRAY_CONV_OK);
+        let mut strided = vec![0f32; 15];
+        let status = unsafe {
+            ndarray_conv_f32_2d(
+                view.as_ptr(),
+                [5, 3].as_ptr(),
+                strides.as_ptr(),
+                kernel.as_ptr(),
+                [2, 3].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_SAME,
+                NDARRAY_CONV_PADDING_REFLECT,
+                0.,
+                strided.as_mut_ptr(),
+                strided.len(),
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NDARRAY_CONV_OK);
+        assert_eq!(
+            strided,
+            output.iter().map(|&v| v as f32).collect::<Vec<_>>()
+        );
+
+        let status = unsafe {
+            ndarray_conv_f32_2d(
+                arr.as_ptr(),
+                [5, 6].as_ptr(),
+                null(),
+                kernel.as_ptr(),
+                [2, 3].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_SAME,
+                NDARRAY_CONV_PADDING_ZEROS,
+                0.,
+                strided.as_mut_ptr(),
+                strided.len(),
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NDARRAY_CONV_OUTPUT_TOO_SMALL);
+
+        let status = unsafe {
+            ndarray_conv_f32_1d(
+                arr.as_ptr(),
+                [3].as_ptr(),
+                null(),
+                kernel.as_ptr(),
+                [4].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_VALID,
+                7,
+                0.,
+                null_mut(),
+                0,
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NDARRAY_CONV_INVALID_ARGUMENT);
+
+        let status = unsafe {
+            ndarray_conv_f32_1d(
+                arr.as_ptr(),
+                [3].as_ptr(),
+                null(),
+                kernel.as_ptr(),
+                [4].as_ptr(),
+                null(),
+                NDARRAY_CONV_MODE_VALID,
+                NDARRAY_CONV_PADDING_ZEROS,
+                0.,
+                null_mut(),
+                0,
+                shape.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, NDARRAY_CONV_INVALID_SHAPE);
+    }
+}
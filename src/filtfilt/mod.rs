@@ -0,0 +1,173 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{ConvExt, ConvMode, PaddingMode};
+
+pub trait FiltFiltExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// zero-phase filtering along the last axis, same as `scipy.signal.filtfilt(kernel, 1, x)`:
+    /// the FIR runs forward then backward, so the output has the squared magnitude response
+    /// and no delay. the input is extended by `3 * kernel.len()` odd samples on both ends
+    /// to settle the transients, so the axis must be longer than that.
+    fn filtfilt<SK: Data<Elem = T>>(
+        &self,
+        kernel: &ArrayBase<SK, Dim<[Ix; 1]>>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// `filtfilt` along `axis`.
+    fn filtfilt_axis<SK: Data<Elem = T>>(
+        &self,
+        kernel: &ArrayBase<SK, Dim<[Ix; 1]>>,
+        axis: usize,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> FiltFiltExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn filtfilt<SK: Data<Elem = T>>(
+        &self,
+        kernel: &ArrayBase<SK, Dim<[Ix; 1]>>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        self.filtfilt_axis(kernel, N.saturating_sub(1))
+    }
+
+    fn filtfilt_axis<SK: Data<Elem = T>>(
+        &self,
+        kernel: &ArrayBase<SK, Dim<[Ix; 1]>>,
+        axis: usize,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if axis >= N {
+            return Err(crate::Error::InvalidParameter(format!(
+                "axis {axis} out of range for {N} dimensions"
+            )));
+        }
+        if kernel.is_empty() {
+            return Err(crate::Error::InvalidParameter(
+                "kernel shouldn't be empty".to_string(),
+            ));
+        }
+
+        let n = self.shape()[axis];
+        let taps = kernel.len();
+        let pad = 3 * taps;
+        if n <= pad {
+            return Err(crate::Error::InvalidParameter(format!(
+                "axis {axis} of length {n} must be longer than 3 * the {taps} taps"
+            )));
+        }
+
+        // odd extension: the samples past an end are reflected about it, then
+        // mirrored through its value
+        let two = T::one() + T::one();
+        let mut shape: [usize; N] = std::array::from_fn(|i| self.shape()[i]);
+        shape[axis] += 2 * pad;
+        let extended = Array::from_shape_fn(shape, |index| {
+            let mut index = index.into_dimension();
+            let i = index[axis] as isize - pad as isize;
+            let (edge, mirror) = if i < 0 {
+                (0, -i as usize)
+            } else if i as usize >= n {
+                (n - 1, 2 * (n - 1) - i as usize)
+            } else {
+                index[axis] = i as usize;
+                return self[index];
+            };
+            let mut at = index;
+            index[axis] = edge;
+            at[axis] = mirror;
+            two * self[index] - self[at]
+        });
+
+        // lfilter starting from the steady state of the first sample is a causal conv
+        // over that sample replicated, so replicated borders stand in for scipy's `zi`.
+        // conv being a correlation, the forward pass takes the reversed taps
+        let along = |reversed: bool| {
+            let mut shape = [1; N];
+            shape[axis] = taps;
+            let taps = kernel.iter().copied();
+            let taps = if reversed {
+                taps.rev().collect()
+            } else {
+                taps.collect()
+            };
+            Array::from_shape_vec(shape, taps).unwrap()
+        };
+        let pass = |padding: [usize; 2]| {
+            let mut explicit = [[0; 2]; N];
+            explicit[axis] = padding;
+            ConvMode::Explicit {
+                padding: explicit,
+                strides: [1; N],
+            }
+        };
+
+        let forward = extended.conv(&along(true), pass([taps - 1, 0]), PaddingMode::Replicate)?;
+        let backward = forward.conv(&along(false), pass([0, taps - 1]), PaddingMode::Replicate)?;
+
+        Ok(backward
+            .slice_axis(ndarray::Axis(axis), (pad..pad + n).into())
+            .to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array1, Array2, Axis};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn aligned_with_scipy() {
+        let x = array![1f64, 3., -2., 4., 0., 5., 2., -1., 3., 6.];
+        for (kernel, expected) in [
+            // scipy.signal.filtfilt(kernel, 1, x)
+            (
+                array![0.25, 0.5, 0.25],
+                array![1., 1.0625, 1.0625, 1.5, 2.25, 2.5625, 1.9375, 1.5625, 3.0625, 6.],
+            ),
+            (
+                array![0.5, 0.25, 0.25],
+                array![1., 1.3125, 0.6875, 2.125, 1.6875, 2.625, 1.875, 1.9375, 3.4375, 6.],
+            ),
+        ] {
+            let res = x.filtfilt(&kernel).unwrap();
+            assert_close(&res, &expected, 1e-12);
+        }
+
+        // zero phase: a symmetric pulse stays centered whatever the kernel
+        let pulse = Array1::from_shape_fn(41, |i| (-((i as f64 - 20.) / 3.).powi(2)).exp());
+        let res = pulse.filtfilt(&array![0.6, 0.3, 0.1]).unwrap();
+        (0..20).for_each(|i| assert!((res[20 - i] - res[20 + i]).abs() < 1e-9));
+
+        // along an axis of a 2D array, the same as its lanes
+        let arr = Array2::from_shape_fn((4, 13), |(i, j)| ((i * 13 + j) * 7 % 11) as f64);
+        let kernel = array![1., -2., 0.5];
+        let rows = arr.filtfilt(&kernel).unwrap();
+        let cols = arr.t().filtfilt_axis(&kernel, 0).unwrap();
+        for (i, lane) in arr.axis_iter(Axis(0)).enumerate() {
+            let expected = lane.filtfilt(&kernel).unwrap();
+            assert_eq!(rows.index_axis(Axis(0), i), expected);
+            assert_eq!(cols.index_axis(Axis(1), i), expected);
+        }
+
+        assert!(arr.filtfilt_axis(&kernel, 0).is_err());
+        assert!(arr.filtfilt_axis(&kernel, 2).is_err());
+        assert!(x.filtfilt(&Array1::<f64>::zeros(0)).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::f64::consts::PI;
+
+use ndarray::Array1;
+use num::traits::Float;
+
+/// Window applied to the ideal (sinc) impulse response.
+#[derive(Debug, Clone, Copy)]
+pub enum Window {
+    Hamming,
+    Blackman,
+    Kaiser(f64),
+}
+
+impl Window {
+    // symmetric window, same as scipy.signal.get_window(.., fftbins=False)
+    fn coefficients(self, taps: usize) -> Vec<f64> {
+        if taps == 1 {
+            return vec![1.];
+        }
+
+        let m = (taps - 1) as f64;
+        (0..taps)
+            .map(|n| {
+                let n = n as f64;
+                match self {
+                    Window::Hamming => 0.54 - 0.46 * (2. * PI * n / m).cos(),
+                    Window::Blackman => {
+                        0.42 - 0.5 * (2. * PI * n / m).cos() + 0.08 * (4. * PI * n / m).cos()
+                    }
+                    Window::Kaiser(beta) => {
+                        let r = 2. * n / m - 1.;
+                        bessel_i0(beta * (1. - r * r).max(0.).sqrt()) / bessel_i0(beta)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Lowpass FIR filter, `cutoff` is relative to the Nyquist frequency (0, 1).
+pub fn lowpass<T: Float>(
+    cutoff: f64,
+    taps: usize,
+    window: Window,
+) -> Result<Array1<T>, crate::Error<1>> {
+    check_cutoff(cutoff)?;
+    design(&[(0., cutoff)], taps, window)
+}
+
+/// Highpass FIR filter, `taps` must be odd to pass the Nyquist frequency.
+pub fn highpass<T: Float>(
+    cutoff: f64,
+    taps: usize,
+    window: Window,
+) -> Result<Array1<T>, crate::Error<1>> {
+    check_cutoff(cutoff)?;
+    if taps % 2 != 1 {
+        return Err(crate::Error::InvalidParameter(format!(
+            "highpass filter requires an odd number of taps, got {taps}"
+        )));
+    }
+    design(&[(cutoff, 1.)], taps, window)
+}
+
+/// Bandpass FIR filter passing `[low, high]`, relative to the Nyquist frequency.
+pub fn bandpass<T: Float>(
+    low: f64,
+    high: f64,
+    taps: usize,
+    window: Window,
+) -> Result<Array1<T>, crate::Error<1>> {
+    check_cutoff(low)?;
+    check_cutoff(high)?;
+    if low >= high {
+        return Err(crate::Error::InvalidParameter(format!(
+            "bandpass requires low < high, got [{low}, {high}]"
+        )));
+    }
+    design(&[(low, high)], taps, window)
+}
+
+fn check_cutoff(cutoff: f64) -> Result<(), crate::Error<1>> {
+    if cutoff > 0. && cutoff < 1. {
+        Ok(())
+    } else {
+        Err(crate::Error::InvalidParameter(format!(
+            "cutoff must be in (0, 1), got {cutoff}"
+        )))
+    }
+}
+
+// same as scipy.signal.firwin with scale = True
+fn design<T: Float>(
+    bands: &[(f64, f64)],
+    taps: usize,
+    window: Window,
+) -> Result<Array1<T>, crate::Error<1>> {
+    if taps == 0 {
+        return Err(crate::Error::InvalidParameter(
+            "taps must be greater than 0".to_string(),
+        ));
+    }
+
+    let alpha = 0.5 * (taps - 1) as f64;
+    let m = (0..taps).map(|i| i as f64 - alpha).collect::<Vec<_>>();
+
+    let mut h = m
+        .iter()
+        .zip(window.coefficients(taps))
+        .map(|(&m, w)| {
+            bands
+                .iter()
+                .map(|&(left, right)| right * sinc(right * m) - left * sinc(left * m))
+                .sum::<f64>()
+                * w
+        })
+        .collect::<Vec<_>>();
+
+    // unit gain at the center of the first passband
+    let (left, right) = bands[0];
+    let scale_frequency = if left == 0. {
+        0.
+    } else if right == 1. {
+        1.
+    } else {
+        0.5 * (left + right)
+    };
+    let s = h
+        .iter()
+        .zip(&m)
+        .map(|(h, m)| h * (PI * m * scale_frequency).cos())
+        .sum::<f64>();
+    h.iter_mut().for_each(|h| *h /= s);
+
+    Ok(h.into_iter().map(|h| T::from(h).unwrap()).collect())
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0. {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+// modified bessel function of the first kind, order 0
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.;
+    let mut term = 1.;
+    let mut k = 1.;
+    while term > sum * 1e-16 {
+        term *= (x / (2. * k)).powi(2);
+        sum += term;
+        k += 1.;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn firwin() {
+        let h = lowpass::<f64>(0.5, 3, Window::Hamming).unwrap();
+        let expect = [
+            0.08 / std::f64::consts::PI,
+            0.5,
+            0.08 / std::f64::consts::PI,
+        ];
+        let sum = expect.iter().sum::<f64>();
+        h.iter()
+            .zip(expect)
+            .for_each(|(h, e)| assert!((h - e / sum).abs() < 1e-12));
+
+        // unit gain at DC / Nyquist / band center
+        let h = lowpass::<f64>(0.3, 31, Window::Kaiser(8.6)).unwrap();
+        assert!((h.sum() - 1.).abs() < 1e-12);
+
+        let h = highpass::<f64>(0.3, 31, Window::Blackman).unwrap();
+        let nyquist = h
+            .iter()
+            .enumerate()
+            .map(|(i, h)| if i % 2 == 1 { *h } else { -h })
+            .sum::<f64>();
+        assert!((nyquist - 1.).abs() < 1e-12);
+        assert!(h
+            .iter()
+            .zip(h.iter().rev())
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+
+        // scipy.signal.firwin(7, [0.2, 0.4], pass_zero=False)
+        let h = bandpass::<f64>(0.2, 0.4, 7, Window::Hamming).unwrap();
+        let expect = [
+            -0.03835111970754954,
+            -0.05262330172324121,
+            0.26141898275416614,
+            0.5872124008602364,
+            0.2614189827541662,
+            -0.05262330172324121,
+            -0.03835111970754954,
+        ];
+        h.iter()
+            .zip(expect)
+            .for_each(|(h, e)| assert!((h - e).abs() < 1e-12));
+
+        let h = bandpass::<f64>(0.2, 0.4, 31, Window::Hamming).unwrap();
+        assert_eq!(h.len(), 31);
+        assert!(h
+            .iter()
+            .zip(h.iter().rev())
+            .all(|(a, b)| (a - b).abs() < 1e-12));
+        let gain = |f: f64| {
+            let (re, im) = h.iter().enumerate().fold((0., 0.), |(re, im), (i, h)| {
+                let w = PI * f * i as f64;
+                (re + h * w.cos(), im - h * w.sin())
+            });
+            (re * re + im * im).sqrt()
+        };
+        assert!((gain(0.3) - 1.).abs() < 1e-12);
+        // hamming stopband, below -40 dB out of the transition bands
+        assert!((0..=50)
+            .map(|i| i as f64 / 1000.)
+            .chain((550..=1000).map(|i| i as f64 / 1000.))
+            .all(|f| gain(f) < 0.01));
+
+        assert!(highpass::<f64>(0.3, 30, Window::Hamming).is_err());
+        assert!(bandpass::<f64>(0.4, 0.2, 31, Window::Hamming).is_err());
+        assert!(lowpass::<f64>(1.5, 31, Window::Hamming).is_err());
+    }
+
+    #[test]
+    fn kaiser_window() {
+        // scipy.signal.get_window(("kaiser", 0.), 5, fftbins=False) is rectangular
+        assert!(Window::Kaiser(0.)
+            .coefficients(5)
+            .iter()
+            .all(|&w| (w - 1.).abs() < 1e-12));
+
+        let w = Window::Kaiser(5.).coefficients(5);
+        assert!((w[2] - 1.).abs() < 1e-12);
+        assert!((w[0] - 1. / bessel_i0(5.)).abs() < 1e-12);
+    }
+}
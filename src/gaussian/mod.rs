@@ -0,0 +1,246 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Axis, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::{
+    traits::{Float, NumAssign},
+    Complex,
+};
+
+use crate::{padding::PaddingExt, separable::SeparableConvExt, ConvMode, PaddingMode};
+
+// kernel radius in sigmas, same default as scipy.ndimage.gaussian_filter
+const TRUNCATE: f64 = 4.0;
+// sigma from which gaussian_blur_recursive runs the recursion
+const RECURSIVE_SIGMA: f64 = 2.0;
+
+pub trait GaussianExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// separable gaussian blur with one sigma per axis, the output has the input's shape.
+    /// a sigma of zero leaves that axis untouched.
+    fn gaussian_blur(
+        &self,
+        sigma: [f64; N],
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// `gaussian_blur` through the recursive filter of Young, van Vliet & van Ginkel, a third
+    /// order IIR run forward then backward along every axis: the cost doesn't grow with sigma,
+    /// so it beats the FIR for sigmas past about 10. the fit of the recursion is off from the
+    /// FIR by up to 3% of the largest magnitude of the input (the impulse response by 2% of
+    /// its peak at sigma 2, 1% for large sigmas); sigmas below 2, where the recursion is less
+    /// accurate and the FIR short anyway, go through the FIR.
+    fn gaussian_blur_recursive(
+        &self,
+        sigma: [f64; N],
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> GaussianExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn gaussian_blur(
+        &self,
+        sigma: [f64; N],
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if let Some(s) = sigma.iter().find(|s| !(s.is_finite() && **s >= 0.)) {
+            return Err(crate::Error::InvalidParameter(format!(
+                "sigma must be finite and non-negative, got {s}"
+            )));
+        }
+
+        let kernels = sigma.map(|s| crate::kernels::gaussian::<T>(s, TRUNCATE).unwrap());
+
+        self.conv_separable(
+            std::array::from_fn(|i| kernels[i].view()),
+            ConvMode::Same,
+            padding_mode,
+        )
+    }
+
+    fn gaussian_blur_recursive(
+        &self,
+        sigma: [f64; N],
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if let Some(s) = sigma.iter().find(|s| !(s.is_finite() && **s >= 0.)) {
+            return Err(crate::Error::InvalidParameter(format!(
+                "sigma must be finite and non-negative, got {s}"
+            )));
+        }
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+
+        let mut output = self.to_owned();
+        for (axis, &s) in sigma.iter().enumerate() {
+            if s < RECURSIVE_SIGMA {
+                let mut sigma = [0.; N];
+                sigma[axis] = s;
+                output = output.gaussian_blur(sigma, padding_mode)?;
+                continue;
+            }
+
+            // padded as much as the FIR would be, the transients of the recursion die out there
+            let radius = (TRUNCATE * s + 0.5) as usize;
+            let mut padding = [[0; 2]; N];
+            padding[axis] = [radius; 2];
+            let mut padded = output.padding(padding_mode, padding);
+
+            let [b, b1, b2, b3] = young_van_vliet(s).map(|c| T::from(c).unwrap());
+            for mut lane in padded.lanes_mut(Axis(axis)) {
+                let n = lane.len();
+                // both passes start from the steady state of the first sample they see
+                let (mut w1, mut w2, mut w3) = (lane[0], lane[0], lane[0]);
+                for i in 0..n {
+                    let w = b * lane[i] + b1 * w1 + b2 * w2 + b3 * w3;
+                    (w3, w2, w1) = (w2, w1, w);
+                    lane[i] = w;
+                }
+                let (mut y1, mut y2, mut y3) = (lane[n - 1], lane[n - 1], lane[n - 1]);
+                for i in (0..n).rev() {
+                    let y = b * lane[i] + b1 * y1 + b2 * y2 + b3 * y3;
+                    (y3, y2, y1) = (y2, y1, y);
+                    lane[i] = y;
+                }
+            }
+
+            let len = output.shape()[axis];
+            output = padded
+                .slice_axis(Axis(axis), (radius..radius + len).into())
+                .to_owned();
+        }
+
+        Ok(output)
+    }
+}
+
+// [B, b1, b2, b3] of the recursion of Young, van Vliet & van Ginkel (2002):
+// the poles fit for sigma 2, raised to 1 / q for the variance of the two passes to be sigma^2
+fn young_van_vliet(sigma: f64) -> [f64; 4] {
+    let poles = [
+        Complex::new(1.41650, 1.00829),
+        Complex::new(1.41650, -1.00829),
+        Complex::new(1.86543, 0.),
+    ];
+    let scaled = |q: f64| poles.map(|d| d.powf(1. / q));
+    let variance = |q: f64| {
+        scaled(q)
+            .iter()
+            .map(|&d| 2. * d / ((d - 1.) * (d - 1.)))
+            .sum::<Complex<f64>>()
+            .re
+    };
+
+    // the variance grows with q
+    let (mut low, mut high) = (0., sigma / 2. + 1.);
+    while variance(high) < sigma * sigma {
+        high *= 2.;
+    }
+    for _ in 0..64 {
+        let q = 0.5 * (low + high);
+        if variance(q) < sigma * sigma {
+            low = q;
+        } else {
+            high = q;
+        }
+    }
+
+    let [p1, p2, p3] = scaled(0.5 * (low + high)).map(|d| d.inv());
+    let b1 = (p1 + p2 + p3).re;
+    let b2 = -(p1 * p2 + p1 * p3 + p2 * p3).re;
+    let b3 = (p1 * p2 * p3).re;
+
+    [1. - (b1 + b2 + b3), b1, b2, b3]
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::ConvExt;
+
+    #[test]
+    fn gaussian_blur() {
+        let mut arr = Array2::<f64>::zeros((15, 21));
+        arr[[7, 10]] = 1.;
+
+        let res = arr.gaussian_blur([1., 2.], PaddingMode::Zeros).unwrap();
+        assert_eq!(res.shape(), arr.shape());
+        assert!((res.sum() - 1.).abs() < 1e-12);
+
+        let k0 = crate::kernels::gaussian::<f64>(1., TRUNCATE).unwrap();
+        let k1 = crate::kernels::gaussian::<f64>(2., TRUNCATE).unwrap();
+        let kernel = Array2::from_shape_fn((k0.len(), k1.len()), |(i, j)| k0[i] * k1[j]);
+        let expect = arr
+            .conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+            .unwrap();
+        assert!(res.iter().zip(&expect).all(|(a, b)| (a - b).abs() < 1e-12));
+
+        let arr = array![1f32, 2., 3.];
+        assert_eq!(
+            arr.gaussian_blur([0.], PaddingMode::Replicate).unwrap(),
+            arr
+        );
+        assert!(arr.gaussian_blur([-1.], PaddingMode::Zeros).is_err());
+    }
+
+    #[test]
+    fn gaussian_blur_recursive() {
+        let arr = Array2::from_shape_fn((60, 70), |(i, j)| ((i * 70 + j) * 7919 % 101) as f64);
+
+        // the documented bound, 3% of the largest magnitude of the input
+        let error = |fir: &Array2<f64>, iir: &Array2<f64>| {
+            (fir - iir).iter().fold(0f64, |m, e| m.max(e.abs()))
+        };
+
+        // values in 0..=100
+        for sigma in [[12., 3.], [0.3, 20.], [2., 0.], [0., 40.]] {
+            let fir = arr.gaussian_blur(sigma, PaddingMode::Reflect).unwrap();
+            let iir = arr
+                .gaussian_blur_recursive(sigma, PaddingMode::Reflect)
+                .unwrap();
+            let error = error(&fir, &iir);
+            assert!(error < 0.03 * 100., "{sigma:?}: {error}");
+        }
+
+        let mut impulse = Array2::<f64>::zeros((101, 3));
+        impulse[[50, 1]] = 1.;
+        for (sigma, peak) in [(2., 0.021), (15., 0.01)] {
+            let fir = impulse
+                .gaussian_blur([sigma, 0.], PaddingMode::Zeros)
+                .unwrap();
+            let iir = impulse
+                .gaussian_blur_recursive([sigma, 0.], PaddingMode::Zeros)
+                .unwrap();
+            assert!((iir.sum() - 1.).abs() < 1e-3, "{sigma}: {}", iir.sum());
+            let error = error(&fir, &iir);
+            assert!(error < 0.03, "{sigma}: {error}");
+            assert!(error < peak * fir[[50, 1]], "{sigma}: {error}");
+        }
+
+        // below the recursion's range, the FIR
+        assert_eq!(
+            arr.gaussian_blur_recursive([1., 0.], PaddingMode::Zeros)
+                .unwrap(),
+            arr.gaussian_blur([1., 0.], PaddingMode::Zeros).unwrap()
+        );
+        assert!(arr
+            .gaussian_blur_recursive([1., f64::NAN], PaddingMode::Zeros)
+            .is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use ndarray::{Array1, ArrayBase, Data, Ix1, RawData};
+use num::{Complex, Zero};
+use rustfft::FftNum;
+
+use crate::conv_fft::{FftBackend, Processor};
+
+pub trait HilbertExt<T, S>
+where
+    T: FftNum,
+    S: RawData,
+{
+    /// analytic signal, same as `scipy.signal.hilbert`: the real part is the input and the
+    /// imaginary part its hilbert transform, so `norm()` gives the envelope.
+    fn hilbert(&self) -> Array1<Complex<T>>;
+
+    /// `hilbert` with a reusable `Processor`, or any other `FftBackend`.
+    fn hilbert_with_processor(&self, fft_processor: &mut impl FftBackend<T>) -> Array1<Complex<T>>;
+}
+
+impl<T, S> HilbertExt<T, S> for ArrayBase<S, Ix1>
+where
+    T: FftNum,
+    S: Data<Elem = T>,
+{
+    fn hilbert(&self) -> Array1<Complex<T>> {
+        self.hilbert_with_processor(&mut Processor::default())
+    }
+
+    fn hilbert_with_processor(&self, fft_processor: &mut impl FftBackend<T>) -> Array1<Complex<T>> {
+        let n = self.len();
+        if n == 0 {
+            return Array1::zeros(0);
+        }
+
+        let mut analytic = self.mapv(|x| Complex::new(x, T::zero()));
+        fft_processor.complex(analytic.as_slice_mut().unwrap(), false);
+
+        // keep dc and nyquist, double the positive frequencies and drop the negative ones
+        let two = T::from_usize(2).unwrap();
+        analytic.iter_mut().enumerate().for_each(|(k, x)| {
+            if 2 * k > n {
+                x.set_zero();
+            } else if k != 0 && 2 * k != n {
+                *x = *x * two;
+            }
+        });
+
+        fft_processor.complex(analytic.as_slice_mut().unwrap(), true);
+        analytic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn aligned_with_scipy() {
+        // scipy.signal.hilbert([1, 2, 3, 4])
+        let res = array![1., 2., 3., 4.].hilbert();
+        let expected = array![
+            Complex::new(1., 1.),
+            Complex::new(2., -1.),
+            Complex::new(3., -1.),
+            Complex::new(4., 1.)
+        ];
+        res.iter()
+            .zip(expected.iter())
+            .for_each(|(a, b)| assert!((a - b).norm() < 1e-12));
+
+        // a cosine over whole periods becomes a complex exponential, of constant envelope
+        for n in [64, 63] {
+            let w = 2. * std::f64::consts::PI * 5. / n as f64;
+            let x = Array1::from_shape_fn(n, |i| 3. * (w * i as f64).cos());
+            x.hilbert().iter().enumerate().for_each(|(i, v)| {
+                assert!((v - Complex::from_polar(3., w * i as f64)).norm() < 1e-9);
+                assert!((v.norm() - 3.).abs() < 1e-9);
+            });
+        }
+
+        // one processor for several lengths
+        let mut processor = Processor::default();
+        for x in [array![1., 2., 3., 4.], array![0.5, -1., 2.5, 3., 0.]] {
+            assert_eq!(x.hilbert_with_processor(&mut processor), x.hilbert());
+        }
+
+        assert_eq!(array![2f32].hilbert(), array![Complex::new(2., 0.)]);
+        assert!(Array1::<f32>::zeros(0).hilbert().is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use ndarray::{
+    Array, ArrayBase, Axis, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{padding::PaddingExt, ConvMode, PaddingMode};
+
+pub trait IntegralImageExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// summed-area table with a leading zero on every axis,
+    /// `out[i + 1] = sum(self[..=i])` and the shape is the input shape + 1.
+    fn integral_image(&self) -> Array<T, Dim<[Ix; N]>>;
+
+    /// conv with a kernel of ones of shape `window_shape`, computed from the
+    /// summed-area table in O(2^N) per output regardless of the window size.
+    fn box_filter_sat(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> IntegralImageExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn integral_image(&self) -> Array<T, Dim<[Ix; N]>> {
+        let shape: [usize; N] = std::array::from_fn(|i| self.shape()[i] + 1);
+        let mut sat = Array::zeros(shape);
+
+        self.indexed_iter().for_each(|(index, &v)| {
+            let index = index.into_dimension();
+            let index: [usize; N] = std::array::from_fn(|i| index[i] + 1);
+            sat[index.into_dimension()] = v;
+        });
+
+        for axis in 0..N {
+            sat.accumulate_axis_inplace(Axis(axis), |&prev, cur| *cur += prev);
+        }
+
+        sat
+    }
+
+    fn box_filter_sat(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+        if window_shape.iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(window_shape.into_dimension()));
+        }
+
+        let cm =
+            conv_mode.unfold_with_dim(window_shape, std::array::from_fn(|i| self.shape()[i]))?;
+        let padded = self.padding(conv_mode.padding_mode(padding_mode), cm.padding);
+        if !(0..N).all(|i| window_shape[i] <= padded.shape()[i]) {
+            return Err(crate::Error::MismatchShape(conv_mode, window_shape));
+        }
+
+        let sat = padded.integral_image();
+        let output_shape: [usize; N] =
+            std::array::from_fn(|i| (padded.shape()[i] - window_shape[i]) / cm.strides[i] + 1);
+
+        Ok(Array::from_shape_fn(output_shape, |index| {
+            let index = index.into_dimension();
+
+            // inclusion–exclusion over the 2^N corners of the window.
+            // positive and negative corners are summed apart so unsigned types don't underflow
+            let (mut pos, mut neg) = (T::zero(), T::zero());
+            for corner in 0..1usize << N {
+                let at: [usize; N] = std::array::from_fn(|i| {
+                    let start = index[i] * cm.strides[i];
+                    if corner >> i & 1 == 1 {
+                        start + window_shape[i]
+                    } else {
+                        start
+                    }
+                });
+
+                if (N as u32 - corner.count_ones()) & 1 == 1 {
+                    neg += sat[at.into_dimension()];
+                } else {
+                    pos += sat[at.into_dimension()];
+                }
+            }
+            pos - neg
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array, Array2};
+
+    use super::*;
+    use crate::ConvExt;
+
+    #[test]
+    fn integral_image() {
+        let arr = array![[1, 2, 3], [4, 5, 6]];
+        assert_eq!(
+            arr.integral_image(),
+            array![[0, 0, 0, 0], [0, 1, 3, 6], [0, 5, 12, 21]]
+        );
+    }
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array::from_shape_fn((9, 13), |(i, j)| ((i * 7 + j * 3) % 11) as u32);
+        for (window_shape, conv_mode) in [
+            ([3, 3], ConvMode::Same),
+            ([4, 7], ConvMode::Full),
+            ([5, 2], ConvMode::Valid),
+            (
+                [3, 4],
+                ConvMode::Custom {
+                    padding: [2, 1],
+                    strides: [2, 3],
+                },
+            ),
+        ] {
+            assert_eq!(
+                arr.box_filter_sat(window_shape, conv_mode, PaddingMode::Replicate)
+                    .unwrap(),
+                arr.conv(
+                    &Array2::ones(window_shape),
+                    conv_mode,
+                    PaddingMode::Replicate
+                )
+                .unwrap()
+            );
+        }
+
+        let arr = Array::from_shape_fn((5, 6, 7), |(i, j, k)| (i * 5 + j * 3 + k) as i64 % 7 - 3);
+        assert_eq!(
+            arr.box_filter_sat([3, 2, 5], ConvMode::Same, PaddingMode::Reflect)
+                .unwrap(),
+            arr.conv(
+                &Array::ones([3, 2, 5]),
+                ConvMode::Same,
+                PaddingMode::Reflect
+            )
+            .unwrap()
+        );
+    }
+}
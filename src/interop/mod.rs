@@ -0,0 +1,4 @@
+//! conversions between the arrays of this crate and the tensors of other libraries.
+
+#[cfg(feature = "tch")]
+pub mod tch;
@@ -0,0 +1,72 @@
+//! `tch::Tensor` glue for checking results against PyTorch.
+//!
+//! torch's conv1d/2d/3d take (batch, channel, ..) inputs and (out channel, in channel, ..)
+//! weights, `to_tensor` adds both leading axes of size 1 and `from_tensor` drops them.
+
+use ndarray::{Array, ArrayBase, Data, Dim, Dimension, IntoDimension, Ix};
+use tch::{kind::Element, Device, Kind, Tensor};
+
+/// tensor of shape (1, 1, ..) of `arr` converted to `kind`.
+pub fn to_tensor<T, S, const N: usize>(arr: &ArrayBase<S, Dim<[Ix; N]>>, kind: Kind) -> Tensor
+where
+    T: Element + Copy,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: Dimension,
+{
+    let shape = [1, 1]
+        .into_iter()
+        .chain(arr.shape().iter().map(|&s| s as i64))
+        .collect::<Vec<_>>();
+
+    Tensor::from_slice(arr.as_standard_layout().as_slice().unwrap())
+        .reshape(shape)
+        .to_kind(kind)
+}
+
+/// array of a tensor of shape (1, 1, ..) or of `N` axes, the values converted to `T`.
+pub fn from_tensor<T, const N: usize>(
+    tensor: &Tensor,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: Element + Copy,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let size = tensor.size();
+    let size = match size.len() {
+        n if n == N + 2 && size[..2] == [1, 1] => &size[2..],
+        n if n == N => &size[..],
+        _ => {
+            return Err(crate::Error::InvalidParameter(format!(
+                "tensor of size {:?} for an array of {} dimensions",
+                size, N
+            )))
+        }
+    };
+    let shape: [usize; N] = std::array::from_fn(|i| size[i] as usize);
+
+    let values = Vec::<T>::try_from(&tensor.to_device(Device::Cpu).reshape([-1]))
+        .map_err(|err| crate::Error::InvalidParameter(err.to_string()))?;
+
+    Ok(Array::from_shape_vec(shape, values).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array3};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let arr = Array3::from_shape_fn((2, 3, 4), |(i, j, k)| (i * 12 + j * 4 + k) as i32);
+        let tensor = to_tensor(&arr.t(), Kind::Double);
+        assert_eq!(tensor.size(), [1, 1, 4, 3, 2]);
+        assert_eq!(tensor.kind(), Kind::Double);
+        assert_eq!(from_tensor::<i32, 3>(&tensor).unwrap(), arr.t());
+
+        let tensor = Tensor::from_slice(&[1.5f32, 2., 3.]);
+        assert_eq!(from_tensor::<f64, 1>(&tensor).unwrap(), array![1.5, 2., 3.]);
+        assert!(from_tensor::<f64, 2>(&tensor).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use ndarray::{Array1, Array2};
+use num::traits::Float;
+
+/// Savitzky–Golay filter coefficients, matching `scipy.signal.savgol_coeffs`
+/// (with `delta = 1` and `use = "conv"`).
+///
+/// `conv` in this crate does not flip the kernel, so for odd `deriv` the
+/// result should be reversed (`kernel.slice(s![..;-1])`) to get the derivative
+/// with the expected sign.
+pub fn savgol<T: Float>(
+    window_len: usize,
+    poly_order: usize,
+    deriv: usize,
+) -> Result<Array1<T>, crate::Error<1>> {
+    if poly_order >= window_len {
+        return Err(crate::Error::InvalidParameter(format!(
+            "poly_order ({poly_order}) must be less than window_len ({window_len})"
+        )));
+    }
+
+    if deriv > poly_order {
+        return Ok(Array1::zeros(window_len));
+    }
+
+    let half = window_len / 2;
+    let pos = if window_len % 2 == 1 {
+        half as f64
+    } else {
+        half as f64 - 0.5
+    };
+
+    // sample positions, already reversed for use = "conv".
+    // scaled into [-1, 1] to keep the normal equations well conditioned,
+    // scaling the rows of A does not change its minimum norm solution.
+    let scale = if pos > 0. { pos } else { 1. };
+    let x = (0..window_len)
+        .map(|i| (pos - i as f64) / scale)
+        .collect::<Vec<_>>();
+
+    let order = poly_order + 1;
+
+    // minimum norm solution of A * c = y, with A[k][i] = x[i]^k.
+    // c = A^T * (A * A^T)^-1 * y
+    let a = (0..order)
+        .map(|k| x.iter().map(|v| v.powi(k as i32)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut gram = (0..order)
+        .map(|k| {
+            (0..order)
+                .map(|l| a[k].iter().zip(&a[l]).map(|(p, q)| p * q).sum())
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut y = vec![0.; order];
+    y[deriv] = (1..=deriv).product::<usize>() as f64 / scale.powi(deriv as i32);
+
+    let z = solve(&mut gram, &mut y);
+
+    Ok((0..window_len)
+        .map(|i| T::from((0..order).map(|k| a[k][i] * z[k]).sum::<f64>()).unwrap())
+        .collect())
+}
+
+/// Normalized 1D gaussian kernel with radius `round(truncate * sigma)`.
+pub fn gaussian<T: Float>(sigma: f64, truncate: f64) -> Result<Array1<T>, crate::Error<1>> {
+    if !(sigma.is_finite() && sigma >= 0. && truncate.is_finite() && truncate >= 0.) {
+        return Err(crate::Error::InvalidParameter(format!(
+            "sigma ({sigma}) and truncate ({truncate}) must be finite and non-negative"
+        )));
+    }
+
+    let radius = (truncate * sigma + 0.5) as isize;
+    if radius == 0 {
+        return Ok(Array1::ones(1));
+    }
+
+    let kernel = (-radius..=radius)
+        .map(|x| (-0.5 * (x as f64 / sigma).powi(2)).exp())
+        .collect::<Array1<f64>>();
+    let sum = kernel.sum();
+
+    Ok(kernel.map(|&v| T::from(v / sum).unwrap()))
+}
+
+/// First derivative of `gaussian`, as a kernel for `conv` (no flip needed),
+/// matching `scipy.ndimage.gaussian_filter1d(order=1)`. A sigma too small for a radius
+/// of one gives the central difference `[-0.5, 0, 0.5]`.
+pub fn gaussian_derivative<T: Float>(
+    sigma: f64,
+    truncate: f64,
+) -> Result<Array1<T>, crate::Error<1>> {
+    let kernel = gaussian::<f64>(sigma, truncate)?;
+    if kernel.len() == 1 {
+        return Ok([-0.5, 0., 0.5]
+            .iter()
+            .map(|&v| T::from(v).unwrap())
+            .collect());
+    }
+
+    let radius = (kernel.len() / 2) as f64;
+    Ok(kernel
+        .iter()
+        .enumerate()
+        .map(|(i, v)| T::from((i as f64 - radius) / (sigma * sigma) * v).unwrap())
+        .collect())
+}
+
+/// Real Gabor kernel, as OpenCV's `getGaborKernel`: a cosine of `wavelength` (in pixels)
+/// and phase `psi` across the `orientation` (radians from the column axis, toward the
+/// row axis), under a gaussian envelope of `sigma` along it and `sigma / gamma` along
+/// the stripes. Indexed `[row, col]`, with a radius of three sigmas as in scikit-image.
+pub fn gabor<T: Float>(
+    wavelength: f64,
+    orientation: f64,
+    sigma: f64,
+    gamma: f64,
+    psi: f64,
+) -> Result<Array2<T>, crate::Error<2>> {
+    let positive = |v: f64| v.is_finite() && v > 0.;
+    if !(positive(wavelength)
+        && positive(sigma)
+        && positive(gamma)
+        && orientation.is_finite()
+        && psi.is_finite())
+    {
+        return Err(crate::Error::InvalidParameter(format!(
+            "wavelength ({wavelength}), sigma ({sigma}) and gamma ({gamma}) must be finite and \
+             positive, orientation ({orientation}) and psi ({psi}) finite"
+        )));
+    }
+
+    let (sin, cos) = orientation.sin_cos();
+    let (sigma_x, sigma_y) = (sigma, sigma / gamma);
+    // the extent of the rotated envelope on each axis
+    let radius = |a: f64, b: f64| (3. * (sigma_x * a).hypot(sigma_y * b)).ceil().max(1.) as isize;
+    let (rows, cols) = (radius(sin, cos), radius(cos, sin));
+
+    Ok(Array2::from_shape_fn(
+        (2 * rows as usize + 1, 2 * cols as usize + 1),
+        |(r, c)| {
+            let (y, x) = ((r as isize - rows) as f64, (c as isize - cols) as f64);
+            let along = x * cos + y * sin;
+            let across = -x * sin + y * cos;
+            let envelope =
+                (-(along * along + gamma * gamma * across * across) / (2. * sigma * sigma)).exp();
+            T::from(envelope * (2. * std::f64::consts::PI * along / wavelength + psi).cos())
+                .unwrap()
+        },
+    ))
+}
+
+/// `gabor` kernels for every wavelength, at `orientations` angles evenly spread over
+/// half a turn, wavelength by wavelength. sigma is `sigma_per_wavelength` times the
+/// wavelength (0.56 gives a bandwidth of one octave). to run with `ConvBankExt::conv_bank`
+/// after taking views.
+pub fn gabor_bank<T: Float>(
+    wavelengths: &[f64],
+    orientations: usize,
+    sigma_per_wavelength: f64,
+    gamma: f64,
+    psi: f64,
+) -> Result<Vec<Array2<T>>, crate::Error<2>> {
+    wavelengths
+        .iter()
+        .flat_map(|&wavelength| {
+            (0..orientations).map(move |i| {
+                gabor(
+                    wavelength,
+                    std::f64::consts::PI * i as f64 / orientations as f64,
+                    sigma_per_wavelength * wavelength,
+                    gamma,
+                    psi,
+                )
+            })
+        })
+        .collect()
+}
+
+// gaussian elimination with partial pivoting, `a` and `b` are consumed.
+fn solve(a: &mut [Vec<f64>], b: &mut [f64]) -> Vec<f64> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+            .unwrap();
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let (top, bottom) = a.split_at_mut(col + 1);
+        let pivot_row = &top[col];
+        for (row, lower) in bottom.iter_mut().enumerate() {
+            let factor = lower[col] / pivot_row[col];
+            lower
+                .iter_mut()
+                .zip(pivot_row)
+                .skip(col)
+                .for_each(|(l, p)| *l -= factor * p);
+            b[col + 1 + row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::assert_close;
+    use ndarray::array;
+
+    #[test]
+    fn savgol_aligned_with_scipy() {
+        // scipy.signal.savgol_coeffs(5, 2)
+        assert_close(
+            &savgol(5, 2, 0).unwrap(),
+            &array![-3., 12., 17., 12., -3.].map(|v| v / 35.),
+            1e-8,
+        );
+
+        // scipy.signal.savgol_coeffs(5, 2, deriv=1)
+        assert_close(
+            &savgol(5, 2, 1).unwrap(),
+            &array![0.2, 0.1, 0., -0.1, -0.2],
+            1e-8,
+        );
+
+        // scipy.signal.savgol_coeffs(7, 3, deriv=2)
+        assert_close(
+            &savgol(7, 3, 2).unwrap(),
+            &array![5., 0., -3., -4., -3., 0., 5.].map(|v| v / 42.),
+            1e-8,
+        );
+
+        // scipy.signal.savgol_coeffs(4, 2)
+        assert_close(
+            &savgol(4, 2, 0).unwrap(),
+            &array![-0.0625, 0.5625, 0.5625, -0.0625],
+            1e-8,
+        );
+
+        assert_close(&savgol(5, 2, 3).unwrap(), &Array1::zeros(5), 1e-8);
+        assert!(savgol::<f64>(3, 3, 0).is_err());
+    }
+
+    #[test]
+    fn gaussian_kernel() {
+        let k = gaussian::<f64>(1., 4.).unwrap();
+        assert_eq!(k.len(), 9);
+        assert!((k.sum() - 1.).abs() < 1e-12);
+        assert!((k[4] / k[5] - 0.5f64.exp()).abs() < 1e-12);
+
+        assert_eq!(gaussian::<f32>(0., 4.).unwrap(), array![1.]);
+        assert!(gaussian::<f32>(-1., 4.).is_err());
+    }
+
+    #[test]
+    fn gaussian_derivative_kernel() {
+        // the response to a unit ramp is one, but for the truncated tails
+        let k = gaussian_derivative::<f64>(1., 4.).unwrap();
+        assert_eq!(k.len(), 9);
+        assert!((k.iter().enumerate().map(|(i, v)| i as f64 * v).sum::<f64>() - 1.).abs() < 1e-4);
+        assert!((k[5] + k[3]).abs() < 1e-12);
+
+        assert_eq!(
+            gaussian_derivative::<f32>(0., 4.).unwrap(),
+            array![-0.5, 0., 0.5]
+        );
+    }
+
+    #[test]
+    fn gabor_kernels() {
+        let k = gabor::<f64>(4., 0., 2., 0.5, 0.).unwrap();
+        // three sigmas along the columns, three sigma / gamma along the rows
+        assert_eq!(k.dim(), (25, 13));
+        assert_eq!(k[[12, 6]], 1.);
+        // a period of 4 pixels across the stripes, even
+        assert!((k[[12, 8]] + (-0.5f64).exp()).abs() < 1e-12);
+        assert!((k[[12, 4]] - k[[12, 8]]).abs() < 1e-12);
+
+        // a quarter turn transposes the kernel
+        let k = gabor::<f64>(4., 0., 2., 1., 0.).unwrap();
+        let rotated = gabor::<f64>(4., std::f64::consts::FRAC_PI_2, 2., 1., 0.).unwrap();
+        assert_close(&rotated, &k.t(), 1e-12);
+
+        let bank = gabor_bank::<f32>(&[4., 8.], 4, 0.56, 0.5, 0.).unwrap();
+        assert_eq!(bank.len(), 8);
+        assert!(gabor::<f32>(0., 0., 2., 0.5, 0.).is_err());
+        assert!(gabor_bank::<f32>(&[4.], 2, 0.56, -1., 0.).is_err());
+    }
+}
@@ -1,61 +1,281 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod bank;
+#[cfg(feature = "std")]
+mod bilateral;
+#[cfg(feature = "std")]
+mod bits;
+#[cfg(any(feature = "python", feature = "candle", feature = "nalgebra"))]
+mod buffer;
+#[cfg(feature = "std")]
+mod builder;
+mod cloned;
 mod conv;
+#[cfg(feature = "std")]
 mod conv_fft;
 mod dilation;
+#[cfg(feature = "nalgebra")]
+mod dmatrix;
+#[cfg(feature = "std")]
+mod features;
+#[cfg(feature = "std")]
+mod filtfilt;
+#[cfg(feature = "std")]
+mod gaussian;
+#[cfg(feature = "std")]
+mod hilbert;
+#[cfg(feature = "std")]
+mod integral;
+#[cfg(feature = "std")]
+mod mixed;
+#[cfg(feature = "std")]
+mod morphology;
+#[cfg(feature = "std")]
+mod normalized;
 mod padding;
+mod parse;
+#[cfg(feature = "std")]
+mod peaks;
+#[cfg(feature = "std")]
+mod pipeline;
+#[cfg(feature = "std")]
+mod plan;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "std")]
+mod pyramid;
+#[cfg(feature = "python")]
+mod python;
+mod qformat;
+#[cfg(feature = "std")]
+mod rank;
+#[cfg(feature = "std")]
+mod separable;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "std")]
+mod sparse;
+#[cfg(feature = "std")]
+mod stats;
+mod trace;
+#[cfg(feature = "std")]
+mod unfold;
+#[cfg(feature = "std")]
+mod unrolled;
+mod window;
+
+#[cfg(feature = "candle")]
+pub mod candle;
+#[cfg(feature = "compat-test")]
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod cv;
+#[cfg(feature = "std")]
+pub mod dwt;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod fir;
+#[cfg(feature = "tch")]
+pub mod interop;
+#[cfg(feature = "std")]
+pub mod kernels;
+#[cfg(feature = "std")]
+pub mod ndimage;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod reference;
+#[cfg(feature = "std")]
+pub mod signal;
 
+#[cfg(feature = "std")]
 pub(crate) use padding::ExplicitPadding;
 
-pub use conv::ConvExt;
-pub use conv_fft::{ConvFFTExt, Processor as FftProcessor};
-pub use dilation::WithDilation;
+#[cfg(feature = "std")]
+pub use bank::ConvBankExt;
+#[cfg(feature = "std")]
+pub use bilateral::BilateralExt;
+#[cfg(feature = "std")]
+pub use bits::BitConvExt;
+#[cfg(feature = "std")]
+pub use builder::{Conv, ConvElement};
+#[cfg(feature = "candle")]
+pub use candle::TensorConvExt;
+pub use cloned::ConvClonedExt;
+pub use conv::{set_checked, set_deterministic, ConvExt, ConvTiles};
+#[cfg(feature = "std")]
+pub use conv_fft::{ConvFFTExt, FftBackend, Processor as FftProcessor, Taper};
+pub use dilation::{WithDilation, WithOrigin};
+#[cfg(feature = "nalgebra")]
+pub use dmatrix::DMatrixConvExt;
+#[cfg(feature = "std")]
+pub use features::StructureTensorExt;
+#[cfg(feature = "std")]
+pub use filtfilt::FiltFiltExt;
+#[cfg(feature = "std")]
+pub use gaussian::GaussianExt;
+#[cfg(feature = "std")]
+pub use hilbert::HilbertExt;
+#[cfg(feature = "std")]
+pub use integral::IntegralImageExt;
+#[cfg(feature = "std")]
+pub use mixed::{ConvMixedExt, Promote};
+#[cfg(feature = "std")]
+pub use morphology::MorphologyExt;
+#[cfg(feature = "std")]
+pub use normalized::NormalizedConvExt;
+#[cfg(feature = "std")]
+pub use peaks::PeakExt;
+#[cfg(feature = "std")]
+pub use pipeline::Pipeline;
+#[cfg(feature = "std")]
+pub use plan::{
+    auto_algorithm, conv_output_shape, set_cost_model, set_fft_threshold, set_separable_threshold,
+    Algorithm, ConvAutoExt, ConvPlan, CostModel, Dtype, Planner, Thresholds,
+};
+#[cfg(feature = "std")]
+pub use pool::PoolExt;
+#[cfg(feature = "std")]
+pub use pyramid::PyramidExt;
+pub use qformat::{QConvExt, QFormat};
+#[cfg(feature = "std")]
+pub use rank::{RankElement, RankFilterExt};
+#[cfg(feature = "std")]
+pub use separable::SeparableConvExt;
+#[cfg(feature = "std")]
+pub use sparse::SparseKernel;
+#[cfg(feature = "std")]
+pub use stats::LocalStatsExt;
+#[cfg(feature = "std")]
+pub use unfold::{fold, UnfoldExt};
+#[cfg(feature = "std")]
+pub use unrolled::UnrolledConvExt;
+pub use window::WindowExt;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConvMode<const N: usize> {
     Full,
     Same,
+    // same, with the padding split of the chosen framework for even kernels
+    SameAs(SamePolicy),
     Valid,
     // (pad, stride)
     Custom {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
         padding: [usize; N],
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
         strides: [usize; N],
     },
     // (pad, stride)
     Explicit {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
         padding: [[usize; 2]; N],
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
+        strides: [usize; N],
+    },
+    // (output shape, stride), the padding is derived from the input shape
+    OutputSize {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
+        shape: [usize; N],
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
         strides: [usize; N],
     },
+    // the input's shape, wrapping around the input whatever the padding mode.
+    // a kernel larger than the input wraps several times
+    Circular,
+    // the input's shape, every output only sees the current and past samples along the
+    // time axis: all its padding goes before, the last tap on the current sample.
+    // the other axes are padded like Same
+    Causal {
+        axis: usize,
+    },
+}
+
+// where the extra padding of "same" goes when the kernel size is even.
+// odd kernels are padded evenly by every policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SamePolicy {
+    // [(k - 1) / 2, k / 2], the extra padding goes after
+    PyTorch,
+    // same split as PyTorch for unit strides
+    TensorFlow,
+    // [k / 2, (k - 1) / 2], the extra padding goes before. this is ConvMode::Same
+    Scipy,
 }
 
 // padding mode. It can be either a single BorderType applied on all sides or a custom tuple of two BorderTypes for (H, W), respectively.
 #[derive(Debug, Clone, Copy)]
-pub enum PaddingMode<const N: usize, T: num::traits::NumAssign + Copy> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: serde::Serialize",
+        deserialize = "T: serde::Deserialize<'de>"
+    ))
+)]
+pub enum PaddingMode<const N: usize, T> {
     Zeros,
     Const(T),
     Reflect,
+    // reflect including the edge, d c b a | a b c d | d c b a
+    Symmetric,
     Replicate,
     Circular,
-    Custom([BorderType<T>; N]),
-    Explicit([[BorderType<T>; 2]; N]),
+    Custom(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))] [BorderType<T>; N],
+    ),
+    Explicit(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
+        [[BorderType<T>; 2]; N],
+    ),
 }
 
 // padding mode for single dim
 #[derive(Debug, Clone, Copy)]
-pub enum BorderType<T: num::traits::NumAssign + Copy> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BorderType<T> {
     Zeros,
     Const(T),
     Reflect,
+    Symmetric,
     Replicate,
     Circular,
 }
 
+#[cfg(feature = "std")]
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Error))]
 pub enum Error<const N: usize> {
-    #[error("Data shape shouldn't have ZERO. {0:?}")]
+    #[cfg_attr(feature = "std", error("Data shape shouldn't have ZERO. {0:?}"))]
     DataShape(ndarray::Dim<[ndarray::Ix; N]>),
-    #[error("Kernel shape shouldn't have ZERO. {0:?}")]
+    #[cfg_attr(feature = "std", error("Kernel shape shouldn't have ZERO. {0:?}"))]
     KernelShape(ndarray::Dim<[ndarray::Ix; N]>),
-    #[error("ConvMode {0:?} does not match KernelWithDilation Size {1:?}")]
+    #[cfg_attr(
+        feature = "std",
+        error("ConvMode {0:?} does not match KernelWithDilation Size {1:?}")
+    )]
     MismatchShape(ConvMode<N>, [ndarray::Ix; N]),
+    #[cfg_attr(feature = "std", error("Invalid parameter: {0}"))]
+    InvalidParameter(alloc::string::String),
+    #[cfg_attr(feature = "std", error("Stride of axis {0} shouldn't be ZERO"))]
+    ZeroStride(usize),
+    #[cfg_attr(feature = "std", error("Dilation of axis {0} shouldn't be ZERO"))]
+    ZeroDilation(usize),
+    // (axis, kernel size with dilation, padded input size)
+    #[cfg_attr(
+        feature = "std",
+        error(
+            "Kernel of size {1} (with dilation) exceeds the padded input of size {2} on axis {0}"
+        )
+    )]
+    KernelTooLarge(usize, usize, usize),
+    // a progress callback returned ControlFlow::Break
+    #[cfg_attr(feature = "std", error("Cancelled by the progress callback"))]
+    Cancelled,
 }
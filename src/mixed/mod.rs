@@ -0,0 +1,150 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::{traits::NumAssign, Zero};
+
+use crate::{
+    dilation::{dilated, IntoKernelWithDilation},
+    window::Windows,
+    ConvMode, PaddingMode,
+};
+
+/// product of an input element and a kernel weight of another type,
+/// in the type both convert into without loss.
+pub trait Promote<K>: Copy {
+    type Output: NumAssign + Copy;
+
+    fn promote_mul(self, k: K) -> Self::Output;
+}
+
+macro_rules! promote {
+    ($out:ty: $($t:ty, $k:ty);*) => {
+        $(
+            impl Promote<$k> for $t {
+                type Output = $out;
+
+                #[inline]
+                fn promote_mul(self, k: $k) -> $out {
+                    <$out>::from(self) * <$out>::from(k)
+                }
+            }
+        )*
+    };
+}
+
+promote!(f32: u8, f32; i8, f32; u16, f32; i16, f32; f32, f32);
+promote!(f64: u8, f64; i8, f64; u16, f64; i16, f64; u32, f64; i32, f64; f32, f64; f64, f32; f64, f64);
+
+pub trait ConvMixedExt<'a, T, K, S, SK, const N: usize>
+where
+    T: Promote<K> + NumAssign,
+    K: NumAssign + Copy,
+    S: RawData,
+    SK: RawData,
+{
+    /// `conv` with a kernel of another element type, e.g. a `u8` image with a `f32` kernel.
+    /// the padding is in the input type, the sum in the promoted type.
+    #[allow(clippy::type_complexity)]
+    fn conv_mixed(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<<T as Promote<K>>::Output, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<'a, T, K, S, SK, const N: usize> ConvMixedExt<'a, T, K, S, SK, N>
+    for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: Promote<K> + NumAssign + Debug,
+    K: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = K> + 'a,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn conv_mixed(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<<T as Promote<K>>::Output, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+        if kwd.kernel.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(kwd.kernel.raw_dim()));
+        }
+
+        let kernel_dim = dilated(std::array::from_fn(|i| kwd.kernel.shape()[i]), kwd.dilation)?;
+
+        let cm = conv_mode.unfold(&kwd, std::array::from_fn(|i| self.shape()[i]))?;
+        let windows = Windows::new(self, kernel_dim, &cm, conv_mode.padding_mode(padding_mode))
+            .ok_or(crate::Error::MismatchShape(conv_mode, kernel_dim))?;
+
+        let offset_list = kwd.gen_offset_list(windows.padded_strides());
+
+        Ok(windows.origins().map(|cur| {
+            let cur = cur as *const T;
+            offset_list
+                .iter()
+                .fold(<T as Promote<K>>::Output::zero(), |acc, &(offset, k)| {
+                    acc + unsafe { *cur.offset(offset) }.promote_mul(k)
+                })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::{ConvExt, WithDilation};
+
+    #[test]
+    fn same_as_converted() {
+        let arr = Array2::from_shape_fn((5, 6), |(i, j)| ((i * 6 + j) * 37 % 256) as u8);
+        let kernel = array![[0.25f32, 0.5], [-1., 0.125]];
+
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Same, PaddingMode::Reflect),
+            (ConvMode::Full, PaddingMode::Const(9)),
+            (
+                ConvMode::Custom {
+                    padding: [1, 2],
+                    strides: [2, 1],
+                },
+                PaddingMode::Replicate,
+            ),
+        ] {
+            let converted_padding = match padding_mode {
+                PaddingMode::Const(c) => PaddingMode::Const(c as f32),
+                PaddingMode::Reflect => PaddingMode::Reflect,
+                _ => PaddingMode::Replicate,
+            };
+
+            assert_eq!(
+                arr.conv_mixed(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+                    .unwrap(),
+                arr.mapv(|v| v as f32)
+                    .conv(kernel.with_dilation([1, 2]), conv_mode, converted_padding)
+                    .unwrap()
+            );
+        }
+
+        let arr = array![1.5f64, -2., 4.];
+        assert_eq!(
+            arr.conv_mixed(&[1f32, 2.], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![-2.5, 6.]
+        );
+    }
+}
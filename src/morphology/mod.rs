@@ -0,0 +1,245 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, ArrayView, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{window::Windows, ConvMode, PaddingMode};
+
+pub trait MorphologyExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// minimum over the `true` elements of the structuring element, centered like `ConvMode::Same`.
+    fn erode<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// maximum over the reflected structuring element.
+    fn dilate<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// erode, then dilate.
+    fn open<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// dilate, then erode.
+    fn close<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> MorphologyExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + PartialOrd + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn erode<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let se_dim: [usize; N] = std::array::from_fn(|i| structuring_element.raw_dim()[i]);
+        let cm =
+            ConvMode::Same.unfold_with_dim(se_dim, std::array::from_fn(|i| self.shape()[i]))?;
+
+        morph(
+            self,
+            structuring_element.view(),
+            ConvMode::Explicit {
+                padding: cm.padding,
+                strides: [1; N],
+            },
+            padding_mode,
+            |acc, v| if v < acc { v } else { acc },
+        )
+    }
+
+    fn dilate<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let se_dim: [usize; N] = std::array::from_fn(|i| structuring_element.raw_dim()[i]);
+        let cm =
+            ConvMode::Same.unfold_with_dim(se_dim, std::array::from_fn(|i| self.shape()[i]))?;
+
+        // reflect the structuring element around its center,
+        // which also swaps the padding of both sides
+        let reflected = structuring_element.slice(unsafe {
+            SliceInfo::new(std::array::from_fn(|_| SliceInfoElem::Slice {
+                start: 0,
+                end: None,
+                step: -1,
+            }))
+            .unwrap()
+        });
+
+        morph(
+            self,
+            reflected,
+            ConvMode::Explicit {
+                padding: cm.padding.map(|[front, back]| [back, front]),
+                strides: [1; N],
+            },
+            padding_mode,
+            |acc, v| if v > acc { v } else { acc },
+        )
+    }
+
+    fn open<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        self.erode(structuring_element, padding_mode)?
+            .dilate(structuring_element, padding_mode)
+    }
+
+    fn close<SK: Data<Elem = bool>>(
+        &self,
+        structuring_element: &ArrayBase<SK, Dim<[Ix; N]>>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        self.dilate(structuring_element, padding_mode)?
+            .erode(structuring_element, padding_mode)
+    }
+}
+
+fn morph<T, S, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    structuring_element: ArrayView<bool, Dim<[Ix; N]>>,
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+    pick: impl Fn(T, T) -> T,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    if input.shape().iter().product::<usize>() == 0 {
+        return Err(crate::Error::DataShape(input.raw_dim()));
+    }
+
+    let se_dim: [usize; N] = std::array::from_fn(|i| structuring_element.raw_dim()[i]);
+    if se_dim.iter().product::<usize>() == 0 {
+        return Err(crate::Error::KernelShape(structuring_element.raw_dim()));
+    }
+
+    let cm = conv_mode.unfold_with_dim(se_dim, std::array::from_fn(|i| input.shape()[i]))?;
+    let windows = Windows::new(input, se_dim, &cm, conv_mode.padding_mode(padding_mode))
+        .ok_or(crate::Error::MismatchShape(conv_mode, se_dim))?;
+
+    let offset_list = windows
+        .offsets(se_dim, [1; N])
+        .into_iter()
+        .zip(structuring_element.iter())
+        .filter(|(_, &m)| m)
+        .map(|(offset, _)| offset)
+        .collect::<Vec<_>>();
+
+    let Some((&first, rest)) = offset_list.split_first() else {
+        return Err(crate::Error::InvalidParameter(
+            "structuring element has no true element".to_string(),
+        ));
+    };
+
+    Ok(windows.origins().map(|cur| {
+        let cur = cur as *const T;
+        rest.iter()
+            .fold(unsafe { *cur.offset(first) }, |acc, &offset| {
+                pick(acc, unsafe { *cur.offset(offset) })
+            })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn erode_dilate() {
+        let cross = array![
+            [false, true, false],
+            [true, true, true],
+            [false, true, false]
+        ];
+
+        let arr = array![
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 5, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0]
+        ];
+        let dilated = arr.dilate(&cross, PaddingMode::Zeros).unwrap();
+        assert_eq!(
+            dilated,
+            array![
+                [0, 0, 0, 0, 0],
+                [0, 0, 5, 0, 0],
+                [0, 5, 5, 5, 0],
+                [0, 0, 5, 0, 0],
+                [0, 0, 0, 0, 0]
+            ]
+        );
+        assert_eq!(dilated.erode(&cross, PaddingMode::Zeros).unwrap(), arr);
+
+        // opening removes the isolated pixel, closing fills the hole
+        assert_eq!(
+            arr.open(&cross, PaddingMode::Zeros).unwrap(),
+            Array::<i32, _>::zeros((5, 5))
+        );
+        let mut filled = Array::from_elem((5, 5), 1);
+        filled[[2, 2]] = 0;
+        assert_eq!(
+            filled.close(&cross, PaddingMode::Replicate).unwrap(),
+            Array::from_elem((5, 5), 1)
+        );
+    }
+
+    #[test]
+    fn asymmetric_structuring_element() {
+        let se = array![true, true, false];
+
+        assert_eq!(
+            array![0, 0, 1, 0, 0]
+                .dilate(&se, PaddingMode::Zeros)
+                .unwrap(),
+            array![0, 1, 1, 0, 0]
+        );
+        assert_eq!(
+            array![1, 1, 1, 0, 1]
+                .erode(&se, PaddingMode::Replicate)
+                .unwrap(),
+            array![1, 1, 1, 0, 0]
+        );
+
+        assert!(array![1, 2]
+            .erode(&array![false, false], PaddingMode::Zeros)
+            .is_err());
+    }
+}
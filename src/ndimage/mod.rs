@@ -0,0 +1,193 @@
+//! scipy.ndimage compatible wrappers, so ported code keeps its boundary
+//! modes and `origin` arguments.
+
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Axis, Data, Dim, IntoDimension, Ix, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{ConvExt, ConvMode, Error, PaddingMode};
+
+/// `scipy.ndimage.convolve(input, weights, mode=mode, cval=cval, origin=origin)`.
+///
+/// `mode` is one of "reflect", "constant", "nearest", "mirror", "wrap",
+/// "grid-mirror", "grid-constant" and "grid-wrap". The output has the shape of
+/// `input`, weights longer than the input fold the boundary back as many times
+/// as scipy does.
+pub fn convolve<T, S, SK, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    weights: &ArrayBase<SK, Dim<[Ix; N]>>,
+    mode: &str,
+    cval: T,
+    origin: [isize; N],
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    // convolve is correlate with the flipped weights and the mirrored origin
+    let origin = std::array::from_fn(|i| {
+        let odd = weights.shape()[i] as isize & 1;
+        -origin[i] - (1 - odd)
+    });
+
+    let mut flipped = weights.view();
+    (0..N).for_each(|i| flipped.invert_axis(Axis(i)));
+
+    correlate(input, &flipped, mode, cval, origin)
+}
+
+/// `scipy.ndimage.correlate(input, weights, mode=mode, cval=cval, origin=origin)`,
+/// see [`convolve`] for the supported modes.
+pub fn correlate<T, S, SK, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    weights: &ArrayBase<SK, Dim<[Ix; N]>>,
+    mode: &str,
+    cval: T,
+    origin: [isize; N],
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let padding_mode = padding_mode(mode, cval)?;
+
+    let mut padding = [[0; 2]; N];
+    for (i, pad) in padding.iter_mut().enumerate() {
+        let size = weights.shape()[i] as isize;
+        let before = size / 2 + origin[i];
+        if before < 0 || before >= size.max(1) {
+            return Err(Error::InvalidParameter(format!(
+                "origin {} out of range for weights of size {} on axis {}",
+                origin[i], size, i
+            )));
+        }
+        *pad = [before as usize, (size - 1 - before) as usize];
+    }
+
+    input.conv(
+        weights,
+        ConvMode::Explicit {
+            padding,
+            strides: [1; N],
+        },
+        padding_mode,
+    )
+}
+
+fn padding_mode<T, const N: usize>(mode: &str, cval: T) -> Result<PaddingMode<N, T>, Error<N>>
+where
+    T: NumAssign + Copy,
+{
+    Ok(match mode {
+        "reflect" | "grid-mirror" => PaddingMode::Symmetric,
+        "constant" | "grid-constant" => PaddingMode::Const(cval),
+        "nearest" => PaddingMode::Replicate,
+        "mirror" => PaddingMode::Reflect,
+        "wrap" | "grid-wrap" => PaddingMode::Circular,
+        _ => {
+            return Err(Error::InvalidParameter(format!(
+                "unknown boundary mode {mode:?}"
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn aligned_with_scipy() {
+        let a = array![1., 2., 0., 4., 5.];
+
+        // ndimage.convolve(a, [1, 2, 3], mode=...)
+        let w = array![1., 2., 3.];
+        assert_eq!(
+            convolve(&a, &w, "reflect", 0., [0]).unwrap(),
+            array![7., 7., 10., 13., 27.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "constant", 0., [0]).unwrap(),
+            array![4., 7., 10., 13., 22.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "constant", 1., [0]).unwrap(),
+            array![7., 7., 10., 13., 23.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "nearest", 0., [0]).unwrap(),
+            array![7., 7., 10., 13., 27.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "mirror", 0., [0]).unwrap(),
+            array![10., 7., 10., 13., 26.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "wrap", 0., [0]).unwrap(),
+            array![19., 7., 10., 13., 23.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "constant", 0., [1]).unwrap(),
+            array![7., 10., 13., 22., 15.]
+        );
+        assert_eq!(
+            correlate(&a, &w, "constant", 0., [0]).unwrap(),
+            array![8., 5., 14., 23., 14.]
+        );
+
+        // even sized weights, ndimage.convolve(a, [1, 2], mode="constant")
+        let w = array![1., 2.];
+        assert_eq!(
+            convolve(&a, &w, "constant", 0., [0]).unwrap(),
+            array![4., 4., 4., 13., 10.]
+        );
+        assert_eq!(
+            convolve(&a, &w, "constant", 0., [-1]).unwrap(),
+            array![1., 4., 4., 4., 13.]
+        );
+
+        let a = array![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+        let w = array![[0, 1, 0], [1, 1, 1], [0, 1, 0]];
+        assert_eq!(
+            convolve(&a, &w, "reflect", 0, [0, 0]).unwrap(),
+            array![[9, 13, 17], [21, 25, 29], [33, 37, 41]]
+        );
+
+        // weights longer than the input
+        let a = array![1, 2, 3];
+        let w = Array::from_elem(9, 1);
+        assert_eq!(
+            convolve(&a, &w, "reflect", 0, [0]).unwrap(),
+            array![20, 18, 16]
+        );
+        assert_eq!(
+            convolve(&a, &w, "mirror", 0, [0]).unwrap(),
+            array![17, 18, 19]
+        );
+        assert_eq!(
+            correlate(&a, &Array::from_elem(6, 1), "reflect", 0, [-3]).unwrap(),
+            array![12, 12, 12]
+        );
+
+        let a = array![[1, 2, 3], [4, 5, 6], [7, 8, 9]];
+        let w = array![[0, 1, 0], [1, 1, 1], [0, 1, 0]];
+        assert!(convolve(&a, &w, "edge", 0, [0, 0]).is_err());
+        assert!(convolve(&a, &w, "constant", 0, [2, 0]).is_err());
+    }
+}
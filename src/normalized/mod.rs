@@ -0,0 +1,125 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem, Zip,
+};
+use num::traits::{Float, NumAssign};
+
+use crate::{ConvExt, ConvMode, PaddingMode};
+
+pub trait NormalizedConvExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// normalized convolution (Knutsson & Westin): `conv(data * certainty) / conv(certainty)`
+    /// with the applicability `kernel`, so samples of zero certainty don't count and the rest
+    /// are interpolated over them. outside the input has zero certainty, no padding mode needed.
+    /// where the convolved certainty is at most `epsilon` there is nothing to interpolate from,
+    /// and the output is `fill` there (e.g. NaN or zero).
+    fn normalized_conv<SC, SK>(
+        &self,
+        certainty: &ArrayBase<SC, Dim<[Ix; N]>>,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        epsilon: T,
+        fill: T,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+    where
+        SC: Data<Elem = T>,
+        SK: Data<Elem = T>;
+}
+
+impl<T, S, const N: usize> NormalizedConvExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn normalized_conv<SC, SK>(
+        &self,
+        certainty: &ArrayBase<SC, Dim<[Ix; N]>>,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        epsilon: T,
+        fill: T,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+    where
+        SC: Data<Elem = T>,
+        SK: Data<Elem = T>,
+    {
+        if self.shape() != certainty.shape() {
+            return Err(crate::Error::InvalidParameter(format!(
+                "certainty of shape {:?} doesn't match the data of shape {:?}",
+                certainty.shape(),
+                self.shape()
+            )));
+        }
+
+        let weighted = (self * certainty).conv(kernel, conv_mode, PaddingMode::Zeros)?;
+        let normalization = certainty.conv(kernel, conv_mode, PaddingMode::Zeros)?;
+
+        Ok(Zip::from(&weighted)
+            .and(&normalization)
+            .map_collect(|&w, &c| if c > epsilon { w / c } else { fill }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn normalized_conv() {
+        // full certainty is a conv with the kernel normalized to its sum, inside the input
+        let arr = Array2::from_shape_fn((6, 7), |(i, j)| ((i * 7 + j) % 5) as f64);
+        let kernel = array![[1., 2., 1.], [2., 4., 2.], [1., 2., 1.]];
+        let res = arr
+            .normalized_conv(
+                &Array2::ones((6, 7)),
+                &kernel,
+                ConvMode::Valid,
+                0.,
+                f64::NAN,
+            )
+            .unwrap();
+        let expected = arr
+            .conv(&(&kernel / 16.), ConvMode::Valid, PaddingMode::Zeros)
+            .unwrap();
+        assert_close(&res, &expected, 1e-12);
+
+        // a line with gaps: the missing samples are interpolated from their neighbours
+        // and a constant stays constant, borders included
+        let data = array![3., 0., 3., 3., 0., 0., 3., 3.];
+        let certainty = array![1., 0., 1., 1., 0., 0., 1., 1.];
+        let res = data
+            .normalized_conv(&certainty, &array![1., 1., 1.], ConvMode::Same, 0., 0.)
+            .unwrap();
+        assert_eq!(res, array![3., 3., 3., 3., 3., 3., 3., 3.]);
+
+        let linear = array![0., 10., 2., 3., 10., 10., 6., 7.];
+        let res = linear
+            .normalized_conv(&certainty, &array![1., 2., 1.], ConvMode::Same, 0., 0.)
+            .unwrap();
+        // the two certain neighbours, whatever is stored at the gap
+        assert_eq!(res[1], 1.);
+
+        // nothing certain within reach of the kernel
+        let certainty = array![1., 0., 0., 0., 0., 0., 0., 1.];
+        let res = data
+            .normalized_conv(&certainty, &array![1., 1., 1.], ConvMode::Same, 0.5, -1.)
+            .unwrap();
+        assert_eq!(res, array![3., 3., -1., -1., -1., -1., 3., 3.]);
+
+        assert!(data
+            .normalized_conv(&array![1., 1.], &array![1.], ConvMode::Same, 0., 0.)
+            .is_err());
+    }
+}
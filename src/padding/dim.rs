@@ -55,6 +55,23 @@ pub fn reflect<const N: usize, T, S, D, DO>(
     half_dim::reflect_back(input_dim, buffer, dim, padding);
 }
 
+#[inline]
+pub fn symmetric<const N: usize, T, S, D, DO>(
+    input_dim: D,
+    buffer: &mut ArrayBase<S, DO>,
+    dim: usize,
+    padding: [usize; 2],
+) where
+    T: NumAssign + Copy,
+    S: DataMut<Elem = T>,
+    D: RemoveAxis,
+    DO: RemoveAxis,
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    half_dim::symmetric_front(buffer, dim, padding);
+    half_dim::symmetric_back(input_dim, buffer, dim, padding);
+}
+
 #[inline]
 pub fn circular<const N: usize, T, S, D, DO>(
     input_dim: D,
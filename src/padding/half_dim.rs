@@ -92,6 +92,25 @@ pub fn replicate_back<const N: usize, T, S, D, DO>(
     }
 }
 
+// the input sample of the coordinate x of an axis of n samples, mirrored as many times
+// as needed, so a padding longer than the input folds back like scipy.ndimage
+// a b c d | c b a b c d c
+fn reflect_index(x: isize, n: usize) -> usize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n as isize - 1);
+    let x = x.rem_euclid(period);
+    (if x < n as isize { x } else { period - x }) as usize
+}
+
+// a b c d | d c b a a b c
+fn symmetric_index(x: isize, n: usize) -> usize {
+    let n = n as isize;
+    let x = x.rem_euclid(2 * n);
+    (if x < n { x } else { 2 * n - 1 - x }) as usize
+}
+
 #[inline]
 pub fn reflect_front<T, S, D>(buffer: &mut ArrayBase<S, D>, dim: usize, padding: [usize; 2])
 where
@@ -99,9 +118,9 @@ where
     S: DataMut<Elem = T>,
     D: RemoveAxis,
 {
-    let border_index = padding[0];
+    let n = buffer.raw_dim()[dim] - padding[0] - padding[1];
     for j in 0..padding[0] {
-        let reflect_j = (border_index - j) + border_index;
+        let reflect_j = padding[0] + reflect_index(j as isize - padding[0] as isize, n);
         unsafe {
             let output_mut = (buffer as *const _ as *mut ArrayBase<S, D>)
                 .as_mut()
@@ -127,9 +146,9 @@ pub fn reflect_back<const N: usize, T, S, D, DO>(
     DO: RemoveAxis,
     Dim<[Ix; N]>: RemoveAxis,
 {
-    let border_index = buffer.raw_dim()[dim] - padding[1] - 1;
-    for j in input_dim[dim] + padding[0]..buffer.raw_dim()[dim] {
-        let reflect_j = border_index - (j - border_index);
+    let n = input_dim[dim];
+    for j in n + padding[0]..buffer.raw_dim()[dim] {
+        let reflect_j = padding[0] + reflect_index(j as isize - padding[0] as isize, n);
         unsafe {
             let output_mut = (buffer as *const _ as *mut ArrayBase<S, D>)
                 .as_mut()
@@ -142,6 +161,56 @@ pub fn reflect_back<const N: usize, T, S, D, DO>(
     }
 }
 
+#[inline]
+pub fn symmetric_front<T, S, D>(buffer: &mut ArrayBase<S, D>, dim: usize, padding: [usize; 2])
+where
+    T: NumAssign + Copy,
+    S: DataMut<Elem = T>,
+    D: RemoveAxis,
+{
+    let n = buffer.raw_dim()[dim] - padding[0] - padding[1];
+    for j in 0..padding[0] {
+        let symmetric_j = padding[0] + symmetric_index(j as isize - padding[0] as isize, n);
+        unsafe {
+            let output_mut = (buffer as *const _ as *mut ArrayBase<S, D>)
+                .as_mut()
+                .unwrap();
+
+            output_mut
+                .index_axis_mut(Axis(dim), j)
+                .assign(&buffer.index_axis(Axis(dim), symmetric_j));
+        }
+    }
+}
+
+#[inline]
+pub fn symmetric_back<const N: usize, T, S, D, DO>(
+    input_dim: D,
+    buffer: &mut ArrayBase<S, DO>,
+    dim: usize,
+    padding: [usize; 2],
+) where
+    T: NumAssign + Copy,
+    S: DataMut<Elem = T>,
+    D: RemoveAxis,
+    DO: RemoveAxis,
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    let n = input_dim[dim];
+    for j in n + padding[0]..buffer.raw_dim()[dim] {
+        let symmetric_j = padding[0] + symmetric_index(j as isize - padding[0] as isize, n);
+        unsafe {
+            let output_mut = (buffer as *const _ as *mut ArrayBase<S, D>)
+                .as_mut()
+                .unwrap();
+
+            output_mut
+                .index_axis_mut(Axis(dim), j)
+                .assign(&buffer.index_axis(Axis(dim), symmetric_j));
+        }
+    }
+}
+
 #[inline]
 pub fn circular_front<T, S, D>(buffer: &mut ArrayBase<S, D>, dim: usize, padding: [usize; 2])
 where
@@ -149,8 +218,9 @@ where
     S: DataMut<Elem = T>,
     D: RemoveAxis,
 {
+    // backwards, so a padding longer than the input wraps onto the entries already filled
     let border_index = padding[0];
-    for j in 0..padding[0] {
+    for j in (0..padding[0]).rev() {
         let circular_j = buffer.raw_dim()[dim] - padding[1] - (border_index - j);
         unsafe {
             let output_mut = (buffer as *const _ as *mut ArrayBase<S, D>)
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use super::{BorderType, PaddingMode};
 
 use ndarray::{
@@ -11,8 +13,145 @@ mod half_dim;
 
 pub type ExplicitPadding<const N: usize> = [[usize; 2]; N];
 
+impl<const N: usize, T: NumAssign + Copy> PaddingMode<N, T> {
+    /// constant padding with a value per side, `[before, after]` for every axis.
+    /// the corners take the value of the last axis, as the axes are padded in order.
+    pub fn const_explicit(values: [[T; 2]; N]) -> Self {
+        PaddingMode::Explicit(
+            values.map(|[before, after]| [BorderType::Const(before), BorderType::Const(after)]),
+        )
+    }
+
+    // the border of every side, `[before, after]` for every axis
+    pub(crate) fn borders(self) -> [[BorderType<T>; 2]; N] {
+        match self {
+            PaddingMode::Zeros => [[BorderType::Zeros; 2]; N],
+            PaddingMode::Const(c) => [[BorderType::Const(c); 2]; N],
+            PaddingMode::Reflect => [[BorderType::Reflect; 2]; N],
+            PaddingMode::Symmetric => [[BorderType::Symmetric; 2]; N],
+            PaddingMode::Replicate => [[BorderType::Replicate; 2]; N],
+            PaddingMode::Circular => [[BorderType::Circular; 2]; N],
+            PaddingMode::Custom(borders) => borders.map(|border| [border; 2]),
+            PaddingMode::Explicit(borders) => borders,
+        }
+    }
+}
+
+// where an element of a padded axis comes from
+#[derive(Clone, Copy)]
+enum Source<T> {
+    Index(usize),
+    Const(T),
+    // not written by the padding of the axis, left to the initial value of the buffer
+    Fill,
+}
+
+/// the part `region` (`(start, end)` per axis, in padded coordinates) of
+/// `data.padding(mode, padding)`, without padding the rest of the input.
+pub(crate) fn padding_region<const N: usize, T, S>(
+    data: &ArrayBase<S, Dim<[Ix; N]>>,
+    mode: PaddingMode<N, T>,
+    padding: ExplicitPadding<N>,
+    region: [(usize, usize); N],
+) -> Array<T, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    const FILL: isize = -1;
+    const ZERO: isize = -2;
+    const BEFORE: isize = -3;
+    const AFTER: isize = -4;
+
+    let fill = match mode {
+        PaddingMode::Const(c) => c,
+        _ => T::zero(),
+    };
+
+    // every axis is padded on its own as an axis of indices, through the same functions
+    // as the whole array, so even a padding larger than the input maps the same way
+    let borders = mode.borders();
+    let sources: [Vec<Source<T>>; N] = core::array::from_fn(|i| {
+        let n = data.shape()[i];
+        let pad = padding[i];
+        let mut buffer = Array::from_elem(n + pad[0] + pad[1], FILL);
+        buffer
+            .slice_mut(ndarray::s![pad[0]..pad[0] + n])
+            .iter_mut()
+            .enumerate()
+            .for_each(|(j, v)| *v = j as isize);
+
+        let input_dim = Dim([n]);
+        let [before, after] = borders[i];
+        match before {
+            BorderType::Zeros => half_dim::constant_front(&mut buffer, 0, pad, ZERO),
+            BorderType::Const(_) => half_dim::constant_front(&mut buffer, 0, pad, BEFORE),
+            BorderType::Reflect => half_dim::reflect_front(&mut buffer, 0, pad),
+            BorderType::Symmetric => half_dim::symmetric_front(&mut buffer, 0, pad),
+            BorderType::Replicate => half_dim::replicate_front(&mut buffer, 0, pad),
+            BorderType::Circular => half_dim::circular_front(&mut buffer, 0, pad),
+        }
+        match after {
+            BorderType::Zeros => {
+                half_dim::constant_back::<1, _, _, _, _>(input_dim, &mut buffer, 0, pad, ZERO)
+            }
+            BorderType::Const(_) => {
+                half_dim::constant_back::<1, _, _, _, _>(input_dim, &mut buffer, 0, pad, AFTER)
+            }
+            BorderType::Reflect => {
+                half_dim::reflect_back::<1, _, _, _, _>(input_dim, &mut buffer, 0, pad)
+            }
+            BorderType::Symmetric => {
+                half_dim::symmetric_back::<1, _, _, _, _>(input_dim, &mut buffer, 0, pad)
+            }
+            BorderType::Replicate => {
+                half_dim::replicate_back::<1, _, _, _, _>(input_dim, &mut buffer, 0, pad)
+            }
+            BorderType::Circular => {
+                half_dim::circular_back::<1, _, _, _, _>(input_dim, &mut buffer, 0, pad)
+            }
+        }
+
+        let constant = |border| match border {
+            BorderType::Const(c) => c,
+            _ => T::zero(),
+        };
+        buffer.as_slice().unwrap()[region[i].0..region[i].1]
+            .iter()
+            .map(|&j| match j {
+                FILL => Source::Fill,
+                ZERO => Source::Const(T::zero()),
+                BEFORE => Source::Const(constant(before)),
+                AFTER => Source::Const(constant(after)),
+                j => Source::Index(j as usize),
+            })
+            .collect()
+    });
+
+    // the axes are padded in order: a constant covers the whole slice of its axis, so the
+    // last one wins, and a slice left unwritten keeps the initial value unless a later
+    // constant covers it
+    let shape: [usize; N] = core::array::from_fn(|i| region[i].1 - region[i].0);
+    Array::from_shape_fn(shape, |index| {
+        let index = index.into_dimension();
+        let mut at = [0; N];
+        let mut constant = None;
+        for i in 0..N {
+            match sources[i][index[i]] {
+                Source::Index(j) => at[i] = j,
+                Source::Const(c) => constant = Some(c),
+                Source::Fill => constant = constant.or(Some(fill)),
+            }
+        }
+        constant.unwrap_or_else(|| data[at.into_dimension()])
+    })
+}
+
 pub trait PaddingExt<const N: usize, T: num::traits::NumAssign + Copy, Output> {
     fn padding(&self, mode: PaddingMode<N, T>, padding_size: ExplicitPadding<N>) -> Output;
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
     fn padding_in<SO: DataMut<Elem = T>, DO: RemoveAxis>(
         &self,
         buffer: &mut ArrayBase<SO, DO>,
@@ -48,7 +187,7 @@ where
         let raw_dim = self.raw_dim();
 
         let output_dim =
-            std::array::from_fn(|i| raw_dim[i] + explicit_padding[i][0] + explicit_padding[i][1]);
+            core::array::from_fn(|i| raw_dim[i] + explicit_padding[i][0] + explicit_padding[i][1]);
 
         let mut output: Array<T, Dim<[Ix; N]>> = Array::from_elem(output_dim, c);
 
@@ -57,6 +196,7 @@ where
         match mode {
             PaddingMode::Replicate => padding_replicate(self, &mut output, explicit_padding),
             PaddingMode::Reflect => padding_reflect(self, &mut output, explicit_padding),
+            PaddingMode::Symmetric => padding_symmetric(self, &mut output, explicit_padding),
             PaddingMode::Circular => padding_circular(self, &mut output, explicit_padding),
             PaddingMode::Custom(borders) => {
                 padding_custom(self, &mut output, explicit_padding, borders)
@@ -97,6 +237,7 @@ where
             }
             PaddingMode::Replicate => padding_replicate(self, buffer, explicit_padding),
             PaddingMode::Reflect => padding_reflect(self, buffer, explicit_padding),
+            PaddingMode::Symmetric => padding_symmetric(self, buffer, explicit_padding),
             PaddingMode::Circular => padding_circular(self, buffer, explicit_padding),
             PaddingMode::Custom(borders) => padding_custom(self, buffer, explicit_padding, borders),
             PaddingMode::Explicit(borders) => {
@@ -123,7 +264,7 @@ pub(crate) fn padding_const<const N: usize, T, S, D, SO, DO>(
     DO: RemoveAxis,
 {
     let mut output_slice = output.slice_mut(unsafe {
-        SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
+        SliceInfo::new(core::array::from_fn(|i| SliceInfoElem::Slice {
             start: explicit_padding[i][0] as isize,
             end: Some((explicit_padding[i][0] + input.raw_dim()[i]) as isize),
             step: 1,
@@ -178,6 +319,28 @@ fn padding_reflect<const N: usize, T, S, D, SO, DO>(
         });
 }
 
+fn padding_symmetric<const N: usize, T, S, D, SO, DO>(
+    input: &ArrayBase<S, D>,
+    output: &mut ArrayBase<SO, DO>,
+    explicit_padding: ExplicitPadding<N>,
+) where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SO: DataMut<Elem = T>,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>: SliceArg<Dim<[Ix; N]>>,
+    Dim<[Ix; N]>: RemoveAxis,
+    D: RemoveAxis,
+    DO: RemoveAxis,
+{
+    explicit_padding
+        .iter()
+        .enumerate()
+        .for_each(|(dim, &explicit_padding)| {
+            dim::symmetric(input.raw_dim(), output, dim, explicit_padding);
+        });
+}
+
 fn padding_circular<const N: usize, T, S, D, SO, DO>(
     input: &ArrayBase<S, D>,
     output: &mut ArrayBase<SO, DO>,
@@ -227,6 +390,7 @@ fn padding_custom<const N: usize, T, S, D, SO, DO>(
                 dim::constant(input.raw_dim(), output, dim, explicit_padding, *c)
             }
             BorderType::Reflect => dim::reflect(input.raw_dim(), output, dim, explicit_padding),
+            BorderType::Symmetric => dim::symmetric(input.raw_dim(), output, dim, explicit_padding),
             BorderType::Replicate => dim::replicate(input.raw_dim(), output, dim, explicit_padding),
             BorderType::Circular => dim::circular(input.raw_dim(), output, dim, explicit_padding),
         });
@@ -258,6 +422,7 @@ fn padding_explicit<const N: usize, T, S, D, SO, DO>(
                 }
                 BorderType::Const(c) => half_dim::constant_front(output, dim, explicit_padding, c),
                 BorderType::Reflect => half_dim::reflect_front(output, dim, explicit_padding),
+                BorderType::Symmetric => half_dim::symmetric_front(output, dim, explicit_padding),
                 BorderType::Replicate => half_dim::replicate_front(output, dim, explicit_padding),
                 BorderType::Circular => half_dim::circular_front(output, dim, explicit_padding),
             }
@@ -275,6 +440,9 @@ fn padding_explicit<const N: usize, T, S, D, SO, DO>(
                 BorderType::Reflect => {
                     half_dim::reflect_back(input.raw_dim(), output, dim, explicit_padding)
                 }
+                BorderType::Symmetric => {
+                    half_dim::symmetric_back(input.raw_dim(), output, dim, explicit_padding)
+                }
                 BorderType::Replicate => {
                     half_dim::replicate_back(input.raw_dim(), output, dim, explicit_padding)
                 }
@@ -293,7 +461,7 @@ mod tests {
 
     use super::*;
     use crate::dilation::IntoKernelWithDilation;
-    use crate::ConvMode;
+    use crate::{ConvExt, ConvMode};
 
     #[test]
     fn index_axis() {
@@ -344,7 +512,7 @@ mod tests {
         let kernel = array![[1, 1, 1], [1, 1, 1], [1, 1, 1]];
         let kernel = kernel.into_kernel_with_dilation();
 
-        let explicit_conv = ConvMode::Full.unfold(&kernel);
+        let explicit_conv = ConvMode::Full.unfold(&kernel, [2, 2]).unwrap();
         let explicit_padding = explicit_conv.padding;
         let arr_padded = arr.padding(
             PaddingMode::Custom([BorderType::Replicate, BorderType::Circular]),
@@ -362,7 +530,7 @@ mod tests {
             ]
         );
 
-        let explicit_conv = ConvMode::Full.unfold(&kernel);
+        let explicit_conv = ConvMode::Full.unfold(&kernel, [2, 2]).unwrap();
         let explicit_padding = explicit_conv.padding;
         let arr_padded = arr.padding(
             PaddingMode::Custom([BorderType::Reflect, BorderType::Const(7)]),
@@ -370,8 +538,9 @@ mod tests {
         );
         assert_eq!(
             arr_padded,
+            // the reflection is longer than the input and folds back to the first row
             array![
-                [7, 7, 0, 0, 7, 7],
+                [7, 7, 1, 2, 7, 7],
                 [7, 7, 3, 4, 7, 7],
                 [7, 7, 1, 2, 7, 7],
                 [7, 7, 3, 4, 7, 7],
@@ -383,6 +552,145 @@ mod tests {
         dbg!(arr_padded);
     }
 
+    #[test]
+    fn padding_symmetric() {
+        let arr = array![[1, 2, 3], [4, 5, 6]];
+
+        assert_eq!(
+            arr.padding(PaddingMode::Symmetric, [[1, 2], [3, 2]]),
+            array![
+                [3, 2, 1, 1, 2, 3, 3, 2],
+                [3, 2, 1, 1, 2, 3, 3, 2],
+                [6, 5, 4, 4, 5, 6, 6, 5],
+                [6, 5, 4, 4, 5, 6, 6, 5],
+                [3, 2, 1, 1, 2, 3, 3, 2]
+            ]
+        );
+        assert_eq!(
+            arr.padding(
+                PaddingMode::Explicit([
+                    [BorderType::Zeros, BorderType::Zeros],
+                    [BorderType::Symmetric, BorderType::Reflect]
+                ]),
+                [[0, 0], [2, 2]]
+            ),
+            array![[2, 1, 1, 2, 3, 2, 1], [5, 4, 4, 5, 6, 5, 4]]
+        );
+    }
+
+    #[test]
+    fn padding_region() {
+        let arr = Array::from_shape_fn((3, 4), |(i, j)| (i * 4 + j) as i32 + 1);
+
+        for mode in [
+            PaddingMode::Zeros,
+            PaddingMode::Const(-1),
+            PaddingMode::Reflect,
+            PaddingMode::Symmetric,
+            PaddingMode::Replicate,
+            PaddingMode::Circular,
+            PaddingMode::Custom([BorderType::Reflect, BorderType::Const(7)]),
+            PaddingMode::Explicit([
+                [BorderType::Const(8), BorderType::Symmetric],
+                [BorderType::Circular, BorderType::Reflect],
+            ]),
+        ] {
+            for padding in [[[1, 2], [0, 3]], [[2, 1], [3, 2]]] {
+                let padded = arr.padding(mode, padding);
+                let (rows, cols) = padded.dim();
+                for region in [[(0, rows), (0, cols)], [(1, rows - 2), (3, cols - 1)]] {
+                    assert_eq!(
+                        super::padding_region(&arr, mode, padding, region),
+                        padded.slice(s![region[0].0..region[0].1, region[1].0..region[1].1]),
+                        "{mode:?} {padding:?} {region:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn padding_longer_than_input() {
+        let arr = array![1, 2, 3];
+
+        // the boundary folds back again past the other end of the input, like scipy.ndimage
+        let reflect = array![2, 1, 2, 3, 2, 1, 2, 3, 2, 1, 2, 3, 2, 1];
+        let symmetric = array![2, 3, 3, 2, 1, 1, 2, 3, 3, 2, 1, 1, 2, 3];
+        assert_eq!(arr.padding(PaddingMode::Reflect, [[5, 6]]), reflect);
+        assert_eq!(arr.padding(PaddingMode::Symmetric, [[5, 6]]), symmetric);
+        assert_eq!(
+            super::padding_region(&arr, PaddingMode::Reflect, [[5, 6]], [(2, 12)]),
+            reflect.slice(s![2..12])
+        );
+        assert_eq!(
+            super::padding_region(&arr, PaddingMode::Symmetric, [[5, 6]], [(2, 12)]),
+            symmetric.slice(s![2..12])
+        );
+        assert_eq!(
+            arr.padding(
+                PaddingMode::Explicit([[BorderType::Const(0), BorderType::Reflect]]),
+                [[2, 5]]
+            ),
+            array![0, 0, 1, 2, 3, 2, 1, 2, 3, 2]
+        );
+
+        let arr = array![[1, 2], [3, 4]];
+        assert_eq!(
+            arr.padding(PaddingMode::Symmetric, [[3, 0], [0, 3]]),
+            array![
+                [3, 4, 4, 3, 3],
+                [3, 4, 4, 3, 3],
+                [1, 2, 2, 1, 1],
+                [1, 2, 2, 1, 1],
+                [3, 4, 4, 3, 3]
+            ]
+        );
+
+        let ret = array![1., 2., 3.]
+            .conv(
+                &Array::ones(6),
+                ConvMode::Explicit {
+                    padding: [[0, 5]],
+                    strides: [1],
+                },
+                PaddingMode::Symmetric,
+            )
+            .unwrap();
+        assert_eq!(ret, array![12., 12., 12.]);
+    }
+
+    #[test]
+    fn const_explicit() {
+        let arr = array![[1, 2], [3, 4]];
+
+        assert_eq!(
+            arr.padding(
+                PaddingMode::const_explicit([[7, 8], [-1, 0]]),
+                [[1, 1], [2, 1]]
+            ),
+            array![
+                [-1, -1, 7, 7, 0],
+                [-1, -1, 1, 2, 0],
+                [-1, -1, 3, 4, 0],
+                [-1, -1, 8, 8, 0]
+            ]
+        );
+
+        // max-plus with -inf below and 0 above
+        let arr = array![-3., -5., -2.];
+        let ret = arr
+            .conv_generic(
+                &array![0., 0., 0.],
+                ConvMode::Same,
+                PaddingMode::const_explicit([[f64::NEG_INFINITY, 0.]]),
+                |x, k| x + k,
+                f64::max,
+                f64::NEG_INFINITY,
+            )
+            .unwrap();
+        assert_eq!(ret, array![-3., -2., 0.]);
+    }
+
     #[test]
     fn tch_example() {
         let arr =
@@ -414,7 +722,9 @@ mod tests {
             [[1, 1, 1], [1, 1, 1], [1, 1, 1]],
             [[1, 1, 1], [1, 1, 1], [1, 1, 1]]
         ];
-        let explicit_conv = ConvMode::Same.unfold(&kernel.into_kernel_with_dilation());
+        let explicit_conv = ConvMode::Same
+            .unfold(&kernel.into_kernel_with_dilation(), [2, 2, 3])
+            .unwrap();
         let explicit_padding = explicit_conv.padding;
         check(&arr, PaddingMode::Zeros, explicit_padding);
         check(&arr, PaddingMode::Const(7), explicit_padding);
@@ -424,7 +734,9 @@ mod tests {
 
         let arr = array![[1, 2], [3, 4]];
         let kernel = array![[1, 1], [1, 1]];
-        let explicit_conv = ConvMode::Full.unfold(&kernel.into_kernel_with_dilation());
+        let explicit_conv = ConvMode::Full
+            .unfold(&kernel.into_kernel_with_dilation(), [2, 2])
+            .unwrap();
         let explicit_padding = explicit_conv.padding;
         check(&arr, PaddingMode::Zeros, explicit_padding);
         check(&arr, PaddingMode::Const(7), explicit_padding);
@@ -434,7 +746,9 @@ mod tests {
 
         let arr = array![1, 2, 3];
         let kernel = array![1, 1, 1, 1];
-        let explicit_conv = ConvMode::Same.unfold(&kernel.into_kernel_with_dilation());
+        let explicit_conv = ConvMode::Same
+            .unfold(&kernel.into_kernel_with_dilation(), [3])
+            .unwrap();
         let explicit_padding = explicit_conv.padding;
         check(&arr, PaddingMode::Zeros, explicit_padding);
         check(&arr, PaddingMode::Const(7), explicit_padding);
@@ -0,0 +1,337 @@
+//! `FromStr` / `Display` of the modes, for configs and command lines.
+//!
+//! `ConvMode`: `full`, `same`, `valid`, `circular`, `same:pytorch` (or `tensorflow`,
+//! `scipy`), `causal:0` (time axis, the last one when left out), `custom:1,2/2,3`
+//! (padding / strides), `explicit:1-2,0-0/1,1` (before-after padding / strides) and
+//! `output:5,5/1,1` (shape / strides).
+//! the strides can be left out for unit strides.
+//!
+//! `PaddingMode`: `zeros`, `const:1.5`, `reflect`, `symmetric`, `replicate`, `circular`,
+//! `custom:reflect,zeros` (one border per axis) and `explicit:zeros/reflect,const:1/zeros`
+//! (before/after border per axis).
+
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use num::traits::NumAssign;
+
+use crate::{BorderType, ConvMode, PaddingMode, SamePolicy};
+
+impl<const N: usize> FromStr for ConvMode<N> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, args) = s.split_once(':').unwrap_or((s, ""));
+        let (first, strides) = args.split_once('/').unwrap_or((args, ""));
+        let strides = || {
+            if strides.is_empty() {
+                Ok([1; N])
+            } else {
+                per_axis(strides, |n| n.parse().ok())
+            }
+        };
+
+        Ok(match (name, args) {
+            ("full", "") => ConvMode::Full,
+            ("same", "") => ConvMode::Same,
+            ("valid", "") => ConvMode::Valid,
+            ("circular", "") => ConvMode::Circular,
+            ("causal", "") => ConvMode::Causal { axis: N - 1 },
+            ("causal", axis) => ConvMode::Causal {
+                axis: axis
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid axis {:?} in {:?}", axis, s))?,
+            },
+            ("same", "pytorch") => ConvMode::SameAs(SamePolicy::PyTorch),
+            ("same", "tensorflow") => ConvMode::SameAs(SamePolicy::TensorFlow),
+            ("same", "scipy") => ConvMode::SameAs(SamePolicy::Scipy),
+            ("custom", _) => ConvMode::Custom {
+                padding: per_axis(first, |n| n.parse().ok())?,
+                strides: strides()?,
+            },
+            ("explicit", _) => ConvMode::Explicit {
+                padding: per_axis(first, |pair| {
+                    let (before, after) = pair.split_once('-')?;
+                    Some([before.parse().ok()?, after.parse().ok()?])
+                })?,
+                strides: strides()?,
+            },
+            ("output", _) => ConvMode::OutputSize {
+                shape: per_axis(first, |n| n.parse().ok())?,
+                strides: strides()?,
+            },
+            _ => return Err(format!("unknown conv mode {:?}", s)),
+        })
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for ConvMode<N> {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl<const N: usize> Display for ConvMode<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvMode::Full => f.write_str("full"),
+            ConvMode::Same => f.write_str("same"),
+            ConvMode::Valid => f.write_str("valid"),
+            ConvMode::Circular => f.write_str("circular"),
+            ConvMode::Causal { axis } => write!(f, "causal:{}", axis),
+            ConvMode::SameAs(policy) => f.write_str(match policy {
+                SamePolicy::PyTorch => "same:pytorch",
+                SamePolicy::TensorFlow => "same:tensorflow",
+                SamePolicy::Scipy => "same:scipy",
+            }),
+            ConvMode::Custom { padding, strides } => {
+                write!(f, "custom:{}/{}", join(padding), join(strides))
+            }
+            ConvMode::Explicit { padding, strides } => {
+                let padding = padding.map(|[before, after]| format!("{}-{}", before, after));
+                write!(f, "explicit:{}/{}", join(&padding), join(strides))
+            }
+            ConvMode::OutputSize { shape, strides } => {
+                write!(f, "output:{}/{}", join(shape), join(strides))
+            }
+        }
+    }
+}
+
+impl<const N: usize, T> FromStr for PaddingMode<N, T>
+where
+    T: NumAssign + Copy + FromStr,
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(match s.split_once(':') {
+            Some(("custom", borders)) => {
+                PaddingMode::Custom(per_axis(borders, |b| b.parse().ok())?)
+            }
+            Some(("explicit", borders)) => PaddingMode::Explicit(per_axis(borders, |pair| {
+                let (before, after) = pair.split_once('/')?;
+                Some([before.parse().ok()?, after.parse().ok()?])
+            })?),
+            _ => match s.parse::<BorderType<T>>()? {
+                BorderType::Zeros => PaddingMode::Zeros,
+                BorderType::Const(value) => PaddingMode::Const(value),
+                BorderType::Reflect => PaddingMode::Reflect,
+                BorderType::Symmetric => PaddingMode::Symmetric,
+                BorderType::Replicate => PaddingMode::Replicate,
+                BorderType::Circular => PaddingMode::Circular,
+            },
+        })
+    }
+}
+
+impl<const N: usize, T> TryFrom<&str> for PaddingMode<N, T>
+where
+    T: NumAssign + Copy + FromStr,
+{
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl<const N: usize, T> Display for PaddingMode<N, T>
+where
+    T: NumAssign + Copy + Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaddingMode::Zeros => f.write_str("zeros"),
+            PaddingMode::Const(value) => write!(f, "const:{}", value),
+            PaddingMode::Reflect => f.write_str("reflect"),
+            PaddingMode::Symmetric => f.write_str("symmetric"),
+            PaddingMode::Replicate => f.write_str("replicate"),
+            PaddingMode::Circular => f.write_str("circular"),
+            PaddingMode::Custom(borders) => write!(f, "custom:{}", join(borders)),
+            PaddingMode::Explicit(borders) => {
+                let borders = borders.map(|[before, after]| format!("{}/{}", before, after));
+                write!(f, "explicit:{}", join(&borders))
+            }
+        }
+    }
+}
+
+impl<T> FromStr for BorderType<T>
+where
+    T: NumAssign + Copy + FromStr,
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(match s {
+            "zeros" => BorderType::Zeros,
+            "reflect" => BorderType::Reflect,
+            "symmetric" => BorderType::Symmetric,
+            "replicate" => BorderType::Replicate,
+            "circular" => BorderType::Circular,
+            _ => match s.split_once(':') {
+                Some(("const", value)) => BorderType::Const(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid constant {:?}", value))?,
+                ),
+                _ => return Err(format!("unknown padding {:?}", s)),
+            },
+        })
+    }
+}
+
+impl<T> Display for BorderType<T>
+where
+    T: NumAssign + Copy + Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorderType::Zeros => f.write_str("zeros"),
+            BorderType::Const(value) => write!(f, "const:{}", value),
+            BorderType::Reflect => f.write_str("reflect"),
+            BorderType::Symmetric => f.write_str("symmetric"),
+            BorderType::Replicate => f.write_str("replicate"),
+            BorderType::Circular => f.write_str("circular"),
+        }
+    }
+}
+
+// one comma separated item per axis
+fn per_axis<A, const N: usize>(
+    s: &str,
+    item: impl Fn(&str) -> Option<A>,
+) -> Result<[A; N], String> {
+    let items = s
+        .split(',')
+        .map(|i| item(i.trim()).ok_or_else(|| format!("invalid item {:?} in {:?}", i, s)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let len = items.len();
+
+    items
+        .try_into()
+        .map_err(|_| format!("{} items in {:?} for {} axes", len, s, N))
+}
+
+fn join(items: &[impl ToString]) -> String {
+    items
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for (s, conv_mode) in [
+            ("full", ConvMode::Full),
+            ("same", ConvMode::Same),
+            ("valid", ConvMode::Valid),
+            ("circular", ConvMode::Circular),
+            ("same:tensorflow", ConvMode::SameAs(SamePolicy::TensorFlow)),
+            ("causal:0", ConvMode::Causal { axis: 0 }),
+            (
+                "custom:1,2/2,3",
+                ConvMode::Custom {
+                    padding: [1, 2],
+                    strides: [2, 3],
+                },
+            ),
+            (
+                "explicit:1-2,0-3/1,2",
+                ConvMode::Explicit {
+                    padding: [[1, 2], [0, 3]],
+                    strides: [1, 2],
+                },
+            ),
+            (
+                "output:5,6/1,1",
+                ConvMode::OutputSize {
+                    shape: [5, 6],
+                    strides: [1, 1],
+                },
+            ),
+        ] {
+            assert_eq!(conv_mode.to_string(), s);
+            assert_eq!(
+                format!("{:?}", s.parse::<ConvMode<2>>().unwrap()),
+                format!("{:?}", conv_mode)
+            );
+        }
+
+        // unit strides by default
+        assert!(matches!(
+            ConvMode::<2>::try_from(" custom:1, 2 "),
+            Ok(ConvMode::Custom {
+                padding: [1, 2],
+                strides: [1, 1]
+            })
+        ));
+        for s in [
+            "same:keras",
+            "custom:1/2,3",
+            "custom:1,x",
+            "explicit:1,2",
+            "fill",
+            "valid:1",
+            "causal:x",
+        ] {
+            assert!(s.parse::<ConvMode<2>>().is_err(), "{}", s);
+        }
+
+        for (s, padding_mode) in [
+            ("zeros", PaddingMode::Zeros),
+            ("const:-1.5", PaddingMode::Const(-1.5)),
+            ("symmetric", PaddingMode::Symmetric),
+            (
+                "custom:reflect,const:2",
+                PaddingMode::Custom([BorderType::Reflect, BorderType::Const(2.)]),
+            ),
+            (
+                "explicit:zeros/replicate,circular/const:0.5",
+                PaddingMode::Explicit([
+                    [BorderType::Zeros, BorderType::Replicate],
+                    [BorderType::Circular, BorderType::Const(0.5)],
+                ]),
+            ),
+        ] {
+            assert_eq!(padding_mode.to_string(), s);
+            assert_eq!(
+                format!("{:?}", s.parse::<PaddingMode<2, f64>>().unwrap()),
+                format!("{:?}", padding_mode)
+            );
+        }
+
+        assert!(matches!(
+            PaddingMode::<1, i32>::try_from("const:3"),
+            Ok(PaddingMode::Const(3))
+        ));
+        for s in [
+            "mirror",
+            "const:x",
+            "custom:reflect",
+            "explicit:zeros,zeros",
+        ] {
+            assert!(s.parse::<PaddingMode<2, f32>>().is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{MorphologyExt, PaddingMode};
+
+pub trait PeakExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// local maxima above `threshold`, strongest first: the elements that are the maximum
+    /// of the box of radius `min_distance` around them, where a peak also suppresses the
+    /// weaker ones (and the rest of a plateau) at most `min_distance` away on every axis.
+    fn find_peaks(
+        &self,
+        min_distance: usize,
+        threshold: T,
+    ) -> Result<Vec<[usize; N]>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> PeakExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + PartialOrd + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn find_peaks(
+        &self,
+        min_distance: usize,
+        threshold: T,
+    ) -> Result<Vec<[usize; N]>, crate::Error<N>> {
+        // replicated borders don't change the maximum of the windows cut by the edges
+        let window = Array::from_elem([2 * min_distance + 1; N], true);
+        let maxima = self.dilate(&window, PaddingMode::Replicate)?;
+
+        let mut candidates = self
+            .indexed_iter()
+            .zip(maxima.iter())
+            .filter(|((_, &v), &max)| v > threshold && v == max)
+            .map(|((index, &v), _)| {
+                let index = index.into_dimension();
+                (std::array::from_fn(|i| index[i]), v)
+            })
+            .collect::<Vec<([usize; N], T)>>();
+        candidates.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+        let mut peaks: Vec<[usize; N]> = Vec::new();
+        for (index, _) in candidates {
+            let suppressed = peaks
+                .iter()
+                .any(|peak| (0..N).all(|i| peak[i].abs_diff(index[i]) <= min_distance));
+            if !suppressed {
+                peaks.push(index);
+            }
+        }
+
+        Ok(peaks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+
+    #[test]
+    fn find_peaks() {
+        let mut arr = Array2::<f64>::zeros((9, 10));
+        arr[[1, 1]] = 5.;
+        arr[[2, 3]] = 4.;
+        arr[[6, 7]] = 3.;
+        // a plateau keeps one peak
+        arr[[8, 0]] = 2.;
+        arr[[8, 1]] = 2.;
+        arr[[0, 9]] = 0.5;
+
+        assert_eq!(
+            arr.find_peaks(1, 1.).unwrap(),
+            [[1, 1], [2, 3], [6, 7], [8, 0]]
+        );
+        // [2, 3] is within 2 of the stronger [1, 1]
+        assert_eq!(arr.find_peaks(2, 1.).unwrap(), [[1, 1], [6, 7], [8, 0]]);
+        assert_eq!(arr.find_peaks(2, 2.5).unwrap(), [[1, 1], [6, 7]]);
+        assert_eq!(arr.find_peaks(1, 0.).unwrap().len(), 5);
+
+        let signal = array![0, 3, 1, 1, 4, 2, 7, 0];
+        assert_eq!(signal.find_peaks(1, 0).unwrap(), [[6], [4], [1]]);
+        assert_eq!(signal.find_peaks(2, 0).unwrap(), [[6], [1]]);
+    }
+}
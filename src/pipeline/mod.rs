@@ -0,0 +1,280 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, Dimension, IntoDimension, Ix, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{signal::convolve, ConvExt, ConvMode, ConvPlan, PaddingMode};
+
+/// a chain of `conv` stages, each run on the output of the previous one.
+///
+/// consecutive stages with unit strides run as a single conv with the kernels convolved
+/// together, whenever that gives the same output: the later stage doesn't pad, or both pad
+/// with zeros and the earlier one by at least its kernel size - 1 wherever the later
+/// pads (e.g. `Full`). other stages run one after the other.
+#[derive(Debug, Clone)]
+pub struct Pipeline<T, const N: usize>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: Dimension,
+{
+    stages: Vec<Stage<T, N>>,
+}
+
+#[derive(Debug, Clone)]
+struct Stage<T, const N: usize>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: Dimension,
+{
+    kernel: Array<T, Dim<[Ix; N]>>,
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+}
+
+impl<T, const N: usize> Default for Pipeline<T, N>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: Dimension,
+{
+    fn default() -> Self {
+        Self { stages: Vec::new() }
+    }
+}
+
+impl<T, const N: usize> Pipeline<T, N>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// appends a stage, run like `conv(kernel, conv_mode, padding_mode)`.
+    pub fn then<SK: Data<Elem = T>>(
+        mut self,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Self {
+        self.stages.push(Stage {
+            kernel: kernel.to_owned(),
+            conv_mode,
+            padding_mode,
+        });
+        self
+    }
+
+    /// the convs actually run for an input of `input_shape`, after fusion.
+    pub fn plan(&self, input_shape: [usize; N]) -> Result<Vec<ConvPlan<N>>, crate::Error<N>> {
+        let mut shape = input_shape;
+        self.fused(input_shape)?
+            .iter()
+            .map(|stage| {
+                let plan = ConvPlan::new(
+                    shape,
+                    std::array::from_fn(|i| stage.kernel.shape()[i]),
+                    1,
+                    stage.conv_mode,
+                );
+                shape = plan.output_shape()?;
+                Ok(plan)
+            })
+            .collect()
+    }
+
+    pub fn run<S: Data<Elem = T>>(
+        &self,
+        input: &ArrayBase<S, Dim<[Ix; N]>>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let input_shape = std::array::from_fn(|i| input.shape()[i]);
+        let mut stages = self.fused(input_shape)?.into_iter();
+
+        let Some(first) = stages.next() else {
+            return Ok(input.to_owned());
+        };
+        let output = input.conv(&first.kernel, first.conv_mode, first.padding_mode)?;
+
+        stages.try_fold(output, |output, stage| {
+            output.conv(&stage.kernel, stage.conv_mode, stage.padding_mode)
+        })
+    }
+
+    // the stages with explicit modes, fused where it gives the same output
+    fn fused(&self, input_shape: [usize; N]) -> Result<Vec<Stage<T, N>>, crate::Error<N>> {
+        let mut shape = input_shape;
+        let mut fused: Vec<Stage<T, N>> = Vec::new();
+
+        for stage in &self.stages {
+            let kernel_dim = std::array::from_fn(|i| stage.kernel.shape()[i]);
+            if kernel_dim.contains(&0) {
+                return Err(crate::Error::KernelShape(stage.kernel.raw_dim()));
+            }
+
+            // unfolding validates the shapes
+            let cm = stage.conv_mode.unfold_with_dim(kernel_dim, shape)?;
+            shape = std::array::from_fn(|i| {
+                (shape[i] + cm.padding[i][0] + cm.padding[i][1] - kernel_dim[i]) / cm.strides[i] + 1
+            });
+
+            let stage = Stage {
+                kernel: stage.kernel.clone(),
+                conv_mode: ConvMode::Explicit {
+                    padding: cm.padding,
+                    strides: cm.strides,
+                },
+                padding_mode: stage.conv_mode.padding_mode(stage.padding_mode),
+            };
+
+            match fused.last_mut().map(|last| fuse(last, &stage)) {
+                Some(Some(stage)) => *fused.last_mut().unwrap() = stage,
+                _ => fused.push(stage),
+            }
+        }
+
+        Ok(fused)
+    }
+}
+
+// `b` run on the output of `a` as a single stage, if the output is the same
+fn fuse<T, const N: usize>(a: &Stage<T, N>, b: &Stage<T, N>) -> Option<Stage<T, N>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let (
+        ConvMode::Explicit {
+            padding: pa,
+            strides: sa,
+        },
+        ConvMode::Explicit {
+            padding: pb,
+            strides: sb,
+        },
+    ) = (a.conv_mode, b.conv_mode)
+    else {
+        return None;
+    };
+    if sa != [1; N] || sb != [1; N] {
+        return None;
+    }
+
+    let (padding, padding_mode) = if pb == [[0; 2]; N] {
+        // b only reads the output of a
+        (pa, a.padding_mode)
+    } else if is_zeros(a.padding_mode)
+        && is_zeros(b.padding_mode)
+        && (0..N)
+            .all(|i| (0..2).all(|side| pb[i][side] == 0 || pa[i][side] + 1 >= a.kernel.shape()[i]))
+    {
+        // the zeros b pads with are outputs of a past the input
+        (
+            std::array::from_fn(|i| [pa[i][0] + pb[i][0], pa[i][1] + pb[i][1]]),
+            PaddingMode::Zeros,
+        )
+    } else {
+        return None;
+    };
+
+    // conv is a correlation, two in a row correlate with the convolution of the kernels
+    Some(Stage {
+        kernel: convolve(&a.kernel, &b.kernel, "full").ok()?,
+        conv_mode: ConvMode::Explicit {
+            padding,
+            strides: [1; N],
+        },
+        padding_mode,
+    })
+}
+
+fn is_zeros<T: NumAssign + Copy, const N: usize>(padding_mode: PaddingMode<N, T>) -> bool {
+    match padding_mode {
+        PaddingMode::Zeros => true,
+        PaddingMode::Const(c) => c == T::zero(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((9, 11), |(i, j)| ((i * 11 + j) % 7) as i32 - 3);
+        let blur = array![[1, 2, 1], [2, 4, 2], [1, 2, 1]];
+        let derivative = array![[1, 0, -1]];
+        let smoothing = array![[1], [3], [1]];
+
+        for (stages, passes) in [
+            (
+                [
+                    (ConvMode::Full, PaddingMode::Zeros),
+                    (ConvMode::Full, PaddingMode::Zeros),
+                    (ConvMode::Valid, PaddingMode::Reflect),
+                ],
+                1,
+            ),
+            (
+                [
+                    (ConvMode::Same, PaddingMode::Reflect),
+                    (ConvMode::Valid, PaddingMode::Zeros),
+                    (ConvMode::Same, PaddingMode::Zeros),
+                ],
+                2,
+            ),
+            (
+                [
+                    (ConvMode::Same, PaddingMode::Zeros),
+                    (
+                        ConvMode::Custom {
+                            padding: [1, 1],
+                            strides: [2, 1],
+                        },
+                        PaddingMode::Zeros,
+                    ),
+                    (ConvMode::Full, PaddingMode::Replicate),
+                ],
+                3,
+            ),
+        ] {
+            let pipeline = Pipeline::new()
+                .then(&blur, stages[0].0, stages[0].1)
+                .then(&derivative, stages[1].0, stages[1].1)
+                .then(&smoothing, stages[2].0, stages[2].1);
+
+            let expected = arr
+                .conv(&blur, stages[0].0, stages[0].1)
+                .unwrap()
+                .conv(&derivative, stages[1].0, stages[1].1)
+                .unwrap()
+                .conv(&smoothing, stages[2].0, stages[2].1)
+                .unwrap();
+
+            let plan = pipeline.plan([9, 11]).unwrap();
+            assert_eq!(plan.len(), passes);
+            assert_eq!(
+                &plan.last().unwrap().output_shape().unwrap()[..],
+                expected.shape()
+            );
+            assert_eq!(pipeline.run(&arr).unwrap(), expected);
+        }
+
+        assert_eq!(Pipeline::new().run(&arr).unwrap(), arr);
+        assert!(Pipeline::new()
+            .then(&array![[1; 12]], ConvMode::Valid, PaddingMode::Zeros)
+            .run(&arr)
+            .is_err());
+    }
+}
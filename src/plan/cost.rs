@@ -0,0 +1,219 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    RwLock,
+};
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, Ix1, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+use rustfft::FftNum;
+
+use super::factorize;
+use crate::{Algorithm, ConvExt, ConvFFTExt, ConvMode, PaddingMode, SeparableConvExt};
+
+static FFT_THRESHOLD: AtomicUsize = AtomicUsize::new(512);
+static SEPARABLE_THRESHOLD: AtomicUsize = AtomicUsize::new(9);
+static COST_MODEL: RwLock<Option<Box<dyn CostModel>>> = RwLock::new(None);
+
+/// the kernel elements from which `conv_auto` goes through the fft, 512 by default.
+pub fn set_fft_threshold(elems: usize) {
+    FFT_THRESHOLD.store(elems, Ordering::Relaxed);
+}
+
+/// the kernel elements from which `conv_auto` runs a rank one kernel as one 1D conv per
+/// axis, 9 by default.
+pub fn set_separable_threshold(elems: usize) {
+    SEPARABLE_THRESHOLD.store(elems, Ordering::Relaxed);
+}
+
+/// the model `conv_auto` picks the algorithm with, `None` for `Thresholds`.
+pub fn set_cost_model(model: Option<Box<dyn CostModel>>) {
+    *COST_MODEL.write().unwrap_or_else(|err| err.into_inner()) = model;
+}
+
+/// relative cost of running a conv of the given shapes with an algorithm,
+/// `None` where it shouldn't run. the cheapest one runs, direct on ties.
+pub trait CostModel: Send + Sync {
+    fn cost(
+        &self,
+        algorithm: Algorithm,
+        input_shape: &[usize],
+        kernel_shape: &[usize],
+    ) -> Option<f64>;
+}
+
+/// the default model: multiply-adds per output, the fft counted as `set_fft_threshold`
+/// of them and separable convs skipped below `set_separable_threshold`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Thresholds;
+
+impl CostModel for Thresholds {
+    fn cost(&self, algorithm: Algorithm, _: &[usize], kernel_shape: &[usize]) -> Option<f64> {
+        let elems = kernel_shape.iter().product::<usize>();
+
+        match algorithm {
+            Algorithm::Direct => Some(elems as f64),
+            Algorithm::Fft => Some(FFT_THRESHOLD.load(Ordering::Relaxed) as f64),
+            Algorithm::Separable => (elems >= SEPARABLE_THRESHOLD.load(Ordering::Relaxed))
+                .then(|| kernel_shape.iter().sum::<usize>() as f64),
+            // only the planner picks it, from measured timings
+            Algorithm::Im2col => None,
+        }
+    }
+}
+
+/// the algorithm `conv_auto` runs for the shapes, with the cost model set.
+pub fn auto_algorithm<T, S, const N: usize>(
+    input_shape: [usize; N],
+    kernel: &ArrayBase<S, Dim<[Ix; N]>>,
+) -> Algorithm
+where
+    T: FftNum + NumAssign + PartialOrd,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    choose(input_shape, kernel).0
+}
+
+// the cheapest algorithm, with the 1D kernels of a separable one
+fn choose<T, S, const N: usize>(
+    input_shape: [usize; N],
+    kernel: &ArrayBase<S, Dim<[Ix; N]>>,
+) -> (Algorithm, Option<[Array<T, Ix1>; N]>)
+where
+    T: FftNum + NumAssign + PartialOrd,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let model = COST_MODEL.read().unwrap_or_else(|err| err.into_inner());
+    let model: &dyn CostModel = model.as_deref().unwrap_or(&Thresholds);
+    let cost = |algorithm| model.cost(algorithm, &input_shape, kernel.shape());
+
+    let mut best = (
+        Algorithm::Direct,
+        cost(Algorithm::Direct).unwrap_or(f64::INFINITY),
+    );
+    if let Some(c) = cost(Algorithm::Fft) {
+        if c < best.1 {
+            best = (Algorithm::Fft, c);
+        }
+    }
+    // factorized only when it could win
+    if cost(Algorithm::Separable).is_some_and(|c| c < best.1) {
+        if let Some(kernels) = factorize(kernel) {
+            return (Algorithm::Separable, Some(kernels));
+        }
+    }
+
+    (best.0, None)
+}
+
+pub trait ConvAutoExt<T, const N: usize>
+where
+    T: FftNum + NumAssign + PartialOrd,
+{
+    /// conv with the algorithm the cost model finds cheapest, see `set_cost_model`.
+    /// direct with `set_deterministic`.
+    fn conv_auto<SK: Data<Elem = T>>(
+        &self,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> ConvAutoExt<T, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: FftNum + NumAssign + PartialOrd,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn conv_auto<SK: Data<Elem = T>>(
+        &self,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if crate::conv::deterministic() {
+            return self.conv(kernel, conv_mode, padding_mode);
+        }
+
+        let input_shape = std::array::from_fn(|i| self.shape()[i]);
+        let (algorithm, kernels) = choose(input_shape, kernel);
+        crate::trace::span!("conv_auto", algorithm = %algorithm);
+
+        match (algorithm, kernels) {
+            (Algorithm::Separable, Some(kernels)) => {
+                let views = std::array::from_fn(|i| kernels[i].view());
+                self.conv_separable(views, conv_mode, padding_mode)
+            }
+            (Algorithm::Fft, _) => self.conv_fft(kernel, conv_mode, padding_mode),
+            _ => self.conv(kernel, conv_mode, padding_mode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    struct Fixed(Algorithm);
+
+    impl CostModel for Fixed {
+        fn cost(&self, algorithm: Algorithm, _: &[usize], _: &[usize]) -> Option<f64> {
+            (algorithm == self.0).then_some(0.)
+        }
+    }
+
+    // one test, the thresholds and the model are global
+    #[test]
+    fn thresholds_and_models() {
+        let arr = Array2::from_shape_fn((9, 11), |(i, j)| ((i * 11 + j) % 5) as f64 - 2.);
+        let separable = array![[1., 0., -1.], [2., 0., -2.], [1., 0., -1.]];
+        let dense = array![[1., 2., 0.], [0., 1., 3.], [-1., 0., 1.]];
+        let expected = |kernel| {
+            arr.conv(kernel, ConvMode::Same, PaddingMode::Reflect)
+                .unwrap()
+        };
+
+        assert_eq!(auto_algorithm([9, 11], &separable), Algorithm::Separable);
+        assert_eq!(auto_algorithm([9, 11], &dense), Algorithm::Direct);
+        set_separable_threshold(10);
+        assert_eq!(auto_algorithm([9, 11], &separable), Algorithm::Direct);
+        set_fft_threshold(9);
+        assert_eq!(auto_algorithm([9, 11], &dense), Algorithm::Direct);
+        set_fft_threshold(8);
+        assert_eq!(auto_algorithm([9, 11], &dense), Algorithm::Fft);
+
+        for (model, kernel, algorithm) in [
+            (Algorithm::Fft, &dense, Algorithm::Fft),
+            (Algorithm::Separable, &separable, Algorithm::Separable),
+            // the kernel doesn't factorize
+            (Algorithm::Separable, &dense, Algorithm::Direct),
+        ] {
+            set_cost_model(Some(Box::new(Fixed(model))));
+            assert_eq!(auto_algorithm([9, 11], kernel), algorithm);
+            assert_close(
+                &arr.conv_auto(kernel, ConvMode::Same, PaddingMode::Reflect)
+                    .unwrap(),
+                &expected(kernel),
+                1e-9,
+            );
+        }
+
+        set_cost_model(None);
+        set_fft_threshold(512);
+        set_separable_threshold(9);
+        assert_eq!(auto_algorithm([9, 11], &separable), Algorithm::Separable);
+    }
+}
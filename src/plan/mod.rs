@@ -0,0 +1,135 @@
+mod cost;
+mod planner;
+
+use ndarray::{Dim, IntoDimension, Ix};
+
+use crate::{
+    dilation::{dilated, IntoDilation},
+    ConvMode,
+};
+
+pub use cost::{
+    auto_algorithm, set_cost_model, set_fft_threshold, set_separable_threshold, ConvAutoExt,
+    CostModel, Thresholds,
+};
+pub(crate) use planner::{factorize, im2col};
+pub use planner::{Algorithm, Dtype, Planner};
+
+/// output shape of `conv` / `conv_fft`, with the same validation, without running it.
+pub fn conv_output_shape<const N: usize>(
+    input_shape: [usize; N],
+    kernel_shape: [usize; N],
+    dilation: impl IntoDilation<N>,
+    conv_mode: ConvMode<N>,
+) -> Result<[usize; N], crate::Error<N>>
+where
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    crate::trace::span!(
+        "plan",
+        input = ?input_shape,
+        kernel = ?kernel_shape,
+        conv_mode = ?conv_mode
+    );
+
+    if input_shape.contains(&0) {
+        return Err(crate::Error::DataShape(input_shape.into_dimension()));
+    }
+    if kernel_shape.contains(&0) {
+        return Err(crate::Error::KernelShape(kernel_shape.into_dimension()));
+    }
+
+    let kernel_dim = dilated(kernel_shape, dilation.into_dilation())?;
+    let cm = conv_mode.unfold_with_dim(kernel_dim, input_shape)?;
+
+    let pds_dim: [usize; N] =
+        std::array::from_fn(|i| input_shape[i] + cm.padding[i][0] + cm.padding[i][1]);
+
+    Ok(std::array::from_fn(|i| {
+        (pds_dim[i] - kernel_dim[i]) / cm.strides[i] + 1
+    }))
+}
+
+/// the shapes and mode of a conv, to validate and size buffers ahead of time.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvPlan<const N: usize> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
+    pub input_shape: [usize; N],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
+    pub kernel_shape: [usize; N],
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::array"))]
+    pub dilation: [usize; N],
+    pub conv_mode: ConvMode<N>,
+}
+
+impl<const N: usize> ConvPlan<N>
+where
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    pub fn new(
+        input_shape: [usize; N],
+        kernel_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+    ) -> Self {
+        Self {
+            input_shape,
+            kernel_shape,
+            dilation: dilation.into_dilation(),
+            conv_mode,
+        }
+    }
+
+    pub fn output_shape(&self) -> Result<[usize; N], crate::Error<N>> {
+        conv_output_shape(
+            self.input_shape,
+            self.kernel_shape,
+            self.dilation,
+            self.conv_mode,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array;
+
+    use super::*;
+    use crate::{dilation::WithDilation, ConvExt, PaddingMode, SamePolicy};
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array::<f32, _>::ones((9, 14));
+        let kernel = Array::<f32, _>::ones((4, 3));
+
+        for conv_mode in [
+            ConvMode::Full,
+            ConvMode::Same,
+            ConvMode::SameAs(SamePolicy::PyTorch),
+            ConvMode::Valid,
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [2, 3],
+            },
+            ConvMode::Explicit {
+                padding: [[0, 3], [1, 0]],
+                strides: [3, 1],
+            },
+            ConvMode::OutputSize {
+                shape: [5, 5],
+                strides: [2, 3],
+            },
+        ] {
+            let res = arr
+                .conv(kernel.with_dilation([2, 1]), conv_mode, PaddingMode::Zeros)
+                .unwrap();
+            let plan = ConvPlan::new([9, 14], [4, 3], [2, 1], conv_mode);
+            assert_eq!(&plan.output_shape().unwrap()[..], res.shape());
+        }
+
+        assert!(conv_output_shape([9, 14], [4, 3], [3, 1], ConvMode::Valid).is_err());
+        assert!(conv_output_shape([9, 0], [4, 3], 1, ConvMode::Full).is_err());
+        assert!(conv_output_shape([9, 14], [0, 3], 1, ConvMode::Full).is_err());
+    }
+}
@@ -0,0 +1,630 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, Dimension, IntoDimension, Ix, Ix1, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+use rustfft::FftNum;
+
+use crate::{ConvExt, ConvFFTExt, ConvMode, PaddingMode, SeparableConvExt, UnfoldExt};
+
+/// the algorithms a `Planner` times against each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Algorithm {
+    Direct,
+    // only runs when the kernel is the outer product of one 1D kernel per axis
+    Separable,
+    Fft,
+    // the windows of the input unfolded as rows, times the flattened kernel
+    Im2col,
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::Direct => "direct",
+            Algorithm::Separable => "separable",
+            Algorithm::Fft => "fft",
+            Algorithm::Im2col => "im2col",
+        })
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "direct" => Ok(Algorithm::Direct),
+            "separable" => Ok(Algorithm::Separable),
+            "fft" => Ok(Algorithm::Fft),
+            "im2col" => Ok(Algorithm::Im2col),
+            _ => Err(format!("unknown algorithm {:?}", s)),
+        }
+    }
+}
+
+/// the element types a `Planner` measures, by the name they have in the saved table.
+pub trait Dtype {
+    const NAME: &'static str;
+}
+
+impl Dtype for f32 {
+    const NAME: &'static str = "f32";
+}
+
+impl Dtype for f64 {
+    const NAME: &'static str = "f64";
+}
+
+const PADDINGS: [&str; 8] = [
+    "zeros",
+    "const",
+    "reflect",
+    "symmetric",
+    "replicate",
+    "circular",
+    "custom",
+    "explicit",
+];
+
+// element type, input shape, kernel shape, padding before and after of every axis,
+// strides, kind of padding
+type Key = (
+    String,
+    Vec<usize>,
+    Vec<usize>,
+    Vec<usize>,
+    Vec<usize>,
+    String,
+);
+
+/// tuning table of measured conv timings, keyed by element type, shapes, the padding and
+/// strides the conv mode unfolds to, and the kind of padding.
+///
+/// `measure` times every algorithm once per key, `conv` then runs the fastest one that
+/// applies to the kernel. the table is saved as text, one key per line:
+/// `f32 64,64 5,5 2,2,2,2 1,1 zeros separable:41000 direct:180000 im2col:210000 fft:260000`
+/// (nanoseconds, fastest first).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Planner {
+    table: BTreeMap<Key, Vec<(Algorithm, Duration)>>,
+}
+
+impl Planner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// times the algorithms on a conv of the given shapes and modes, unless the key is
+    /// already in the table, and returns the fastest.
+    pub fn measure<T, const N: usize>(
+        &mut self,
+        input_shape: [usize; N],
+        kernel_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Algorithm, crate::Error<N>>
+    where
+        T: FftNum + NumAssign + Dtype,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+    {
+        let key = key(input_shape, kernel_shape, conv_mode, &padding_mode)?;
+        if let Some(timings) = self.table.get(&key) {
+            return Ok(timings[0].0);
+        }
+
+        let input = Array::from_shape_fn(input_shape, |idx| {
+            T::from_usize(idx.into_dimension().slice().iter().sum::<usize>() % 7).unwrap()
+        });
+        let kernels: [Array<T, _>; N] = std::array::from_fn(|i| {
+            Array::from_shape_fn(kernel_shape[i], |j| T::from_usize(j % 3 + 1).unwrap())
+        });
+        let kernel = Array::from_shape_fn(kernel_shape, |idx| {
+            let idx = idx.into_dimension();
+            (0..N).fold(T::one(), |acc, i| acc * kernels[i][idx[i]])
+        });
+        let mut timings = vec![
+            (
+                Algorithm::Direct,
+                time(|| input.conv(&kernel, conv_mode, padding_mode))?,
+            ),
+            (
+                Algorithm::Fft,
+                time(|| input.conv_fft(&kernel, conv_mode, padding_mode))?,
+            ),
+            (
+                Algorithm::Im2col,
+                time(|| im2col(&input, &kernel, [1; N], conv_mode, padding_mode))?,
+            ),
+        ];
+        if N > 1 {
+            let views = std::array::from_fn(|i| kernels[i].view());
+            timings.push((
+                Algorithm::Separable,
+                time(|| input.conv_separable(views, conv_mode, padding_mode))?,
+            ));
+        }
+        timings.sort_by_key(|&(_, duration)| duration);
+
+        let fastest = timings[0].0;
+        self.table.insert(key, timings);
+        Ok(fastest)
+    }
+
+    /// the fastest algorithm measured for the shapes and modes, if any.
+    pub fn algorithm<T: Dtype, const N: usize>(
+        &self,
+        input_shape: [usize; N],
+        kernel_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Option<Algorithm>
+    where
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    {
+        let key = key(input_shape, kernel_shape, conv_mode, &padding_mode).ok()?;
+        self.table.get(&key).map(|timings| timings[0].0)
+    }
+
+    /// conv with the fastest measured algorithm that applies to the kernel,
+    /// direct conv for shapes and modes that were not measured or with `set_deterministic`.
+    pub fn conv<T, S, SK, const N: usize>(
+        &self,
+        input: &ArrayBase<S, Dim<[Ix; N]>>,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+    where
+        T: FftNum + NumAssign + PartialOrd + Dtype,
+        S: Data<Elem = T>,
+        SK: Data<Elem = T>,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+    {
+        self.run(
+            input,
+            kernel,
+            conv_mode,
+            padding_mode,
+            crate::conv::deterministic(),
+        )
+    }
+
+    fn run<T, S, SK, const N: usize>(
+        &self,
+        input: &ArrayBase<S, Dim<[Ix; N]>>,
+        kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        deterministic: bool,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+    where
+        T: FftNum + NumAssign + PartialOrd + Dtype,
+        S: Data<Elem = T>,
+        SK: Data<Elem = T>,
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+        SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+            SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+    {
+        // timings differ between runs, and so would the rounding of the algorithm picked
+        if deterministic {
+            return input.conv(kernel, conv_mode, padding_mode);
+        }
+
+        let key = key(
+            std::array::from_fn(|i| input.shape()[i]),
+            std::array::from_fn(|i| kernel.shape()[i]),
+            conv_mode,
+            &padding_mode,
+        )?;
+        let timings = self.table.get(&key).map_or(&[][..], |timings| timings);
+
+        for &(algorithm, _) in timings {
+            match algorithm {
+                Algorithm::Direct => return input.conv(kernel, conv_mode, padding_mode),
+                Algorithm::Fft => return input.conv_fft(kernel, conv_mode, padding_mode),
+                Algorithm::Im2col => return im2col(input, kernel, [1; N], conv_mode, padding_mode),
+                Algorithm::Separable => {
+                    if let Some(kernels) = factorize(kernel) {
+                        let views = std::array::from_fn(|i| kernels[i].view());
+                        return input.conv_separable(views, conv_mode, padding_mode);
+                    }
+                }
+            }
+        }
+
+        input.conv(kernel, conv_mode, padding_mode)
+    }
+
+    /// writes the tuning table, to be read back by `load` in later runs.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// adds the entries of another table, replacing the keys measured in both.
+    pub fn merge(&mut self, other: Planner) {
+        self.table.extend(other.table);
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+impl Display for Planner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |shape: &[usize]| {
+            shape
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        for ((dtype, input_shape, kernel_shape, padding, strides, padding_mode), timings) in
+            &self.table
+        {
+            write!(
+                f,
+                "{} {} {} {} {} {}",
+                dtype,
+                join(input_shape),
+                join(kernel_shape),
+                join(padding),
+                join(strides),
+                padding_mode
+            )?;
+            for (algorithm, duration) in timings {
+                write!(f, " {}:{}", algorithm, duration.as_nanos())?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Planner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let shape = |field: Option<&str>| -> Result<Vec<usize>, String> {
+            field
+                .ok_or("missing shape")?
+                .split(',')
+                .map(|n| n.parse().map_err(|_| format!("invalid shape {:?}", n)))
+                .collect()
+        };
+
+        let mut table = BTreeMap::new();
+
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let entry = || -> Result<_, String> {
+                let mut fields = line.split_whitespace();
+                let dtype = fields.next().unwrap().to_string();
+                let (input_shape, kernel_shape) = (shape(fields.next())?, shape(fields.next())?);
+                if input_shape.len() != kernel_shape.len() {
+                    return Err("input and kernel of different dimensions".to_string());
+                }
+                let (padding, strides) = (shape(fields.next())?, shape(fields.next())?);
+                if padding.len() != 2 * input_shape.len() || strides.len() != input_shape.len() {
+                    return Err("padding or strides of different dimensions".to_string());
+                }
+                let padding_mode = fields.next().ok_or("missing padding")?;
+                if !PADDINGS.contains(&padding_mode) {
+                    return Err(format!("unknown padding {:?}", padding_mode));
+                }
+                let padding_mode = padding_mode.to_string();
+
+                let mut timings = fields
+                    .map(|field| {
+                        let (algorithm, nanos) = field
+                            .split_once(':')
+                            .ok_or(format!("invalid timing {:?}", field))?;
+                        let nanos = nanos
+                            .parse()
+                            .map_err(|_| format!("invalid timing {:?}", field))?;
+                        Ok((algorithm.parse()?, Duration::from_nanos(nanos)))
+                    })
+                    .collect::<Result<Vec<(Algorithm, Duration)>, String>>()?;
+                if timings.is_empty() {
+                    return Err("no timings".to_string());
+                }
+                timings.sort_by_key(|&(_, duration)| duration);
+
+                Ok((
+                    (
+                        dtype,
+                        input_shape,
+                        kernel_shape,
+                        padding,
+                        strides,
+                        padding_mode,
+                    ),
+                    timings,
+                ))
+            };
+
+            let (key, timings) = entry().map_err(|err| format!("line {}: {}", i + 1, err))?;
+            table.insert(key, timings);
+        }
+
+        Ok(Self { table })
+    }
+}
+
+fn key<T: Dtype, const N: usize>(
+    input_shape: [usize; N],
+    kernel_shape: [usize; N],
+    conv_mode: ConvMode<N>,
+    padding_mode: &PaddingMode<N, T>,
+) -> Result<Key, crate::Error<N>>
+where
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let cm = conv_mode.unfold_with_dim(kernel_shape, input_shape)?;
+    let padding_mode = match padding_mode {
+        PaddingMode::Zeros => PADDINGS[0],
+        PaddingMode::Const(_) => PADDINGS[1],
+        PaddingMode::Reflect => PADDINGS[2],
+        PaddingMode::Symmetric => PADDINGS[3],
+        PaddingMode::Replicate => PADDINGS[4],
+        PaddingMode::Circular => PADDINGS[5],
+        PaddingMode::Custom(_) => PADDINGS[6],
+        PaddingMode::Explicit(_) => PADDINGS[7],
+    };
+
+    Ok((
+        T::NAME.to_string(),
+        input_shape.to_vec(),
+        kernel_shape.to_vec(),
+        cm.padding.concat(),
+        cm.strides.to_vec(),
+        padding_mode.to_string(),
+    ))
+}
+
+// conv as the product of the unfolded input with the flattened kernel
+pub(crate) fn im2col<T, S, SK, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+    dilation: [usize; N],
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: FftNum + NumAssign,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let input_shape = std::array::from_fn(|i| input.shape()[i]);
+    let kernel_shape = std::array::from_fn(|i| kernel.shape()[i]);
+    let patches = input.unfold(kernel_shape, dilation, conv_mode, padding_mode)?;
+    let taps = kernel.iter().copied().collect::<Array<T, Ix1>>();
+    let output_shape = crate::conv_output_shape(input_shape, kernel_shape, dilation, conv_mode)?;
+
+    // one row per output, in row major order
+    Ok(patches.dot(&taps).into_shape(output_shape).unwrap())
+}
+
+// best of a few runs, after a warm up
+fn time<T, const N: usize>(
+    f: impl Fn() -> Result<T, crate::Error<N>>,
+) -> Result<Duration, crate::Error<N>> {
+    f()?;
+    Ok((0..3)
+        .map(|_| {
+            let now = Instant::now();
+            let _ = f();
+            now.elapsed()
+        })
+        .min()
+        .unwrap())
+}
+
+// the 1D kernels whose outer product is the kernel, if it has rank one
+pub(crate) fn factorize<T, S, const N: usize>(
+    kernel: &ArrayBase<S, Dim<[Ix; N]>>,
+) -> Option<[Array<T, Ix1>; N]>
+where
+    T: FftNum + NumAssign + PartialOrd,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    if N < 2 {
+        return None;
+    }
+
+    let (pivot, &value) = kernel
+        .indexed_iter()
+        .max_by(|(_, a), (_, b)| a.abs().partial_cmp(&b.abs()).unwrap())?;
+    if value == T::zero() {
+        return None;
+    }
+
+    // the lines through the pivot, all but the first divided by the pivot
+    let pivot = pivot.into_dimension();
+    let kernels: [Array<T, Ix1>; N] = std::array::from_fn(|axis| {
+        Array::from_shape_fn(kernel.shape()[axis], |j| {
+            let mut idx = pivot;
+            idx[axis] = j;
+            if axis == 0 {
+                kernel[idx]
+            } else {
+                kernel[idx] / value
+            }
+        })
+    });
+
+    let tolerance = value.abs() * T::from_f64(1e-6).unwrap();
+    kernel
+        .indexed_iter()
+        .all(|(idx, &k)| {
+            let idx = idx.into_dimension();
+            let product = (0..N).fold(T::one(), |acc, i| acc * kernels[i][idx[i]]);
+            (product - k).abs() <= tolerance
+        })
+        .then_some(kernels)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn measure_and_reload() {
+        let (same, zeros) = (ConvMode::Same, PaddingMode::Zeros);
+        let mut planner = Planner::new();
+        let fastest = planner
+            .measure::<f32, 2>([24, 20], [5, 3], same, zeros)
+            .unwrap();
+        assert_eq!(
+            planner.algorithm::<f32, 2>([24, 20], [5, 3], same, zeros),
+            Some(fastest)
+        );
+        assert_eq!(
+            planner.algorithm::<f64, 2>([24, 20], [5, 3], same, PaddingMode::Zeros),
+            None
+        );
+        // rankings of other modes and paddings are measured on their own
+        assert_eq!(
+            planner.algorithm::<f32, 2>([24, 20], [5, 3], ConvMode::Full, zeros),
+            None
+        );
+        assert_eq!(
+            planner.algorithm::<f32, 2>([24, 20], [5, 3], same, PaddingMode::Reflect),
+            None
+        );
+
+        // measured once per key
+        let table = planner.clone();
+        planner
+            .measure::<f32, 2>([24, 20], [5, 3], same, zeros)
+            .unwrap();
+        assert_eq!(planner, table);
+        planner
+            .measure::<f64, 1>([100], [9], ConvMode::Valid, PaddingMode::Const(1.))
+            .unwrap();
+        assert_eq!(planner.len(), 2);
+
+        let path = std::env::temp_dir().join(format!("ndarray-conv-plan-{}", std::process::id()));
+        planner.save(&path).unwrap();
+        let loaded = Planner::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, planner);
+        assert!(planner.to_string().contains("f64 100 9 0,0 1 const "));
+        // every algorithm that applies is timed, separable only for more than one axis
+        let line = planner.to_string();
+        let line = line.lines().find(|l| l.starts_with("f32")).unwrap();
+        for algorithm in ["direct:", "separable:", "fft:", "im2col:"] {
+            assert!(line.contains(algorithm), "{line}");
+        }
+
+        assert!("f32 24,20 5 2,2,1,1 1,1 zeros direct:10"
+            .parse::<Planner>()
+            .is_err());
+        assert!("f32 24,20 5,3 2,2 1,1 zeros direct:10"
+            .parse::<Planner>()
+            .is_err());
+        assert!("f32 24,20 5,3 2,2,1,1 1,1 mirror direct:10"
+            .parse::<Planner>()
+            .is_err());
+        assert!("f32 24,20 5,3 2,2,1,1 1,1 zeros winograd:10"
+            .parse::<Planner>()
+            .is_err());
+        assert!("f32 24,20 5,3 2,2,1,1 1,1 zeros"
+            .parse::<Planner>()
+            .is_err());
+    }
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((9, 11), |(i, j)| ((i * 11 + j) % 5) as f64 - 2.);
+        let separable = array![[1., 0., -1.], [2., 0., -2.], [1., 0., -1.]];
+        let dense = array![[1., 2., 0.], [0., 1., 3.], [-1., 0., 1.]];
+
+        for algorithm in [
+            Algorithm::Direct,
+            Algorithm::Separable,
+            Algorithm::Fft,
+            Algorithm::Im2col,
+        ] {
+            let planner: Planner = format!(
+                "f64 9,11 3,3 1,1,1,1 1,1 reflect {}:1 direct:2 fft:3 separable:4",
+                algorithm
+            )
+            .parse()
+            .unwrap();
+            assert_eq!(
+                planner.algorithm::<f64, 2>([9, 11], [3, 3], ConvMode::Same, PaddingMode::Reflect),
+                Some(algorithm)
+            );
+
+            for kernel in [&separable, &dense] {
+                assert_close(
+                    &planner
+                        .conv(&arr, kernel, ConvMode::Same, PaddingMode::Reflect)
+                        .unwrap(),
+                    &arr.conv(kernel, ConvMode::Same, PaddingMode::Reflect)
+                        .unwrap(),
+                    1e-9,
+                );
+            }
+        }
+
+        assert!(factorize(&separable).is_some());
+        assert!(factorize(&dense).is_none());
+    }
+
+    #[test]
+    fn deterministic() {
+        let arr = Array2::from_shape_fn((9, 11), |(i, j)| ((i * 11 + j) % 7) as f64 * 0.1 - 0.3);
+        let kernel = array![[0.1, 0.7, 0.3], [0.2, 0.9, 0.6]];
+        let planner: Planner = "f64 9,11 2,3 0,1,1,1 1,1 zeros fft:1 direct:2"
+            .parse()
+            .unwrap();
+
+        // the setting is passed in, the global one is left to the other tests
+        let ret = planner.run(&arr, &kernel, ConvMode::Same, PaddingMode::Zeros, true);
+
+        assert_eq!(
+            ret.unwrap(),
+            arr.conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::{FromPrimitive, NumAssign};
+
+use crate::{
+    dilation::{dilated, IntoDilation},
+    window::Windows,
+    ConvMode, PaddingMode,
+};
+
+pub trait PoolExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// maximum of every window, with the same window geometry as `conv`.
+    /// use `ConvMode::Custom { strides: window_shape, .. }` for non overlapping windows.
+    fn max_pool(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// maximum of every window and the flat index of the maximum in the input (row major),
+    /// as torch's `return_indices`. the padding is left out of the windows, so every window
+    /// has to overlap the input.
+    #[allow(clippy::type_complexity)]
+    fn max_pool_with_indices(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+    ) -> Result<(Array<T, Dim<[Ix; N]>>, Array<usize, Dim<[Ix; N]>>), crate::Error<N>>;
+
+    /// mean of every window, padded elements included.
+    fn avg_pool(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> PoolExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + PartialOrd + FromPrimitive + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn max_pool(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        pool(
+            self,
+            window_shape,
+            dilation.into_dilation(),
+            conv_mode,
+            padding_mode,
+            |offsets, cur| {
+                offsets[1..]
+                    .iter()
+                    .fold(unsafe { *cur.offset(offsets[0]) }, |acc, &offset| {
+                        let v = unsafe { *cur.offset(offset) };
+                        if v > acc {
+                            v
+                        } else {
+                            acc
+                        }
+                    })
+            },
+        )
+    }
+
+    fn max_pool_with_indices(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+    ) -> Result<(Array<T, Dim<[Ix; N]>>, Array<usize, Dim<[Ix; N]>>), crate::Error<N>> {
+        let dilation = dilation.into_dilation();
+        let window_dim = dilated(window_shape, dilation)?;
+        let input_dim: [usize; N] = std::array::from_fn(|i| self.shape()[i]);
+        let cm = conv_mode.unfold_with_dim(window_dim, input_dim)?;
+
+        let output_shape: [usize; N] = std::array::from_fn(|i| {
+            (input_dim[i] + cm.padding[i][0] + cm.padding[i][1] - window_dim[i]) / cm.strides[i] + 1
+        });
+        // row major strides of the flat index
+        let flat: [usize; N] = std::array::from_fn(|i| input_dim[i + 1..].iter().product());
+
+        let mut values = Vec::with_capacity(output_shape.iter().product());
+        let mut indices = Vec::with_capacity(values.capacity());
+
+        for window in ndarray::indices(output_shape) {
+            let window = window.into_dimension();
+
+            let max = ndarray::indices(window_shape)
+                .into_iter()
+                .filter_map(|tap| {
+                    let tap = tap.into_dimension();
+                    let index: [usize; N] = std::array::from_fn(|i| {
+                        (window[i] * cm.strides[i] + tap[i] * dilation[i])
+                            .wrapping_sub(cm.padding[i][0])
+                    });
+                    (0..N)
+                        .all(|i| index[i] < input_dim[i])
+                        .then(|| (self[index.into_dimension()], index))
+                })
+                .reduce(|acc, cur| if cur.0 > acc.0 { cur } else { acc });
+
+            let Some((value, index)) = max else {
+                return Err(crate::Error::InvalidParameter(format!(
+                    "window {:?} lies in the padding",
+                    window
+                )));
+            };
+            values.push(value);
+            indices.push((0..N).map(|i| index[i] * flat[i]).sum());
+        }
+
+        Ok((
+            Array::from_shape_vec(output_shape, values).unwrap(),
+            Array::from_shape_vec(output_shape, indices).unwrap(),
+        ))
+    }
+
+    fn avg_pool(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let count = T::from_usize(window_shape.iter().product()).unwrap();
+
+        pool(
+            self,
+            window_shape,
+            dilation.into_dilation(),
+            conv_mode,
+            padding_mode,
+            |offsets, cur| {
+                offsets.iter().fold(T::zero(), |acc, &offset| {
+                    acc + unsafe { *cur.offset(offset) }
+                }) / count
+            },
+        )
+    }
+}
+
+fn pool<T, S, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    window_shape: [usize; N],
+    dilation: [usize; N],
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+    reduce: impl Fn(&[isize], *const T) -> T,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    if input.shape().iter().product::<usize>() == 0 {
+        return Err(crate::Error::DataShape(input.raw_dim()));
+    }
+    if window_shape.iter().product::<usize>() == 0 {
+        return Err(crate::Error::KernelShape(window_shape.into_dimension()));
+    }
+
+    let window_dim = dilated(window_shape, dilation)?;
+
+    let cm = conv_mode.unfold_with_dim(window_dim, std::array::from_fn(|i| input.shape()[i]))?;
+    let windows = Windows::new(input, window_dim, &cm, conv_mode.padding_mode(padding_mode))
+        .ok_or(crate::Error::MismatchShape(conv_mode, window_dim))?;
+
+    let offset_list = windows.offsets(window_shape, dilation);
+
+    Ok(windows
+        .origins()
+        .map(|cur| reduce(&offset_list, cur as *const T)))
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+
+    #[test]
+    fn max_avg_pool() {
+        let arr = Array2::from_shape_fn((4, 5), |(i, j)| (i * 5 + j) as f32);
+        let conv_mode = ConvMode::Custom {
+            padding: [0, 0],
+            strides: [2, 2],
+        };
+
+        assert_eq!(
+            arr.max_pool([2, 2], 1, conv_mode, PaddingMode::Zeros)
+                .unwrap(),
+            array![[6., 8.], [16., 18.]]
+        );
+        assert_eq!(
+            arr.avg_pool([2, 2], 1, conv_mode, PaddingMode::Zeros)
+                .unwrap(),
+            array![[3., 5.], [13., 15.]]
+        );
+
+        // dilated window, padded with replicate
+        assert_eq!(
+            arr.max_pool([2, 2], 2, ConvMode::Same, PaddingMode::Replicate)
+                .unwrap()
+                .shape(),
+            &[4, 5]
+        );
+        assert_eq!(
+            array![1, 5, 2, 4, 3]
+                .max_pool([2], 2, ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![2, 5, 3]
+        );
+    }
+
+    #[test]
+    fn max_pool_with_indices() {
+        let arr = array![[3, 9, 1, 4], [7, 2, 8, 6], [5, 0, 12, 11]];
+        let conv_mode = ConvMode::Custom {
+            padding: [0, 0],
+            strides: [2, 2],
+        };
+
+        let (values, indices) = arr.max_pool_with_indices([2, 2], 1, conv_mode).unwrap();
+        assert_eq!(values, array![[9, 8]]);
+        assert_eq!(indices, array![[1, 6]]);
+
+        // same values as max_pool where the padding can't win
+        let arr = Array2::from_shape_fn((5, 6), |(i, j)| ((i * 7 + j * 3) % 11) as f32 + 1.);
+        for (dilation, conv_mode) in [
+            ([1, 1], ConvMode::Same),
+            ([2, 1], ConvMode::Full),
+            (
+                [1, 2],
+                ConvMode::Custom {
+                    padding: [1, 1],
+                    strides: [2, 3],
+                },
+            ),
+        ] {
+            let (values, indices) = arr
+                .max_pool_with_indices([3, 2], dilation, conv_mode)
+                .unwrap();
+            assert_eq!(
+                values,
+                arr.max_pool([3, 2], dilation, conv_mode, PaddingMode::Zeros)
+                    .unwrap()
+            );
+            let flat = arr.iter().copied().collect::<Vec<_>>();
+            assert!(values
+                .iter()
+                .zip(indices.iter())
+                .all(|(&v, &i)| flat[i] == v));
+        }
+
+        // a window left entirely in the padding
+        assert!(arr
+            .max_pool_with_indices(
+                [2, 2],
+                1,
+                ConvMode::Custom {
+                    padding: [3, 0],
+                    strides: [1, 1],
+                }
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, Array1, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::{Float, NumAssign};
+
+use crate::{padding::PaddingExt, separable::SeparableConvExt, ConvMode, PaddingMode};
+
+pub trait PyramidExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// gaussian blur with the 5-tap binomial kernel, then keep every second sample.
+    /// each axis of length `n` becomes `ceil(n / 2)`.
+    fn pyr_down(
+        &self,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// insert zeros between samples, then blur with the 5-tap binomial kernel
+    /// (a transposed conv with stride 2). each axis of length `n` becomes `2 * n`.
+    fn pyr_up(
+        &self,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> PyramidExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Float + Debug + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn pyr_down(
+        &self,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kernel = binomial::<T>(T::from(16).unwrap());
+
+        self.conv_separable(
+            std::array::from_fn(|_| kernel.view()),
+            ConvMode::Custom {
+                padding: [2; N],
+                strides: [2; N],
+            },
+            padding_mode,
+        )
+    }
+
+    fn pyr_up(
+        &self,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+
+        // the borders come from the input: pad it by one sample, enough for the kernel
+        // over the zero-stuffed samples, then keep the valid part of the blur
+        let padded: Array<T, Dim<[Ix; N]>> = self.padding(padding_mode, [[1; 2]; N]);
+        let raw_dim = padded.raw_dim();
+        let mut upsampled = Array::zeros(std::array::from_fn(|i| raw_dim[i] * 2));
+        upsampled
+            .slice_mut(unsafe {
+                SliceInfo::new(std::array::from_fn(|_| SliceInfoElem::Slice {
+                    start: 0,
+                    end: None,
+                    step: 2,
+                }))
+                .unwrap()
+            })
+            .assign(&padded);
+
+        // every output sample only sees half of the taps, so the gain is doubled
+        let kernel = binomial::<T>(T::from(8).unwrap());
+
+        upsampled.conv_separable(
+            std::array::from_fn(|_| kernel.view()),
+            ConvMode::Valid,
+            PaddingMode::Zeros,
+        )
+    }
+}
+
+fn binomial<T: Float>(norm: T) -> Array1<T> {
+    [1, 4, 6, 4, 1]
+        .into_iter()
+        .map(|v| T::from(v).unwrap() / norm)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array3};
+
+    use super::*;
+
+    #[test]
+    fn pyr_down_up() {
+        let arr = Array3::<f64>::from_elem((5, 8, 9), 3.);
+
+        let down = arr.pyr_down(PaddingMode::Reflect).unwrap();
+        assert_eq!(down.shape(), &[3, 4, 5]);
+        assert!(down.iter().all(|v| (v - 3.).abs() < 1e-12));
+
+        let up = down.pyr_up(PaddingMode::Reflect).unwrap();
+        assert_eq!(up.shape(), &[6, 8, 10]);
+        assert!(up.iter().all(|v| (v - 3.).abs() < 1e-12));
+
+        // a constant stays constant up to the borders whatever the padding
+        let arr = ndarray::Array2::<f64>::from_elem((4, 4), 3.);
+        for padding_mode in [
+            PaddingMode::Reflect,
+            PaddingMode::Symmetric,
+            PaddingMode::Replicate,
+            PaddingMode::Circular,
+            PaddingMode::Const(3.),
+        ] {
+            let up = arr.pyr_up(padding_mode).unwrap();
+            assert_eq!(up.shape(), &[8, 8]);
+            assert!(
+                up.iter().all(|v| (v - 3.).abs() < 1e-12),
+                "{padding_mode:?}: {up}"
+            );
+        }
+
+        // zeros outside: the last row only gets the middle tap of the last input row
+        let up = arr.pyr_up(PaddingMode::Zeros).unwrap();
+        assert!(up
+            .slice(ndarray::s![7, 1..6])
+            .iter()
+            .all(|v| (v - 1.5).abs() < 1e-12));
+
+        let mut arr = ndarray::Array2::<f32>::zeros((5, 5));
+        arr[[2, 2]] = 256.;
+        let down = arr.pyr_down(PaddingMode::Zeros).unwrap();
+        assert_eq!(down, array![[1., 6., 1.], [6., 36., 6.], [1., 6., 1.]]);
+    }
+}
@@ -0,0 +1,311 @@
+//! python bindings of `conv` and `conv_fft`, built with maturin (see pyproject.toml), which
+//! builds the crate as a cdylib itself.
+//! numpy arrays of f32 or f64 with 1 to 3 dimensions are read in place and the output
+//! buffer is handed to numpy without a copy.
+//!
+//! ```python
+//! import numpy as np
+//! import ndarray_conv
+//!
+//! ndarray_conv.conv(np.ones((5, 5)), np.ones((3, 3)), mode="same", padding="reflect")
+//! ndarray_conv.conv_fft(np.ones(8, np.float32), np.ones(3, np.float32), padding="const", value=1.0)
+//! ```
+
+use numpy::{Element, PyArray1, PyArrayMethods, PyReadonlyArrayDyn, PyUntypedArrayMethods};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use ndarray::{
+    ArrayD, ArrayViewD, Axis, Dim, IntoDimension, Ix, IxDyn, RemoveAxis, ShapeBuilder, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+use rustfft::FftNum;
+
+use crate::{ConvExt, ConvFFTExt, ConvMode, PaddingMode};
+
+/// cross-correlation of `input` with `kernel`, like `ConvExt::conv`.
+///
+/// mode is "full", "same", "valid", "circular" or "causal" (along the last axis), padding is
+/// "zeros", "const", "reflect", "symmetric", "replicate" or "circular", value is the
+/// constant of "const".
+#[pyfunction]
+#[pyo3(signature = (input, kernel, mode = "same", padding = "zeros", value = 0.0))]
+fn conv<'py>(
+    py: Python<'py>,
+    input: &Bound<'py, PyAny>,
+    kernel: &Bound<'py, PyAny>,
+    mode: &str,
+    padding: &str,
+    value: f64,
+) -> PyResult<Bound<'py, PyAny>> {
+    dispatch(py, input, kernel, mode, padding, value, false)
+}
+
+/// `conv` computed with FFT, like `ConvFFTExt::conv_fft`.
+#[pyfunction]
+#[pyo3(signature = (input, kernel, mode = "same", padding = "zeros", value = 0.0))]
+fn conv_fft<'py>(
+    py: Python<'py>,
+    input: &Bound<'py, PyAny>,
+    kernel: &Bound<'py, PyAny>,
+    mode: &str,
+    padding: &str,
+    value: f64,
+) -> PyResult<Bound<'py, PyAny>> {
+    dispatch(py, input, kernel, mode, padding, value, true)
+}
+
+#[pymodule]
+fn ndarray_conv(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(conv, m)?)?;
+    m.add_function(wrap_pyfunction!(conv_fft, m)?)?;
+    m.add(
+        "PADDING_MODES",
+        [
+            "zeros",
+            "const",
+            "reflect",
+            "symmetric",
+            "replicate",
+            "circular",
+        ],
+    )?;
+    Ok(())
+}
+
+fn dispatch<'py>(
+    py: Python<'py>,
+    input: &Bound<'py, PyAny>,
+    kernel: &Bound<'py, PyAny>,
+    mode: &str,
+    padding: &str,
+    value: f64,
+    fft: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    if let Ok(input) = input.extract::<PyReadonlyArrayDyn<'py, f32>>() {
+        let kernel = kernel.extract::<PyReadonlyArrayDyn<'py, f32>>()?;
+        let (input, kernel) = (view(&input)?, view(&kernel)?);
+        let ret = py.detach(|| run(input, kernel, mode, padding, value, fft))?;
+        return into_pyarray(py, ret);
+    }
+
+    let input = input.extract::<PyReadonlyArrayDyn<'py, f64>>()?;
+    let kernel = kernel.extract::<PyReadonlyArrayDyn<'py, f64>>()?;
+    let (input, kernel) = (view(&input)?, view(&kernel)?);
+    let ret = py.detach(|| run(input, kernel, mode, padding, value, fft))?;
+    into_pyarray(py, ret)
+}
+
+// numpy may be built on another ndarray version, so the view is made from the raw parts.
+// negative strides start from the lowest address and the axis is inverted back
+fn view<'a, T: Element>(array: &'a PyReadonlyArrayDyn<'_, T>) -> PyResult<ArrayViewD<'a, T>> {
+    let shape = array.shape();
+    let mut ptr = array.data() as *const T;
+    let mut inverted = Vec::new();
+    let mut strides = Vec::with_capacity(shape.len());
+
+    for (i, &stride) in array.strides().iter().enumerate() {
+        if stride % std::mem::size_of::<T>() as isize != 0 {
+            return Err(PyValueError::new_err(
+                "strides must be a multiple of the element size",
+            ));
+        }
+        let stride = stride / std::mem::size_of::<T>() as isize;
+        if stride < 0 {
+            ptr = ptr.wrapping_offset(stride * shape[i].saturating_sub(1) as isize);
+            inverted.push(i);
+        }
+        strides.push(stride.unsigned_abs());
+    }
+
+    let mut view =
+        unsafe { ArrayViewD::from_shape_ptr(IxDyn(shape).strides(IxDyn(&strides)), ptr) };
+    inverted.into_iter().for_each(|i| view.invert_axis(Axis(i)));
+    Ok(view)
+}
+
+// hands the buffer of a standard layout output to numpy without a copy
+fn into_pyarray<T: Element + Copy>(py: Python<'_>, ret: ArrayD<T>) -> PyResult<Bound<'_, PyAny>> {
+    let shape = ret.shape().to_vec();
+
+    let vec = if ret.is_standard_layout() {
+        crate::buffer::into_memory_order(ret)
+    } else {
+        ret.iter().copied().collect()
+    };
+
+    Ok(PyArray1::from_vec(py, vec).reshape(shape)?.into_any())
+}
+
+// picks the dimension of the arrays, the GIL is released meanwhile
+fn run<T>(
+    input: ArrayViewD<T>,
+    kernel: ArrayViewD<T>,
+    mode: &str,
+    padding: &str,
+    value: f64,
+    fft: bool,
+) -> PyResult<ArrayD<T>>
+where
+    T: FftNum + NumAssign,
+{
+    if input.ndim() != kernel.ndim() {
+        return Err(PyValueError::new_err(format!(
+            "input of {} dimensions and kernel of {} dimensions",
+            input.ndim(),
+            kernel.ndim()
+        )));
+    }
+
+    match input.ndim() {
+        1 => run_n::<T, 1>(input, kernel, mode, padding, value, fft),
+        2 => run_n::<T, 2>(input, kernel, mode, padding, value, fft),
+        3 => run_n::<T, 3>(input, kernel, mode, padding, value, fft),
+        n => Err(PyValueError::new_err(format!(
+            "arrays of {} dimensions, 1 to 3 are supported",
+            n
+        ))),
+    }
+}
+
+fn run_n<T, const N: usize>(
+    input: ArrayViewD<T>,
+    kernel: ArrayViewD<T>,
+    mode: &str,
+    padding: &str,
+    value: f64,
+    fft: bool,
+) -> PyResult<ArrayD<T>>
+where
+    T: FftNum + NumAssign,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let input = input.into_dimensionality::<Dim<[Ix; N]>>().unwrap();
+    let kernel = kernel.into_dimensionality::<Dim<[Ix; N]>>().unwrap();
+    let conv_mode = conv_mode(mode)?;
+    let padding_mode = padding_mode(padding, value)?;
+
+    let ret = if fft {
+        input.conv_fft(&kernel, conv_mode, padding_mode)
+    } else {
+        input.conv(&kernel, conv_mode, padding_mode)
+    };
+
+    ret.map(|ret| ret.into_dyn())
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+fn conv_mode<const N: usize>(mode: &str) -> PyResult<ConvMode<N>> {
+    Ok(match mode {
+        "full" => ConvMode::Full,
+        "same" => ConvMode::Same,
+        "valid" => ConvMode::Valid,
+        "circular" => ConvMode::Circular,
+        // the time axis last, as in (batch, channels, time)
+        "causal" => ConvMode::Causal { axis: N - 1 },
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "unknown mode {:?}, expected full, same, valid, circular or causal",
+                mode
+            )))
+        }
+    })
+}
+
+fn padding_mode<T: FftNum + NumAssign, const N: usize>(
+    padding: &str,
+    value: f64,
+) -> PyResult<PaddingMode<N, T>> {
+    Ok(match padding {
+        "zeros" => PaddingMode::Zeros,
+        "const" => PaddingMode::Const(T::from_f64(value).unwrap()),
+        "reflect" => PaddingMode::Reflect,
+        "symmetric" => PaddingMode::Symmetric,
+        "replicate" => PaddingMode::Replicate,
+        "circular" => PaddingMode::Circular,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "unknown padding {:?}, expected one of {:?}",
+                padding,
+                [
+                    "zeros",
+                    "const",
+                    "reflect",
+                    "symmetric",
+                    "replicate",
+                    "circular"
+                ]
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn parse_and_run() {
+        let arr = Array::from_shape_fn((4, 5), |(i, j)| (i * 5 + j) as f64);
+        let kernel = array![[1., 0.], [-1., 2.]];
+
+        for (padding, padding_mode) in [
+            ("zeros", PaddingMode::Zeros),
+            ("const", PaddingMode::Const(1.5)),
+            ("reflect", PaddingMode::Reflect),
+            ("circular", PaddingMode::Circular),
+        ] {
+            let ret = run(
+                arr.view().into_dyn(),
+                kernel.view().into_dyn(),
+                "full",
+                padding,
+                1.5,
+                false,
+            )
+            .unwrap();
+            assert_eq!(
+                ret,
+                arr.conv(&kernel, ConvMode::Full, padding_mode)
+                    .unwrap()
+                    .into_dyn()
+            );
+        }
+
+        let ret = run(
+            arr.view().into_dyn(),
+            kernel.view().into_dyn(),
+            "same",
+            "replicate",
+            0.,
+            true,
+        )
+        .unwrap();
+        assert_close(
+            &ret,
+            &arr.conv(&kernel, ConvMode::Same, PaddingMode::Replicate)
+                .unwrap()
+                .into_dyn(),
+            1e-9,
+        );
+
+        let arr = arr.into_dyn();
+        assert!(run(arr.view(), arr.view(), "wide", "zeros", 0., false).is_err());
+        assert!(run(arr.view(), arr.view(), "same", "mirror", 0., false).is_err());
+        assert!(run(
+            arr.view(),
+            array![1.].view().into_dyn(),
+            "same",
+            "zeros",
+            0.,
+            false
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use core::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, ArrayView, Data, Dim, Dimension, IntoDimension, Ix, RawData, RemoveAxis,
+    SliceArg, SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{
+    dilation::{dilated, IntoKernelWithDilation, KernelRef, KernelWithDilation},
+    window::Windows,
+    BorderType, ConvMode, PaddingMode,
+};
+
+/// signed fixed-point samples, the value over 2^FRAC: `i8` as Q7, `i16` as Q15 and `i32`
+/// as Q31 stored as their raw bits, and with the `fixed` feature the `I1F7`, `I1F15` and
+/// `I1F31` types of the fixed crate.
+///
+/// # Safety
+///
+/// `Self` must have the size, alignment and bit pattern of `Bits`, the samples are read
+/// as their bits in place.
+pub unsafe trait QFormat: Copy + Debug {
+    /// the integer the samples are stored as.
+    type Bits: QFormat<Bits = Self::Bits> + NumAssign;
+
+    const FRAC: u32;
+
+    fn to_bits(self) -> Self::Bits;
+
+    fn widen(self) -> i64;
+
+    /// the nearest value, saturated to the range of the format.
+    fn saturate(v: i64) -> Self;
+}
+
+macro_rules! q_format {
+    ($($t:ty: $frac:literal),*) => {
+        $(
+            unsafe impl QFormat for $t {
+                type Bits = $t;
+
+                const FRAC: u32 = $frac;
+
+                #[inline]
+                fn to_bits(self) -> $t {
+                    self
+                }
+
+                #[inline]
+                fn widen(self) -> i64 {
+                    self as i64
+                }
+
+                #[inline]
+                fn saturate(v: i64) -> $t {
+                    v.clamp(<$t>::MIN as i64, <$t>::MAX as i64) as $t
+                }
+            }
+        )*
+    };
+}
+
+q_format!(i8: 7, i16: 15, i32: 31);
+
+// the fixed types are repr(transparent) over their bits
+#[cfg(feature = "fixed")]
+macro_rules! q_fixed {
+    ($($t:ty: $bits:ty),*) => {
+        $(
+            unsafe impl QFormat for $t {
+                type Bits = $bits;
+
+                const FRAC: u32 = <$t>::FRAC_NBITS;
+
+                #[inline]
+                fn to_bits(self) -> $bits {
+                    <$t>::to_bits(self)
+                }
+
+                #[inline]
+                fn widen(self) -> i64 {
+                    <$t>::to_bits(self) as i64
+                }
+
+                #[inline]
+                fn saturate(v: i64) -> $t {
+                    <$t>::from_bits(<$bits>::saturate(v))
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "fixed")]
+q_fixed!(fixed::types::I1F7: i8, fixed::types::I1F15: i16, fixed::types::I1F31: i32);
+
+pub trait QConvExt<'a, T, S, SK, const N: usize>
+where
+    T: QFormat,
+    S: RawData,
+    SK: RawData,
+{
+    /// `conv` of fixed-point samples with a fixed-point kernel of the same format.
+    /// the products are summed in an i64, then shifted back by FRAC bits rounding half
+    /// up, and saturated. the i64 can't overflow for Q7 and Q15; Q31 products take 62 bits
+    /// and the sum wraps past 2^63, as in CMSIS-DSP, so scale the input down by
+    /// log2(taps) bits when needed.
+    fn conv_q(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<'a, T, S, SK, const N: usize> QConvExt<'a, T, S, SK, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: QFormat,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T> + 'a,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn conv_q(
+        &self,
+        kernel: impl IntoKernelWithDilation<'a, SK, N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let kwd = kernel.into_kernel_with_dilation();
+        let kwd = KernelWithDilation {
+            kernel: KernelRef::Owned(bits(&kwd.kernel)),
+            dilation: kwd.dilation,
+            origin: kwd.origin,
+            zero_taps: kwd.zero_taps,
+        };
+        let data = bits(self);
+
+        if data.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(data.raw_dim()));
+        }
+        if kwd.kernel.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(kwd.kernel.raw_dim()));
+        }
+
+        let kernel_dim = dilated(
+            core::array::from_fn(|i| kwd.kernel.shape()[i]),
+            kwd.dilation,
+        )?;
+
+        let cm = conv_mode.unfold(&kwd, core::array::from_fn(|i| data.shape()[i]))?;
+        let padding_mode = conv_mode.padding_mode(bits_padding(padding_mode));
+        let windows = Windows::new(&data, kernel_dim, &cm, padding_mode)
+            .ok_or(crate::Error::MismatchShape(conv_mode, kernel_dim))?;
+
+        let offset_list = kwd.gen_offset_list(windows.padded_strides());
+        let half = 1i64 << (T::FRAC - 1);
+
+        Ok(windows.origins().map(|cur| {
+            let cur = cur as *const T::Bits;
+            let sum = offset_list.iter().fold(0i64, |acc, &(offset, k)| {
+                acc.wrapping_add(unsafe { *cur.offset(offset) }.widen() * k.widen())
+            });
+            T::saturate(sum.wrapping_add(half) >> T::FRAC)
+        }))
+    }
+}
+
+// the samples as their raw bits, read in place
+fn bits<T, S, D>(array: &ArrayBase<S, D>) -> ArrayView<'_, T::Bits, D>
+where
+    T: QFormat,
+    S: Data<Elem = T>,
+    D: Dimension,
+{
+    // SAFETY: QFormat guarantees T has the layout of its bits, the view borrows array
+    unsafe { array.raw_view().cast::<T::Bits>().deref_into_view() }
+}
+
+fn bits_padding<T: QFormat, const N: usize>(
+    padding_mode: PaddingMode<N, T>,
+) -> PaddingMode<N, T::Bits> {
+    let border = |border: BorderType<T>| match border {
+        BorderType::Zeros => BorderType::Zeros,
+        BorderType::Const(c) => BorderType::Const(c.to_bits()),
+        BorderType::Reflect => BorderType::Reflect,
+        BorderType::Symmetric => BorderType::Symmetric,
+        BorderType::Replicate => BorderType::Replicate,
+        BorderType::Circular => BorderType::Circular,
+    };
+
+    match padding_mode {
+        PaddingMode::Zeros => PaddingMode::Zeros,
+        PaddingMode::Const(c) => PaddingMode::Const(c.to_bits()),
+        PaddingMode::Reflect => PaddingMode::Reflect,
+        PaddingMode::Symmetric => PaddingMode::Symmetric,
+        PaddingMode::Replicate => PaddingMode::Replicate,
+        PaddingMode::Circular => PaddingMode::Circular,
+        PaddingMode::Custom(borders) => PaddingMode::Custom(borders.map(border)),
+        PaddingMode::Explicit(borders) => {
+            PaddingMode::Explicit(borders.map(|[before, after]| [border(before), border(after)]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::{ConvExt, WithDilation};
+
+    #[test]
+    fn same_as_float() {
+        let arr = Array2::from_shape_fn((6, 7), |(i, j)| ((i * 7 + j) * 3779 % 65536) as i16);
+        let kernel = array![[8192i16, -16384], [4096, 2048]];
+        let q15 = |v: i16| v as f64 / 32768.;
+
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Same, PaddingMode::Reflect),
+            (ConvMode::Full, PaddingMode::Const(-32768)),
+            (
+                ConvMode::Custom {
+                    padding: [1, 2],
+                    strides: [2, 1],
+                },
+                PaddingMode::Replicate,
+            ),
+        ] {
+            let float_padding = match padding_mode {
+                PaddingMode::Const(c) => PaddingMode::Const(q15(c)),
+                PaddingMode::Reflect => PaddingMode::Reflect,
+                _ => PaddingMode::Replicate,
+            };
+            let expected = arr
+                .mapv(q15)
+                .conv(
+                    kernel.mapv(q15).with_dilation([1, 2]),
+                    conv_mode,
+                    float_padding,
+                )
+                .unwrap()
+                .mapv(|v| (v * 32768. + 0.5).floor().clamp(-32768., 32767.) as i16);
+
+            assert_eq!(
+                arr.conv_q(kernel.with_dilation([1, 2]), conv_mode, padding_mode)
+                    .unwrap(),
+                expected
+            );
+        }
+
+        // rounds half up and saturates
+        assert_eq!(
+            array![1i16, 2, 32767]
+                .conv_q(&[16384i16, 16384], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![2, 16385]
+        );
+        assert_eq!(
+            array![i32::MIN]
+                .conv_q(&[i32::MIN], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![i32::MAX]
+        );
+        assert_eq!(
+            array![100i8, 100]
+                .conv_q(&[127i8, 127], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![127]
+        );
+    }
+
+    #[cfg(feature = "fixed")]
+    #[test]
+    fn fixed_types() {
+        use fixed::types::{I1F15, I1F31, I1F7};
+
+        let arr = Array2::from_shape_fn((5, 6), |(i, j)| {
+            I1F15::from_num((i * 6 + j) as f64 / 32. - 0.45)
+        });
+        let kernel = array![[I1F15::from_num(0.25), I1F15::from_num(-0.5)]];
+        let bits = |a: &Array2<I1F15>| a.mapv(I1F15::to_bits);
+
+        // the same bits as the raw i16 samples, even through a strided view
+        let ret = arr
+            .slice(ndarray::s![..;2, ..])
+            .conv_q(
+                &kernel,
+                ConvMode::Same,
+                PaddingMode::Const(I1F15::from_num(0.5)),
+            )
+            .unwrap();
+        let expected = bits(&arr)
+            .slice(ndarray::s![..;2, ..])
+            .conv_q(
+                &bits(&kernel),
+                ConvMode::Same,
+                PaddingMode::Const(I1F15::from_num(0.5).to_bits()),
+            )
+            .unwrap();
+        assert_eq!(ret.mapv(I1F15::to_bits), expected);
+        // the constant before the first sample, then the first sample
+        assert!((ret[[0, 0]].to_num::<f64>() - (0.5 * 0.25 + 0.45 * 0.5)).abs() < 1e-4);
+
+        // saturates to the range of the format
+        assert_eq!(
+            array![I1F7::MIN]
+                .conv_q(&[I1F7::MIN], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![I1F7::MAX]
+        );
+        assert_eq!(
+            array![I1F31::from_num(0.5), I1F31::from_num(-0.25)]
+                .conv_q(&[I1F31::from_num(0.5)], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![I1F31::from_num(0.25), I1F31::from_num(-0.125)]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::{cmp::Ordering, fmt::Debug};
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{window::Windows, ConvMode, PaddingMode};
+
+// window size from which u8 switches to the sliding histogram
+const HISTOGRAM_THRESHOLD: usize = 32;
+
+pub trait RankFilterExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// replace every element by the `rank`-th smallest value of the window centered on it
+    /// (`rank` in `0..window_len`). the output has the input's shape.
+    fn rank_filter(
+        &self,
+        window_shape: [usize; N],
+        rank: usize,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// `rank_filter` with the middle rank, for even sized windows the lower median.
+    fn median_filter(
+        &self,
+        window_shape: [usize; N],
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> RankFilterExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: RankElement + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn rank_filter(
+        &self,
+        window_shape: [usize; N],
+        rank: usize,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+
+        let window_len = window_shape.iter().product::<usize>();
+        if window_len == 0 {
+            return Err(crate::Error::KernelShape(window_shape.into_dimension()));
+        }
+        if rank >= window_len {
+            return Err(crate::Error::InvalidParameter(format!(
+                "rank ({rank}) must be less than the window size ({window_len})"
+            )));
+        }
+
+        let conv_mode = ConvMode::Same;
+        let cm =
+            conv_mode.unfold_with_dim(window_shape, std::array::from_fn(|i| self.shape()[i]))?;
+        let windows = Windows::new(self, window_shape, &cm, padding_mode)
+            .ok_or(crate::Error::MismatchShape(conv_mode, window_shape))?;
+
+        Ok(T::rank_filter_windows(&windows, window_shape, rank))
+    }
+
+    fn median_filter(
+        &self,
+        window_shape: [usize; N],
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let window_len = window_shape.iter().product::<usize>();
+        self.rank_filter(window_shape, window_len.saturating_sub(1) / 2, padding_mode)
+    }
+}
+
+/// Element types supported by the rank filters.
+pub trait RankElement: NumAssign + Copy + PartialOrd {
+    #[doc(hidden)]
+    fn rank_filter_windows<const N: usize>(
+        windows: &Windows<Self, N>,
+        window_shape: [usize; N],
+        rank: usize,
+    ) -> Array<Self, Dim<[Ix; N]>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    {
+        select(windows, window_shape, rank)
+    }
+}
+
+macro_rules! impl_rank_element {
+    ($($t:ty),*) => {
+        $(impl RankElement for $t {})*
+    };
+}
+
+impl_rank_element!(i8, i16, i32, i64, isize, u16, u32, u64, usize, f32, f64);
+
+impl RankElement for u8 {
+    fn rank_filter_windows<const N: usize>(
+        windows: &Windows<Self, N>,
+        window_shape: [usize; N],
+        rank: usize,
+    ) -> Array<Self, Dim<[Ix; N]>>
+    where
+        Dim<[Ix; N]>: RemoveAxis,
+        [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    {
+        if window_shape.iter().product::<usize>() < HISTOGRAM_THRESHOLD {
+            select(windows, window_shape, rank)
+        } else {
+            histogram(windows, window_shape, rank)
+        }
+    }
+}
+
+// NaN sorts after every other value, to keep the order total
+fn total_cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| {
+        #[allow(clippy::eq_op)]
+        match (a != a, b != b) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            _ => Ordering::Less,
+        }
+    })
+}
+
+fn select<T, const N: usize>(
+    windows: &Windows<T, N>,
+    window_shape: [usize; N],
+    rank: usize,
+) -> Array<T, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + PartialOrd,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let offsets = windows.offsets(window_shape, [1; N]);
+    let mut buffer = Vec::with_capacity(offsets.len());
+
+    windows.origins().map(|cur| {
+        buffer.clear();
+        buffer.extend(
+            offsets
+                .iter()
+                .map(|&offset| unsafe { *(cur as *const T).offset(offset) }),
+        );
+
+        *buffer.select_nth_unstable_by(rank, total_cmp).1
+    })
+}
+
+// slide a 256 bin histogram along the last axis:
+// one slice of the window leaves and one enters for every step.
+fn histogram<const N: usize>(
+    windows: &Windows<u8, N>,
+    window_shape: [usize; N],
+    rank: usize,
+) -> Array<u8, Dim<[Ix; N]>>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let offsets = windows.offsets(window_shape, [1; N]);
+    let last_len = window_shape[N - 1];
+    let last_stride = windows.padded_strides()[N - 1];
+
+    // the first slice along the last axis, the other slices are shifted by last_stride
+    let slice = offsets
+        .iter()
+        .copied()
+        .step_by(last_len)
+        .collect::<Vec<_>>();
+    let entering = last_len as isize * last_stride;
+
+    let mut output = Array::zeros(windows.output_shape());
+    for (origins, mut output) in windows.origins().rows().into_iter().zip(output.rows_mut()) {
+        let mut hist = [0usize; 256];
+        let mut origins = origins.iter();
+
+        let first = origins.next().unwrap() as *const u8;
+        offsets
+            .iter()
+            .for_each(|&offset| hist[unsafe { *first.offset(offset) } as usize] += 1);
+        output[0] = rank_of(&hist, rank);
+
+        for (i, cur) in origins.enumerate() {
+            let cur = cur as *const u8;
+            slice.iter().for_each(|&offset| unsafe {
+                hist[*cur.offset(offset - last_stride) as usize] -= 1;
+                hist[*cur.offset(offset - last_stride + entering) as usize] += 1;
+            });
+            output[i + 1] = rank_of(&hist, rank);
+        }
+    }
+
+    output
+}
+
+fn rank_of(hist: &[usize; 256], rank: usize) -> u8 {
+    let mut count = 0;
+    for (value, &n) in hist.iter().enumerate() {
+        count += n;
+        if count > rank {
+            return value as u8;
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+
+    #[test]
+    fn median_filter() {
+        let arr = array![[1, 9, 3], [4, 0, 6], [7, 8, 2]];
+        assert_eq!(
+            arr.median_filter([3, 3], PaddingMode::Replicate).unwrap(),
+            array![[1, 3, 3], [4, 4, 3], [7, 6, 2]]
+        );
+
+        let arr = array![1., f64::NAN, 3., 2.];
+        assert_eq!(
+            arr.rank_filter([3], 0, PaddingMode::Zeros).unwrap(),
+            array![0., 1., 2., 0.]
+        );
+        assert!(arr.rank_filter([3], 3, PaddingMode::Zeros).is_err());
+    }
+
+    #[test]
+    fn histogram_same_as_select() {
+        let arr = Array2::from_shape_fn((23, 31), |(i, j)| ((i * 131 + j * 71) % 256) as u8);
+
+        let cm = ConvMode::Same.unfold_with_dim([7, 9], [23, 31]).unwrap();
+        let windows = Windows::new(&arr, [7, 9], &cm, PaddingMode::Reflect).unwrap();
+
+        for rank in [0, 31, 62] {
+            assert_eq!(
+                histogram(&windows, [7, 9], rank),
+                select(&windows, [7, 9], rank)
+            );
+        }
+        assert_eq!(
+            arr.median_filter([7, 9], PaddingMode::Reflect).unwrap(),
+            select(&windows, [7, 9], 31)
+        );
+    }
+}
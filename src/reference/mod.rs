@@ -0,0 +1,192 @@
+//! a deliberately naive convolution to test against, one loop over the outputs
+//! and one over the kernel with every padded element looked up by hand, and the
+//! comparison of float outputs the tests share.
+
+use std::fmt::Debug;
+
+use ndarray::{Array, ArrayBase, Data, Dim, Dimension, IntoDimension, Ix};
+use num::traits::{Float, NumAssign};
+
+use crate::{BorderType, ConvMode, PaddingMode};
+
+/// the same output as `ConvExt::conv`, for paddings up to the input size.
+pub fn conv<T, S, SK, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    kernel: &ArrayBase<SK, Dim<[Ix; N]>>,
+    dilation: [usize; N],
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    let input_shape: [usize; N] = std::array::from_fn(|i| input.shape()[i]);
+    let kernel_shape: [usize; N] = std::array::from_fn(|i| kernel.shape()[i]);
+    if input_shape.contains(&0) {
+        return Err(crate::Error::DataShape(input.raw_dim()));
+    }
+    if kernel_shape.contains(&0) {
+        return Err(crate::Error::KernelShape(kernel.raw_dim()));
+    }
+
+    if let Some(axis) = dilation.iter().position(|&d| d == 0) {
+        return Err(crate::Error::ZeroDilation(axis));
+    }
+
+    let kernel_dim = std::array::from_fn(|i| (kernel_shape[i] - 1) * dilation[i] + 1);
+    let cm = conv_mode.unfold_with_dim(kernel_dim, input_shape)?;
+
+    let mut output_shape = [0; N];
+    for i in 0..N {
+        let padded = input_shape[i] + cm.padding[i][0] + cm.padding[i][1];
+        if padded < kernel_dim[i] || cm.padding[i].iter().any(|&p| p > input_shape[i]) {
+            return Err(crate::Error::MismatchShape(conv_mode, kernel_dim));
+        }
+        output_shape[i] = (padded - kernel_dim[i]) / cm.strides[i] + 1;
+    }
+
+    let borders: [[BorderType<T>; 2]; N] = match conv_mode.padding_mode(padding_mode) {
+        PaddingMode::Zeros => [[BorderType::Zeros; 2]; N],
+        PaddingMode::Const(c) => [[BorderType::Const(c); 2]; N],
+        PaddingMode::Reflect => [[BorderType::Reflect; 2]; N],
+        PaddingMode::Symmetric => [[BorderType::Symmetric; 2]; N],
+        PaddingMode::Replicate => [[BorderType::Replicate; 2]; N],
+        PaddingMode::Circular => [[BorderType::Circular; 2]; N],
+        PaddingMode::Custom(borders) => borders.map(|border| [border; 2]),
+        PaddingMode::Explicit(borders) => borders,
+    };
+
+    let mut output = Array::zeros(output_shape);
+    for (out_index, out) in output.indexed_iter_mut() {
+        let out_index = out_index.into_dimension();
+        for (k_index, &k) in kernel.indexed_iter() {
+            let k_index = k_index.into_dimension();
+
+            let mut index = [0; N];
+            let mut constant = None;
+            for i in 0..N {
+                // position in the unpadded input, negative before it
+                let x = (out_index[i] * cm.strides[i] + k_index[i] * dilation[i]) as isize
+                    - cm.padding[i][0] as isize;
+                match source(x, input_shape[i] as isize, borders[i]) {
+                    Ok(j) => index[i] = j,
+                    // the axes are padded in order, the last constant one wins
+                    Err(c) => constant = Some(c),
+                }
+            }
+
+            let v = match constant {
+                Some(c) => c,
+                None => input[index.into_dimension()],
+            };
+            *out += v * k;
+        }
+    }
+
+    Ok(output)
+}
+
+// the input index of position x on an axis of length n, or the constant of its border
+fn source<T: Copy + NumAssign>(
+    x: isize,
+    n: isize,
+    borders: [BorderType<T>; 2],
+) -> Result<usize, T> {
+    if (0..n).contains(&x) {
+        return Ok(x as usize);
+    }
+
+    let before = x < 0;
+    let border = if before { borders[0] } else { borders[1] };
+    let j = match border {
+        BorderType::Zeros => return Err(T::zero()),
+        BorderType::Const(c) => return Err(c),
+        // a b c d | d d d
+        BorderType::Replicate => x.clamp(0, n - 1),
+        // a b c d | c b a b c, folded again past the other end
+        BorderType::Reflect if n == 1 => 0,
+        BorderType::Reflect => {
+            let x = x.rem_euclid(2 * (n - 1));
+            x.min(2 * (n - 1) - x)
+        }
+        // a b c d | d c b a a
+        BorderType::Symmetric => {
+            let x = x.rem_euclid(2 * n);
+            x.min(2 * n - 1 - x)
+        }
+        // a b c d | a b c
+        BorderType::Circular => x.rem_euclid(n),
+    };
+
+    Ok(j.clamp(0, n - 1) as usize)
+}
+
+/// panics unless `a` and `b` have the same shape and every element of `a` is within
+/// `tolerance` of the one of `b` at its index.
+#[track_caller]
+pub fn assert_close<T, S, S2, D>(a: &ArrayBase<S, D>, b: &ArrayBase<S2, D>, tolerance: T)
+where
+    T: Float + Debug,
+    S: Data<Elem = T>,
+    S2: Data<Elem = T>,
+    D: Dimension,
+{
+    assert_eq!(a.shape(), b.shape());
+    for ((index, &x), &y) in a.indexed_iter().zip(b.iter()) {
+        assert!(
+            (x - y).abs() <= tolerance,
+            "{:?}: {:?} != {:?}",
+            index,
+            x,
+            y
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array3};
+
+    use super::*;
+    use crate::{dilation::WithDilation, ConvExt};
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array3::from_shape_fn((4, 5, 6), |(i, j, k)| ((i * 30 + j * 6 + k) % 13) as i64);
+        let kernel = array![[[1, 0], [2, -1]], [[0, 3], [-2, 1]]];
+
+        for conv_mode in [
+            ConvMode::Same,
+            ConvMode::Full,
+            ConvMode::Valid,
+            ConvMode::Explicit {
+                padding: [[1, 0], [2, 3], [0, 1]],
+                strides: [1, 2, 3],
+            },
+        ] {
+            for padding_mode in [
+                PaddingMode::Zeros,
+                PaddingMode::Const(-3),
+                PaddingMode::Reflect,
+                PaddingMode::Symmetric,
+                PaddingMode::Replicate,
+                PaddingMode::Circular,
+                PaddingMode::Custom([
+                    BorderType::Reflect,
+                    BorderType::Const(2),
+                    BorderType::Replicate,
+                ]),
+            ] {
+                assert_eq!(
+                    conv(&arr, &kernel, [1, 2, 1], conv_mode, padding_mode).unwrap(),
+                    arr.conv(kernel.with_dilation([1, 2, 1]), conv_mode, padding_mode)
+                        .unwrap()
+                );
+            }
+        }
+    }
+}
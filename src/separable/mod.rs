@@ -0,0 +1,114 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, ArrayView1, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{padding::PaddingExt, ConvExt, ConvMode, PaddingMode};
+
+pub trait SeparableConvExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// conv with a kernel given as the outer product of one 1D kernel per axis.
+    /// the input is padded once, then convolved axis by axis. each pass only evaluates
+    /// the strided outputs of its axis, so the later passes run on the decimated array.
+    fn conv_separable(
+        &self,
+        kernels: [ArrayView1<T>; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> SeparableConvExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn conv_separable(
+        &self,
+        kernels: [ArrayView1<T>; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+
+        let kernel_dim: [usize; N] = std::array::from_fn(|i| kernels[i].len());
+        if kernel_dim.contains(&0) {
+            return Err(crate::Error::KernelShape(kernel_dim.into_dimension()));
+        }
+
+        let cm = conv_mode.unfold_with_dim(kernel_dim, std::array::from_fn(|i| self.shape()[i]))?;
+        let mut output = self.padding(conv_mode.padding_mode(padding_mode), cm.padding);
+
+        for (axis, kernel) in kernels.iter().enumerate() {
+            let mut shape = [1; N];
+            shape[axis] = kernel.len();
+            let kernel = Array::from_shape_vec(shape, kernel.to_vec()).unwrap();
+
+            let mut strides = [1; N];
+            strides[axis] = cm.strides[axis];
+
+            output = output
+                .conv(
+                    &kernel,
+                    ConvMode::Custom {
+                        padding: [0; N],
+                        strides,
+                    },
+                    PaddingMode::Zeros,
+                )
+                .map_err(|_| crate::Error::MismatchShape(conv_mode, kernel_dim))?;
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::BorderType;
+
+    #[test]
+    fn same_as_conv() {
+        let arr = array![
+            [1, 2, 3, 4, 5],
+            [6, 7, 8, 9, 10],
+            [11, 12, 13, 14, 15],
+            [16, 17, 18, 19, 20]
+        ];
+        let k0 = array![1, 2, 1];
+        let k1 = array![1, 0, -1, 2];
+        let kernel = array![[1, 0, -1, 2], [2, 0, -2, 4], [1, 0, -1, 2]];
+
+        for conv_mode in [
+            ConvMode::Full,
+            ConvMode::Same,
+            ConvMode::Valid,
+            ConvMode::Custom {
+                padding: [2, 1],
+                strides: [2, 3],
+            },
+        ] {
+            let padding_mode = PaddingMode::Custom([BorderType::Reflect, BorderType::Circular]);
+            assert_eq!(
+                arr.conv_separable([k0.view(), k1.view()], conv_mode, padding_mode)
+                    .unwrap(),
+                arr.conv(&kernel, conv_mode, padding_mode).unwrap()
+            );
+        }
+    }
+}
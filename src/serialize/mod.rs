@@ -0,0 +1,164 @@
+//! serde support: `ConvMode`, `PaddingMode`, `BorderType` and `ConvPlan` derive it,
+//! `KernelWithDilation` is written as its shape, weights in row major order, dilation and origin.
+
+use alloc::{format, vec::Vec};
+
+use ndarray::{Array, Data, Dim, Dimension, IntoDimension, Ix, OwnedRepr};
+use serde::{
+    de::Error as _, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::dilation::{KernelRef, KernelWithDilation};
+
+// serde implements arrays of a fixed length only, const generic arrays go through a sequence
+pub(crate) mod array {
+    use super::*;
+
+    pub fn serialize<T: Serialize, S: Serializer, const N: usize>(
+        arr: &[T; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(arr)
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[T; N], D::Error> {
+        let vec = Vec::<T>::deserialize(deserializer)?;
+        let len = vec.len();
+        vec.try_into().map_err(|_| {
+            D::Error::invalid_length(len, &format!("an array of length {}", N).as_str())
+        })
+    }
+}
+
+impl<'a, T, S, const N: usize> Serialize for KernelWithDilation<'a, S, N>
+where
+    T: Serialize,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: Dimension,
+{
+    fn serialize<SE: Serializer>(&self, serializer: SE) -> Result<SE::Ok, SE::Error> {
+        let mut state = serializer.serialize_struct("KernelWithDilation", 5)?;
+        state.serialize_field("shape", self.kernel.shape())?;
+        state.serialize_field("kernel", &self.kernel.iter().collect::<Vec<_>>())?;
+        state.serialize_field("dilation", &self.dilation[..])?;
+        state.serialize_field("origin", &self.origin[..])?;
+        state.serialize_field("zero_taps", &self.zero_taps)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "KernelWithDilation")]
+struct Descriptor<T, const N: usize> {
+    #[serde(deserialize_with = "array::deserialize")]
+    shape: [usize; N],
+    kernel: Vec<T>,
+    #[serde(deserialize_with = "array::deserialize")]
+    dilation: [usize; N],
+    #[serde(deserialize_with = "array::deserialize", default = "no_origin")]
+    origin: [isize; N],
+    #[serde(default)]
+    zero_taps: bool,
+}
+
+fn no_origin<const N: usize>() -> [isize; N] {
+    [0; N]
+}
+
+/// a kernel read from a config owns its weights
+impl<'de, T, const N: usize> Deserialize<'de> for KernelWithDilation<'static, OwnedRepr<T>, N>
+where
+    T: Deserialize<'de>,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let descriptor = Descriptor::<T, N>::deserialize(deserializer)?;
+        let kernel =
+            Array::from_shape_vec(descriptor.shape, descriptor.kernel).map_err(D::Error::custom)?;
+
+        Ok(KernelWithDilation {
+            kernel: KernelRef::Owned(kernel),
+            dilation: descriptor.dilation,
+            origin: descriptor.origin,
+            zero_taps: descriptor.zero_taps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::{
+        dilation::WithDilation, BorderType, ConvExt, ConvMode, ConvPlan, PaddingMode, SamePolicy,
+    };
+
+    #[test]
+    fn round_trip() {
+        for conv_mode in [
+            ConvMode::Same,
+            ConvMode::SameAs(SamePolicy::TensorFlow),
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [2, 1],
+            },
+            ConvMode::Explicit {
+                padding: [[0, 1], [2, 3]],
+                strides: [1, 3],
+            },
+        ] {
+            let json = serde_json::to_string(&conv_mode).unwrap();
+            let back: ConvMode<2> = serde_json::from_str(&json).unwrap();
+            assert_eq!(format!("{:?}", back), format!("{:?}", conv_mode));
+        }
+
+        let padding_mode = PaddingMode::Explicit([
+            [BorderType::Const(1.5), BorderType::Reflect],
+            [BorderType::Circular, BorderType::Zeros],
+        ]);
+        let json = serde_json::to_string(&padding_mode).unwrap();
+        assert_eq!(
+            json,
+            r#"{"Explicit":[[{"Const":1.5},"Reflect"],["Circular","Zeros"]]}"#
+        );
+        let back: PaddingMode<2, f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", back), format!("{:?}", padding_mode));
+
+        let plan: ConvPlan<2> = serde_json::from_str(
+            r#"{"input_shape":[5,6],"kernel_shape":[3,3],"dilation":[1,2],"conv_mode":"Same"}"#,
+        )
+        .unwrap();
+        assert_eq!(plan.output_shape().unwrap(), [5, 6]);
+        assert!(serde_json::from_str::<ConvPlan<2>>(
+            r#"{"input_shape":[5,6,7],"kernel_shape":[3,3],"dilation":[1,2],"conv_mode":"Same"}"#,
+        )
+        .is_err());
+
+        let kernel = array![[1, 2, 3], [4, 5, 6]];
+        let kwd = kernel.with_dilation([1, 2]).with_origin([0, -1]);
+        let json = serde_json::to_string(&kwd).unwrap();
+        let back: KernelWithDilation<OwnedRepr<i32>, 2> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*back.kernel, kernel);
+        assert_eq!((back.dilation, back.origin), ([1, 2], [0, -1]));
+
+        let arr = array![[1, 0, 2, 3], [0, 4, 1, 1], [2, 2, 0, 1]];
+        assert_eq!(
+            arr.conv(back, ConvMode::Same, PaddingMode::Zeros).unwrap(),
+            arr.conv(kwd, ConvMode::Same, PaddingMode::Zeros).unwrap()
+        );
+
+        let back: KernelWithDilation<OwnedRepr<i32>, 2> =
+            serde_json::from_str(r#"{"shape":[1,2],"kernel":[1,2],"dilation":[1,1]}"#).unwrap();
+        assert_eq!((back.origin, back.zero_taps), ([0, 0], false));
+        assert!(
+            serde_json::from_str::<KernelWithDilation<OwnedRepr<i32>, 2>>(
+                r#"{"shape":[2,2],"kernel":[1,2],"dilation":[1,1]}"#
+            )
+            .is_err()
+        );
+    }
+}
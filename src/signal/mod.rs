@@ -0,0 +1,518 @@
+//! scipy.signal / numpy style free functions. These are true convolutions
+//! (the kernel is flipped), and the output sizes and "same" centering follow
+//! scipy: "full" is `n + m - 1`, "same" is the shape of `in1` centered on the
+//! full output, and "valid" is `max(n, m) - min(n, m) + 1`.
+
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, Array1, Array2, ArrayBase, ArrayView, ArrayView1, Axis, Data, Dim, IntoDimension, Ix,
+    Ix1, Ix2, RemoveAxis, SliceArg, SliceInfo, SliceInfoElem,
+};
+use num::{integer::gcd, traits::NumAssign, Float};
+use rustfft::FftNum;
+
+use crate::{
+    fir::{lowpass, Window},
+    ConvExt, ConvFFTExt, ConvMode, Error, ExplicitPadding, PaddingMode,
+};
+
+/// `scipy.signal.convolve(in1, in2, mode)` with `method="direct"`.
+pub fn convolve<T, S, SK, const N: usize>(
+    in1: &ArrayBase<S, Dim<[Ix; N]>>,
+    in2: &ArrayBase<SK, Dim<[Ix; N]>>,
+    mode: &str,
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let (data, kernel, padding) = unfold(in1.view(), in2.view(), mode)?;
+    data.conv(
+        &kernel,
+        ConvMode::Explicit {
+            padding,
+            strides: [1; N],
+        },
+        PaddingMode::Zeros,
+    )
+}
+
+/// `scipy.signal.convolve2d(in1, in2, mode, boundary, fillvalue)`,
+/// `boundary` is one of "fill", "wrap" and "symm".
+pub fn convolve2d<T, S, SK>(
+    in1: &ArrayBase<S, Ix2>,
+    in2: &ArrayBase<SK, Ix2>,
+    mode: &str,
+    boundary: &str,
+    fillvalue: T,
+) -> Result<Array2<T>, Error<2>>
+where
+    T: NumAssign + Copy + Debug + 'static,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+{
+    let padding_mode = match boundary {
+        "fill" => PaddingMode::Const(fillvalue),
+        "wrap" => PaddingMode::Circular,
+        "symm" => PaddingMode::Symmetric,
+        _ => {
+            return Err(Error::InvalidParameter(format!(
+                "unknown boundary {boundary:?}"
+            )))
+        }
+    };
+
+    let (data, kernel, padding) = unfold(in1.view(), in2.view(), mode)?;
+    data.conv(
+        &kernel,
+        ConvMode::Explicit {
+            padding,
+            strides: [1; 2],
+        },
+        padding_mode,
+    )
+}
+
+/// `scipy.signal.fftconvolve(in1, in2, mode)`.
+pub fn fftconvolve<T, S, SK, const N: usize>(
+    in1: &ArrayBase<S, Dim<[Ix; N]>>,
+    in2: &ArrayBase<SK, Dim<[Ix; N]>>,
+    mode: &str,
+) -> Result<Array<T, Dim<[Ix; N]>>, Error<N>>
+where
+    T: NumAssign + Debug + FftNum,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let (data, kernel, padding) = unfold(in1.view(), in2.view(), mode)?;
+    data.conv_fft(
+        &kernel,
+        ConvMode::Explicit {
+            padding,
+            strides: [1; N],
+        },
+        PaddingMode::Zeros,
+    )
+}
+
+/// `scipy.signal.upfirdn(h, x, up, down)`: upsample by zero stuffing, FIR filter with `h`
+/// and downsample, computed polyphase so only the taps hitting input samples of the kept
+/// outputs are multiplied.
+pub fn upfirdn<T, S, SK>(
+    h: &ArrayBase<SK, Ix1>,
+    x: &ArrayBase<S, Ix1>,
+    up: usize,
+    down: usize,
+) -> Result<Array1<T>, Error<1>>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+    SK: Data<Elem = T>,
+{
+    if up == 0 || down == 0 {
+        return Err(Error::InvalidParameter(format!(
+            "up and down must be greater than 0, got {up} and {down}"
+        )));
+    }
+    if x.is_empty() {
+        return Err(Error::DataShape(x.raw_dim()));
+    }
+    if h.is_empty() {
+        return Err(Error::KernelShape(h.raw_dim()));
+    }
+
+    // ceil(((len(x) - 1) * up + len(h)) / down), the full output decimated
+    let len = ((x.len() - 1) * up + h.len() - 1) / down + 1;
+
+    Ok(Array1::from_shape_fn(len, |k| {
+        let n = k * down;
+        // the taps of the phase of n, tap `n % up + t * up` reads x[n / up - t]
+        h.iter()
+            .skip(n % up)
+            .step_by(up)
+            .zip((0..=n / up).rev())
+            .filter(|&(_, i)| i < x.len())
+            .fold(T::zero(), |acc, (&h, i)| acc + h * x[i])
+    }))
+}
+
+/// `scipy.signal.resample_poly(x, up, down)`: resampling by `up / down` with a
+/// Kaiser windowed lowpass (beta 5, 10 zero crossings per side) run through `upfirdn`,
+/// the output has `ceil(len * up / down)` samples.
+pub fn resample_poly<T, S>(
+    x: &ArrayBase<S, Ix1>,
+    up: usize,
+    down: usize,
+) -> Result<Array1<T>, Error<1>>
+where
+    T: NumAssign + Float,
+    S: Data<Elem = T>,
+{
+    if up == 0 || down == 0 {
+        return Err(Error::InvalidParameter(format!(
+            "up and down must be greater than 0, got {up} and {down}"
+        )));
+    }
+    if x.is_empty() {
+        return Err(Error::DataShape(x.raw_dim()));
+    }
+
+    let g = gcd(up, down);
+    let (up, down) = (up / g, down / g);
+    if up == 1 && down == 1 {
+        return Ok(x.to_owned());
+    }
+
+    let max_rate = up.max(down);
+    let half_len = 10 * max_rate;
+    let filter = lowpass::<T>(1. / max_rate as f64, 2 * half_len + 1, Window::Kaiser(5.))?;
+
+    // zeros before the filter so its delay is a whole number of output samples
+    let n_out = (x.len() * up).div_ceil(down);
+    let n_pre_pad = down - half_len % down;
+    let n_pre_remove = (half_len + n_pre_pad) / down;
+    let output_len = |h_len: usize| ((x.len() - 1) * up + h_len - 1) / down + 1;
+    let mut n_post_pad = 0;
+    while output_len(filter.len() + n_pre_pad + n_post_pad) < n_out + n_pre_remove {
+        n_post_pad += 1;
+    }
+
+    let gain = T::from(up).unwrap();
+    let h = std::iter::repeat_n(T::zero(), n_pre_pad)
+        .chain(filter.iter().map(|&v| v * gain))
+        .chain(std::iter::repeat_n(T::zero(), n_post_pad))
+        .collect::<Array1<T>>();
+
+    let y = upfirdn(&h, x, up, down)?;
+    Ok(y.slice_move(ndarray::s![n_pre_remove..n_pre_remove + n_out]))
+}
+
+// products with a shorter factor than this are cheaper direct than through an FFT
+const POLY_MUL_DIRECT: usize = 64;
+
+/// coefficients `poly_mul` can multiply. floats may always take the FFT, integers only
+/// when rounding the FFT product back is exact.
+pub trait PolyCoefficient: NumAssign + Copy + Debug {
+    /// errors when a coefficient of the product may overflow.
+    fn check(a: ArrayView1<Self>, b: ArrayView1<Self>) -> Result<(), Error<1>>;
+
+    /// the full product through an FFT, `None` when it can't be exact.
+    fn fft_mul(a: ArrayView1<Self>, b: ArrayView1<Self>) -> Option<Array1<Self>>;
+}
+
+macro_rules! poly_float {
+    ($($t:ty),*) => {
+        $(
+            impl PolyCoefficient for $t {
+                fn check(_: ArrayView1<$t>, _: ArrayView1<$t>) -> Result<(), Error<1>> {
+                    Ok(())
+                }
+
+                fn fft_mul(a: ArrayView1<$t>, b: ArrayView1<$t>) -> Option<Array1<$t>> {
+                    fftconvolve(&a, &b, "full").ok()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! poly_int {
+    ($($t:ty),*) => {
+        $(
+            impl PolyCoefficient for $t {
+                fn check(a: ArrayView1<$t>, b: ArrayView1<$t>) -> Result<(), Error<1>> {
+                    let max = |x: ArrayView1<$t>| {
+                        x.iter().map(|&v| (v as f64).abs()).fold(0., f64::max)
+                    };
+                    // the largest coefficient the product can reach
+                    let bound = max(a) * max(b) * a.len().min(b.len()) as f64;
+                    if bound > <$t>::MAX as f64 {
+                        return Err(Error::InvalidParameter(format!(
+                            "coefficients of the product may overflow {}",
+                            stringify!($t)
+                        )));
+                    }
+                    Ok(())
+                }
+
+                fn fft_mul(a: ArrayView1<$t>, b: ArrayView1<$t>) -> Option<Array1<$t>> {
+                    let (a, b) = (a.mapv(|v| v as f64), b.mapv(|v| v as f64));
+
+                    // the f64 FFT error grows like |a| |b| eps log(n), it must stay well
+                    // under the 0.5 that rounding absorbs. this also rejects coefficients
+                    // f64 can't hold exactly
+                    let norm = |x: &Array1<f64>| x.dot(x).sqrt();
+                    let n = (a.len() + b.len() - 1) as f64;
+                    if norm(&a) * norm(&b) * f64::EPSILON * 4. * (n.log2() + 1.) >= 0.125 {
+                        return None;
+                    }
+
+                    fftconvolve(&a, &b, "full")
+                        .ok()
+                        .map(|p| p.mapv(|v| v.round() as $t))
+                }
+            }
+        )*
+    };
+}
+
+poly_float!(f32, f64);
+poly_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// `numpy.polymul(a, b)`: the product of two polynomials, i.e. the full convolution of
+/// their coefficients (in either order, the same for both). long products go through an
+/// FFT, integer ones only when the rounding is guaranteed exact and direct otherwise.
+/// errors when an integer coefficient of the product may overflow.
+pub fn poly_mul<T, S, SB>(
+    a: &ArrayBase<S, Ix1>,
+    b: &ArrayBase<SB, Ix1>,
+) -> Result<Array1<T>, Error<1>>
+where
+    T: PolyCoefficient + 'static,
+    S: Data<Elem = T>,
+    SB: Data<Elem = T>,
+{
+    if a.is_empty() {
+        return Err(Error::DataShape(a.raw_dim()));
+    }
+    if b.is_empty() {
+        return Err(Error::KernelShape(b.raw_dim()));
+    }
+    T::check(a.view(), b.view())?;
+
+    if a.len().min(b.len()) > POLY_MUL_DIRECT {
+        if let Some(product) = T::fft_mul(a.view(), b.view()) {
+            return Ok(product);
+        }
+    }
+    convolve(a, b, "full")
+}
+
+// picks the data and the flipped kernel, and the padding of `mode`
+#[allow(clippy::type_complexity)]
+fn unfold<'a, T, const N: usize>(
+    in1: ArrayView<'a, T, Dim<[Ix; N]>>,
+    in2: ArrayView<'a, T, Dim<[Ix; N]>>,
+    mode: &str,
+) -> Result<
+    (
+        ArrayView<'a, T, Dim<[Ix; N]>>,
+        ArrayView<'a, T, Dim<[Ix; N]>>,
+        ExplicitPadding<N>,
+    ),
+    Error<N>,
+>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+{
+    let (data, mut kernel, padding) = match mode {
+        "full" => {
+            let padding = std::array::from_fn(|i| [in2.shape()[i].max(1) - 1; 2]);
+            (in1, in2, padding)
+        }
+        "same" => {
+            // the full output sliced from (m - 1) / 2
+            let padding = std::array::from_fn(|i| {
+                let m = in2.shape()[i].max(1);
+                [m / 2, (m - 1) / 2]
+            });
+            (in1, in2, padding)
+        }
+        "valid" => {
+            let (data, kernel) = if (0..N).all(|i| in1.shape()[i] >= in2.shape()[i]) {
+                (in1, in2)
+            } else if (0..N).all(|i| in2.shape()[i] >= in1.shape()[i]) {
+                // convolution commutes, valid uses the larger one as the data
+                (in2, in1)
+            } else {
+                return Err(Error::InvalidParameter(
+                    "for 'valid' mode, one input must be at least as large as the other in every dimension".to_string(),
+                ));
+            };
+            (data, kernel, [[0; 2]; N])
+        }
+        _ => return Err(Error::InvalidParameter(format!("unknown mode {mode:?}"))),
+    };
+
+    (0..N).for_each(|i| kernel.invert_axis(Axis(i)));
+
+    Ok((data, kernel, padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array};
+
+    use super::*;
+    use crate::reference::assert_close;
+
+    #[test]
+    fn aligned_with_numpy() {
+        // the numpy.convolve doc example
+        let a = array![1., 2., 3.];
+        let v = array![0., 1., 0.5];
+
+        assert_eq!(
+            convolve(&a, &v, "full").unwrap(),
+            array![0., 1., 2.5, 4., 1.5]
+        );
+        assert_eq!(convolve(&a, &v, "same").unwrap(), array![1., 2.5, 4.]);
+        assert_eq!(convolve(&a, &v, "valid").unwrap(), array![2.5]);
+        assert_eq!(convolve(&v, &a, "valid").unwrap(), array![2.5]);
+
+        // even kernel, the full output is [1, 3, 5, 3]
+        assert_eq!(
+            convolve(&a, &array![1., 1.], "same").unwrap(),
+            array![1., 3., 5.]
+        );
+
+        let a = array![[1, 2], [3, 4]];
+        let k = Array::ones((2, 2));
+        assert_eq!(
+            convolve(&a, &k, "full").unwrap(),
+            array![[1, 3, 2], [4, 10, 6], [3, 7, 4]]
+        );
+        assert_eq!(
+            convolve2d(&a, &k, "same", "fill", 0).unwrap(),
+            array![[1, 3], [4, 10]]
+        );
+        assert_eq!(
+            convolve2d(&a, &k, "same", "fill", 1).unwrap(),
+            array![[4, 5], [6, 10]]
+        );
+        assert_eq!(
+            convolve2d(&a, &k, "same", "symm", 0).unwrap(),
+            array![[4, 6], [8, 10]]
+        );
+        assert_eq!(
+            convolve2d(&a, &k, "same", "wrap", 0).unwrap(),
+            array![[10, 10], [10, 10]]
+        );
+
+        assert!(convolve(&array![[1, 2, 3]], &array![[1], [2]], "valid").is_err());
+        assert!(convolve(&a, &k, "circular").is_err());
+    }
+
+    #[test]
+    fn fft_same_as_direct() {
+        let a = Array::from_shape_fn((7, 9), |(i, j)| ((i * 5 + j * 3) % 7) as f64);
+        let k = Array::from_shape_fn((4, 3), |(i, j)| (i * 3 + j) as f64 - 4.);
+
+        for mode in ["full", "same", "valid"] {
+            assert_close(
+                &convolve(&a, &k, mode).unwrap(),
+                &fftconvolve(&a, &k, mode).unwrap(),
+                1e-9,
+            );
+        }
+    }
+
+    #[test]
+    fn upfirdn_aligned_with_scipy() {
+        // the scipy.signal.upfirdn doc examples
+        let ones = array![1., 1., 1.];
+        assert_eq!(
+            upfirdn(&ones, &ones, 1, 1).unwrap(),
+            array![1., 2., 3., 2., 1.]
+        );
+        assert_eq!(
+            upfirdn(&array![1.], &array![1., 2., 3.], 3, 1).unwrap(),
+            array![1., 0., 0., 2., 0., 0., 3.]
+        );
+        assert_eq!(
+            upfirdn(&ones, &array![1., 2., 3.], 3, 1).unwrap(),
+            array![1., 1., 1., 2., 2., 2., 3., 3., 3.]
+        );
+        assert_eq!(
+            upfirdn(&array![0.5, 1., 0.5], &ones, 2, 1).unwrap(),
+            array![0.5, 1., 1., 1., 1., 1., 0.5]
+        );
+        let x = Array::from_iter((0..10).map(|i| i as f64));
+        assert_eq!(
+            upfirdn(&array![1.], &x, 1, 3).unwrap(),
+            array![0., 3., 6., 9.]
+        );
+        assert_eq!(
+            upfirdn(&array![0.5, 1., 0.5], &x, 2, 3).unwrap(),
+            array![0., 1., 2.5, 4., 5.5, 7., 8.5]
+        );
+
+        // zero stuffing, full convolution and decimation
+        let h = array![1, -2, 3, 1, 4];
+        let x = array![2, 0, 1, -1, 3, 5];
+        for (up, down) in [(1, 2), (3, 2), (2, 5), (4, 1)] {
+            let mut stuffed = Array::zeros((x.len() - 1) * up + 1);
+            stuffed.slice_mut(ndarray::s![..;up]).assign(&x);
+            let full = convolve(&stuffed, &h, "full").unwrap();
+            let ret = upfirdn(&h, &x, up, down).unwrap();
+            ret.iter().enumerate().for_each(|(k, &v)| {
+                assert_eq!(v, full.get(k * down).copied().unwrap_or(0));
+            });
+        }
+
+        assert!(upfirdn(&h, &x, 0, 1).is_err());
+    }
+
+    #[test]
+    fn resample_poly_keeps_the_signal() {
+        let x = Array::from_iter((0..60).map(|i| (i as f64 * 0.1).sin()));
+
+        for (up, down) in [(3, 2), (2, 3), (1, 4), (5, 1)] {
+            let y = resample_poly(&x, up, down).unwrap();
+            assert_eq!(y.len(), (x.len() * up).div_ceil(down));
+
+            // a slow sine survives away from the edges, sampled at the new rate
+            let ratio = down as f64 / up as f64;
+            let edge = 12 * up.max(down) / down + 1;
+            y.iter()
+                .enumerate()
+                .skip(edge)
+                .take(y.len().saturating_sub(2 * edge))
+                .for_each(|(k, &v)| assert!((v - (k as f64 * ratio * 0.1).sin()).abs() < 1e-2));
+        }
+
+        assert_eq!(resample_poly(&x, 2, 2).unwrap(), x);
+    }
+
+    #[test]
+    fn poly_mul_is_exact() {
+        // (1 + 2x + 3x²)(4 + 5x)
+        assert_eq!(
+            poly_mul(&array![1, 2, 3], &array![4, 5]).unwrap(),
+            array![4, 13, 22, 15]
+        );
+
+        // long enough for the FFT, and still exact
+        let a = Array::from_iter((0..300).map(|i| (i * 7919 % 2001) as i64 - 1000));
+        let b = Array::from_iter((0..200).map(|i| (i * 104729 % 2001) as i64 - 1000));
+        let expected = convolve(&a, &b, "full").unwrap();
+        assert_eq!(poly_mul(&a, &b).unwrap(), expected);
+        assert_eq!(i64::fft_mul(a.view(), b.view()).unwrap(), expected);
+
+        // too large for an exact f64 FFT, computed directly
+        let a = a.mapv(|v| v * 1009);
+        let b = b.mapv(|v| v * 997);
+        assert!(i64::fft_mul(a.view(), b.view()).is_none());
+        assert_eq!(poly_mul(&a, &b).unwrap(), convolve(&a, &b, "full").unwrap());
+
+        let a = Array::from_iter((0..100).map(|i| (i as f64 * 0.3).sin()));
+        assert_close(
+            &poly_mul(&a, &a).unwrap(),
+            &convolve(&a, &a, "full").unwrap(),
+            1e-9,
+        );
+
+        assert!(poly_mul(&array![i32::MAX / 2, 1], &array![3, 1]).is_err());
+        assert!(poly_mul(&array![1u8, 1], &Array1::zeros(0)).is_err());
+    }
+}
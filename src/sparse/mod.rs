@@ -0,0 +1,121 @@
+use ndarray::{Array, Dim, Dimension, IntoDimension, Ix, OwnedRepr};
+use num::traits::NumAssign;
+
+use crate::dilation::{IntoDilation, IntoKernelWithDilation, KernelWithDilation, WithDilation};
+
+// a kernel given by its nonzero weights. it is stored densely, the zero taps
+// are pruned when the offset list is built so only the entries are visited.
+#[derive(Debug, Clone)]
+pub struct SparseKernel<T, const N: usize>
+where
+    Dim<[Ix; N]>: Dimension,
+{
+    kernel: Array<T, Dim<[Ix; N]>>,
+}
+
+impl<T, const N: usize> SparseKernel<T, N>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: Dimension,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    /// build a kernel of `shape` from (index, weight) pairs, repeated indices are summed.
+    pub fn from_coo(
+        shape: [usize; N],
+        entries: impl IntoIterator<Item = ([usize; N], T)>,
+    ) -> Result<Self, crate::Error<N>> {
+        if shape.iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(shape.into_dimension()));
+        }
+
+        let mut kernel = Array::zeros(shape);
+        for (index, weight) in entries {
+            if (0..N).any(|i| index[i] >= shape[i]) {
+                return Err(crate::Error::InvalidParameter(format!(
+                    "entry {:?} is outside the kernel of shape {:?}",
+                    index, shape
+                )));
+            }
+            kernel[index.into_dimension()] += weight;
+        }
+
+        Ok(Self { kernel })
+    }
+
+    pub fn dense(&self) -> &Array<T, Dim<[Ix; N]>> {
+        &self.kernel
+    }
+}
+
+impl<'a, T, const N: usize> IntoKernelWithDilation<'a, OwnedRepr<T>, N> for &'a SparseKernel<T, N>
+where
+    Dim<[Ix; N]>: Dimension,
+{
+    #[inline]
+    fn into_kernel_with_dilation(self) -> KernelWithDilation<'a, OwnedRepr<T>, N> {
+        self.kernel.with_dilation(1)
+    }
+}
+
+impl<T, const N: usize> WithDilation<OwnedRepr<T>, N> for SparseKernel<T, N>
+where
+    Dim<[Ix; N]>: Dimension,
+{
+    #[inline]
+    fn with_dilation(
+        &self,
+        dilation: impl IntoDilation<N>,
+    ) -> KernelWithDilation<'_, OwnedRepr<T>, N> {
+        self.kernel.with_dilation(dilation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::{reference::assert_close, ConvExt, ConvFFTExt, ConvMode, PaddingMode};
+
+    #[test]
+    fn from_coo() {
+        let kernel =
+            SparseKernel::from_coo([3, 4], [([0, 1], 2), ([2, 3], -1), ([0, 1], 3)]).unwrap();
+        assert_eq!(
+            kernel.dense(),
+            array![[0, 5, 0, 0], [0, 0, 0, 0], [0, 0, 0, -1]]
+        );
+
+        let arr = Array2::from_shape_fn((6, 7), |(i, j)| (i * 7 + j) as i32 % 5);
+        for conv_mode in [ConvMode::Same, ConvMode::Full, ConvMode::Valid] {
+            assert_eq!(
+                arr.conv(&kernel, conv_mode, PaddingMode::Reflect).unwrap(),
+                arr.conv(kernel.dense(), conv_mode, PaddingMode::Reflect)
+                    .unwrap()
+            );
+            assert_eq!(
+                arr.conv(kernel.with_dilation(2), conv_mode, PaddingMode::Zeros)
+                    .unwrap(),
+                arr.conv(
+                    kernel.dense().with_dilation(2),
+                    conv_mode,
+                    PaddingMode::Zeros
+                )
+                .unwrap()
+            );
+        }
+
+        let kernel = SparseKernel::from_coo([2, 2], [([1, 0], 0.5)]).unwrap();
+        let arr = arr.mapv(|v| v as f64);
+        assert_close(
+            &arr.conv_fft(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap(),
+            &arr.conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap(),
+            1e-9,
+        );
+
+        assert!(SparseKernel::from_coo([2, 2], [([2, 0], 1)]).is_err());
+        assert!(SparseKernel::<i32, 2>::from_coo([2, 0], []).is_err());
+    }
+}
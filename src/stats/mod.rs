@@ -0,0 +1,216 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, ArrayBase, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, SliceArg, SliceInfo,
+    SliceInfoElem,
+};
+use num::traits::{Float, FromPrimitive, NumAssign};
+
+use crate::{
+    integral::IntegralImageExt, padding::PaddingExt, window::WindowExt, ConvMode, PaddingMode,
+};
+
+pub trait LocalStatsExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// mean of every window, padded elements included, with the same window geometry as `conv`.
+    fn local_mean(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// population variance of every window, E[x²] - E[x]² from two summed-area tables.
+    /// the input is centered on its mean first, which keeps the cancellation small
+    /// unless the local spread is tiny next to the global one.
+    fn local_variance(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// `local_variance` summing the squared deviations from the mean of each window,
+    /// O(window) per output but without cancellation.
+    fn local_variance_two_pass(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+
+    /// square root of `local_variance`.
+    fn local_std(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> LocalStatsExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Float + FromPrimitive + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn local_mean(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let (shift, centered, conv_mode) = centered(self, window_shape, conv_mode, padding_mode)?;
+        let count = T::from_usize(window_shape.iter().product()).unwrap();
+
+        Ok(centered
+            .box_filter_sat(window_shape, conv_mode, PaddingMode::Zeros)?
+            .mapv_into(|sum| sum / count + shift))
+    }
+
+    fn local_variance(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let (_, centered, conv_mode) = centered(self, window_shape, conv_mode, padding_mode)?;
+        let count = T::from_usize(window_shape.iter().product()).unwrap();
+
+        let sum = centered.box_filter_sat(window_shape, conv_mode, PaddingMode::Zeros)?;
+        let sum_sq =
+            centered
+                .mapv(|v| v * v)
+                .box_filter_sat(window_shape, conv_mode, PaddingMode::Zeros)?;
+
+        // rounding can leave a flat window slightly negative
+        Ok(ndarray::Zip::from(&sum)
+            .and(&sum_sq)
+            .map_collect(|&s, &sq| {
+                let mean = s / count;
+                (sq / count - mean * mean).max(T::zero())
+            }))
+    }
+
+    fn local_variance_two_pass(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        let count = T::from_usize(window_shape.iter().product()).unwrap();
+
+        self.map_windows(window_shape, 1, conv_mode, padding_mode, |window| {
+            let mean = window.sum() / count;
+            window.fold(T::zero(), |acc, &v| acc + (v - mean) * (v - mean)) / count
+        })
+    }
+
+    fn local_std(
+        &self,
+        window_shape: [usize; N],
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>> {
+        Ok(self
+            .local_variance(window_shape, conv_mode, padding_mode)?
+            .mapv_into(T::sqrt))
+    }
+}
+
+// the padded input minus its mean, and the mode that runs on the padded input
+#[allow(clippy::type_complexity)]
+fn centered<T, S, const N: usize>(
+    input: &ArrayBase<S, Dim<[Ix; N]>>,
+    window_shape: [usize; N],
+    conv_mode: ConvMode<N>,
+    padding_mode: PaddingMode<N, T>,
+) -> Result<(T, Array<T, Dim<[Ix; N]>>, ConvMode<N>), crate::Error<N>>
+where
+    T: NumAssign + Float + FromPrimitive,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    if input.shape().iter().product::<usize>() == 0 {
+        return Err(crate::Error::DataShape(input.raw_dim()));
+    }
+
+    let cm = conv_mode.unfold_with_dim(window_shape, std::array::from_fn(|i| input.shape()[i]))?;
+    let shift = input.mean().unwrap();
+    let centered = input
+        .padding(conv_mode.padding_mode(padding_mode), cm.padding)
+        .mapv_into(|v| v - shift);
+
+    Ok((
+        shift,
+        centered,
+        ConvMode::Custom {
+            padding: [0; N],
+            strides: cm.strides,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::{reference::assert_close, BorderType, PoolExt};
+
+    #[test]
+    fn mean_and_variance() {
+        let arr = Array2::from_shape_fn((7, 9), |(i, j)| ((i * 7 + j * 3) % 11) as f64 - 2.5);
+
+        for (conv_mode, padding_mode) in [
+            (ConvMode::Same, PaddingMode::Reflect),
+            (ConvMode::Full, PaddingMode::Const(1.5)),
+            (
+                ConvMode::Custom {
+                    padding: [2, 1],
+                    strides: [2, 3],
+                },
+                PaddingMode::Custom([BorderType::Replicate, BorderType::Zeros]),
+            ),
+        ] {
+            let mean = arr.local_mean([3, 4], conv_mode, padding_mode).unwrap();
+            let expected = arr.avg_pool([3, 4], 1, conv_mode, padding_mode).unwrap();
+            assert_close(&mean, &expected, 1e-9);
+
+            let variance = arr.local_variance([3, 4], conv_mode, padding_mode).unwrap();
+            let expected = arr
+                .local_variance_two_pass([3, 4], conv_mode, padding_mode)
+                .unwrap();
+            assert_close(&variance, &expected, 1e-9);
+
+            let std = arr.local_std([3, 4], conv_mode, padding_mode).unwrap();
+            std.iter()
+                .zip(variance.iter())
+                .for_each(|(s, v)| assert!((s * s - v).abs() < 1e-9));
+        }
+
+        let arr = array![1., 3., 3., 1.];
+        assert_eq!(
+            arr.local_variance_two_pass([2], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![1., 0., 1.]
+        );
+
+        // a large offset leaves the variance of the sums exact
+        let arr = array![1e8 + 1., 1e8 + 3., 1e8 + 3., 1e8 + 1.];
+        assert_eq!(
+            arr.local_variance([2], ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![1., 0., 1.]
+        );
+    }
+}
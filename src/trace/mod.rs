@@ -0,0 +1,111 @@
+//! spans of the conv stages behind the `tracing` feature: "plan" (mode unfolding and shape
+//! validation), "padding" (the padded copy of the input), and "execute" (the loop or the
+//! ffts, with the chosen algorithm and the output shape). they are debug spans, without
+//! the feature the fields are not even evaluated.
+
+macro_rules! span {
+    ($name:literal $(, $($field:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $($field)*)?).entered();
+    };
+}
+
+pub(crate) use span;
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    };
+
+    use ndarray::array;
+    use tracing::{
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use crate::{ConvExt, ConvFFTExt, ConvMode, PaddingMode};
+
+    // the names and fields of the spans, in creation order
+    #[derive(Default)]
+    struct Spans {
+        spans: Arc<Mutex<Vec<String>>>,
+        next: AtomicU64,
+    }
+
+    impl Subscriber for Spans {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = String::new();
+            span.record(
+                &mut |field: &tracing::field::Field, value: &dyn std::fmt::Debug| {
+                    fields += &format!(" {}={:?}", field.name(), value)
+                },
+            );
+            self.spans
+                .lock()
+                .unwrap()
+                .push(format!("{}{}", span.metadata().name(), fields));
+            Id::from_u64(self.next.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn spans(f: impl FnOnce()) -> Vec<String> {
+        let subscriber = Spans::default();
+        let spans = subscriber.spans.clone();
+        tracing::subscriber::with_default(subscriber, f);
+        let spans = spans.lock().unwrap().clone();
+        spans
+    }
+
+    #[test]
+    fn stages() {
+        let arr = array![[1., 2., 3., 4.], [5., 6., 7., 8.], [9., 10., 11., 12.]];
+        let kernel = array![[1., 0.], [0., -1.]];
+
+        let names = |spans: Vec<String>| {
+            spans
+                .iter()
+                .map(|span| span.split(' ').next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let ret = spans(|| {
+            arr.conv(&kernel, ConvMode::Full, PaddingMode::Reflect)
+                .unwrap();
+        });
+        assert_eq!(names(ret.clone()), ["conv", "plan", "padding", "execute"]);
+        assert!(ret[0].contains("input=[3, 4] kernel=[2, 2]"));
+        // f64 runs on the vector unit when the cpu has one
+        let algorithm = if crate::conv::simd_enabled::<f64>() {
+            "simd"
+        } else {
+            "direct"
+        };
+        assert!(ret[3].contains(&format!("algorithm={algorithm:?} output=[4, 5]")));
+
+        let ret = spans(|| {
+            arr.conv_fft(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap();
+        });
+        assert_eq!(
+            names(ret.clone()),
+            ["conv_fft", "plan", "padding", "execute"]
+        );
+        assert!(ret[3].contains("algorithm=\"fft\""));
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt::Debug;
+
+use ndarray::{
+    Array, Array2, ArrayBase, ArrayView2, Data, Dim, IntoDimension, Ix, RemoveAxis, SliceArg,
+    SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{
+    dilation::{dilated, IntoDilation},
+    window::Windows,
+    ConvMode, PaddingMode,
+};
+
+pub trait UnfoldExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: Data<Elem = T>,
+{
+    /// im2col: every window of the padded input as a row, with the same window geometry
+    /// as `conv` with a kernel of shape `window_shape`. rows follow the output in row major
+    /// order (see `conv_output_shape`), columns the window elements in row major order,
+    /// so `conv` is the product of the patches with the flattened kernel.
+    fn unfold(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array2<T>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> UnfoldExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn unfold(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Result<Array2<T>, crate::Error<N>> {
+        let dilation = dilation.into_dilation();
+        let window_dim = dilated(window_shape, dilation)?;
+
+        let cm = conv_mode.unfold_with_dim(window_dim, std::array::from_fn(|i| self.shape()[i]))?;
+        let windows = Windows::new(self, window_dim, &cm, conv_mode.padding_mode(padding_mode))
+            .ok_or(crate::Error::MismatchShape(conv_mode, window_dim))?;
+
+        let offsets = windows.offsets(window_shape, dilation);
+        let origins = windows.origins();
+
+        let mut patches = Array2::zeros((origins.len(), offsets.len()));
+        patches
+            .rows_mut()
+            .into_iter()
+            .zip(origins.iter())
+            .for_each(|(mut row, cur)| {
+                row.iter_mut().zip(offsets.iter()).for_each(|(p, &offset)| {
+                    *p = unsafe { *(cur as *const T).offset(offset) };
+                })
+            });
+
+        Ok(patches)
+    }
+}
+
+/// col2im: the adjoint of `unfold`, every patch is added back at its window in an input
+/// of shape `output_shape`. taps falling into the padding are dropped.
+pub fn fold<T, const N: usize>(
+    patches: ArrayView2<T>,
+    output_shape: [usize; N],
+    window_shape: [usize; N],
+    dilation: impl IntoDilation<N>,
+    conv_mode: ConvMode<N>,
+) -> Result<Array<T, Dim<[Ix; N]>>, crate::Error<N>>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    let dilation = dilation.into_dilation();
+    let window_dim = dilated(window_shape, dilation)?;
+    let cm = conv_mode.unfold_with_dim(window_dim, output_shape)?;
+
+    let padded_shape: [usize; N] =
+        std::array::from_fn(|i| output_shape[i] + cm.padding[i][0] + cm.padding[i][1]);
+    let windows: [usize; N] =
+        std::array::from_fn(|i| (padded_shape[i] - window_dim[i]) / cm.strides[i] + 1);
+
+    let expected = (
+        windows.iter().product::<usize>(),
+        window_shape.iter().product::<usize>(),
+    );
+    if patches.dim() != expected {
+        return Err(crate::Error::InvalidParameter(format!(
+            "patches of shape {:?} for {} windows of {} elements",
+            patches.dim(),
+            expected.0,
+            expected.1
+        )));
+    }
+
+    let mut padded = Array::zeros(padded_shape);
+    ndarray::indices(windows)
+        .into_iter()
+        .zip(patches.rows())
+        .for_each(|(window, row)| {
+            let window = window.into_dimension();
+            ndarray::indices(window_shape)
+                .into_iter()
+                .zip(row.iter())
+                .for_each(|(tap, &p)| {
+                    let tap = tap.into_dimension();
+                    let index: [usize; N] =
+                        std::array::from_fn(|i| window[i] * cm.strides[i] + tap[i] * dilation[i]);
+                    padded[index.into_dimension()] += p;
+                });
+        });
+
+    Ok(padded.slice_move(unsafe {
+        SliceInfo::new(std::array::from_fn(|i| SliceInfoElem::Slice {
+            start: cm.padding[i][0] as isize,
+            end: Some((cm.padding[i][0] + output_shape[i]) as isize),
+            step: 1,
+        }))
+        .unwrap()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array, Array2};
+
+    use super::*;
+    use crate::{conv_output_shape, dilation::WithDilation, reference::assert_close, ConvExt};
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((6, 7), |(i, j)| ((i * 7 + j) % 9) as f64 - 4.);
+        let kernel = array![[1., 0., -2.], [3., 1., 0.5]];
+
+        for (dilation, conv_mode) in [
+            ([1, 1], ConvMode::Full),
+            ([2, 1], ConvMode::Same),
+            (
+                [1, 2],
+                ConvMode::Custom {
+                    padding: [1, 2],
+                    strides: [2, 3],
+                },
+            ),
+        ] {
+            let patches = arr
+                .unfold([2, 3], dilation, conv_mode, PaddingMode::Reflect)
+                .unwrap();
+            let expected = arr
+                .conv(
+                    kernel.with_dilation(dilation),
+                    conv_mode,
+                    PaddingMode::Reflect,
+                )
+                .unwrap();
+            let shape = conv_output_shape([6, 7], [2, 3], dilation, conv_mode).unwrap();
+
+            assert_eq!(patches.dim(), (shape[0] * shape[1], 6));
+            let ret = patches
+                .dot(&Array::from_iter(kernel.iter().copied()))
+                .into_shape(shape)
+                .unwrap();
+            assert_close(&ret, &expected, 1e-9);
+        }
+
+        let arr = array![1, 2, 3, 4, 5];
+        assert_eq!(
+            arr.unfold([2], 2, ConvMode::Valid, PaddingMode::Zeros)
+                .unwrap(),
+            array![[1, 3], [2, 4], [3, 5]]
+        );
+        assert!(arr
+            .unfold([6], 1, ConvMode::Valid, PaddingMode::Zeros)
+            .is_err());
+    }
+
+    #[test]
+    fn fold_is_adjoint() {
+        let arr = Array2::from_shape_fn((5, 7), |(i, j)| ((i * 7 + j) % 6) as i32 - 2);
+
+        for (dilation, conv_mode) in [
+            ([1, 1], ConvMode::Full),
+            ([2, 1], ConvMode::Same),
+            (
+                [1, 2],
+                ConvMode::Custom {
+                    padding: [1, 0],
+                    strides: [2, 3],
+                },
+            ),
+        ] {
+            let patches = arr
+                .unfold([2, 3], dilation, conv_mode, PaddingMode::Zeros)
+                .unwrap();
+            let other = Array2::from_shape_fn(patches.dim(), |(i, j)| ((i * 3 + j) % 5) as i32);
+
+            // <unfold(x), p> == <x, fold(p)>
+            let folded = fold(other.view(), [5, 7], [2, 3], dilation, conv_mode).unwrap();
+            assert_eq!(folded.dim(), (5, 7));
+            assert_eq!((&patches * &other).sum(), (&arr * &folded).sum());
+        }
+
+        // non overlapping windows fold back to the input
+        let patches = arr
+            .unfold([1, 7], 1, ConvMode::Valid, PaddingMode::Zeros)
+            .unwrap();
+        assert_eq!(
+            fold(patches.view(), [5, 7], [1, 7], 1, ConvMode::Valid).unwrap(),
+            arr
+        );
+
+        assert!(fold(patches.view(), [5, 7], [2, 7], 1, ConvMode::Valid).is_err());
+    }
+}
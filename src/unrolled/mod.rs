@@ -0,0 +1,124 @@
+use std::fmt::Debug;
+
+use ndarray::{Array2, ArrayBase, Data, Ix2, RawData};
+use num::traits::NumAssign;
+
+use crate::{window::Windows, ConvMode, PaddingMode};
+
+pub trait UnrolledConvExt<T, S>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// `conv` with a kernel whose shape is known at compile time,
+    /// the loop over the taps is unrolled for every (H, W). zero taps are skipped
+    /// like in `conv`, so an infinite or NaN input under them doesn't spread.
+    fn conv_unrolled<const H: usize, const W: usize>(
+        &self,
+        kernel: &[[T; W]; H],
+        conv_mode: ConvMode<2>,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<Array2<T>, crate::Error<2>>;
+}
+
+impl<T, S> UnrolledConvExt<T, S> for ArrayBase<S, Ix2>
+where
+    T: NumAssign + Copy + Debug,
+    S: Data<Elem = T>,
+{
+    fn conv_unrolled<const H: usize, const W: usize>(
+        &self,
+        kernel: &[[T; W]; H],
+        conv_mode: ConvMode<2>,
+        padding_mode: PaddingMode<2, T>,
+    ) -> Result<Array2<T>, crate::Error<2>> {
+        if self.is_empty() {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+        if H * W == 0 {
+            return Err(crate::Error::KernelShape(Ix2(H, W)));
+        }
+
+        let cm = conv_mode.unfold_with_dim([H, W], [self.nrows(), self.ncols()])?;
+        let windows = Windows::new(self, [H, W], &cm, conv_mode.padding_mode(padding_mode))
+            .ok_or(crate::Error::MismatchShape(conv_mode, [H, W]))?;
+
+        let strides = windows.padded_strides();
+        let (row, col) = (strides[0], strides[1]);
+
+        Ok(windows.origins().map(|cur| {
+            let cur = cur as *const T;
+            let mut sum = T::zero();
+            for (i, taps) in kernel.iter().enumerate() {
+                for (j, &k) in taps.iter().enumerate() {
+                    if k != T::zero() {
+                        sum += unsafe { *cur.offset(i as isize * row + j as isize * col) } * k;
+                    }
+                }
+            }
+            sum
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+    use crate::ConvExt;
+
+    #[test]
+    fn same_as_conv() {
+        let arr = Array2::from_shape_fn((6, 7), |(i, j)| (i * 7 + j) as i32 % 9 - 4);
+        let kernel = [[1, 2, 0], [-1, 3, 4]];
+
+        for conv_mode in [
+            ConvMode::Same,
+            ConvMode::Full,
+            ConvMode::Valid,
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [2, 3],
+            },
+        ] {
+            let expected = arr
+                .conv(
+                    &array![[1, 2, 0], [-1, 3, 4]],
+                    conv_mode,
+                    PaddingMode::Reflect,
+                )
+                .unwrap();
+
+            assert_eq!(
+                arr.conv_unrolled(&kernel, conv_mode, PaddingMode::Reflect)
+                    .unwrap(),
+                expected
+            );
+            // nested arrays are kernels for conv too
+            assert_eq!(
+                arr.conv(&kernel, conv_mode, PaddingMode::Reflect).unwrap(),
+                expected
+            );
+            assert_eq!(
+                arr.conv(kernel, conv_mode, PaddingMode::Reflect).unwrap(),
+                expected
+            );
+        }
+
+        assert!(arr
+            .conv_unrolled(&[[1; 8]; 2], ConvMode::Valid, PaddingMode::Zeros)
+            .is_err());
+
+        // the zero taps never read the infinity
+        let mut arr = arr.mapv(f64::from);
+        arr[[2, 3]] = f64::INFINITY;
+        let kernel = [[1., 0., 2.], [0., 0., 3.]];
+        assert_eq!(
+            arr.conv_unrolled(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap(),
+            arr.conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use core::fmt::Debug;
+
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+
+use ndarray::{
+    Array, ArrayBase, ArrayView, Data, Dim, IntoDimension, Ix, RawData, RemoveAxis, ShapeBuilder,
+    SliceArg, SliceInfo, SliceInfoElem,
+};
+use num::traits::NumAssign;
+
+use crate::{
+    conv::ExplicitConv,
+    dilation::{dilated, IntoDilation},
+    padding::PaddingExt,
+    ConvMode, PaddingMode,
+};
+
+pub trait WindowExt<T, S, const N: usize>
+where
+    T: NumAssign + Copy,
+    S: RawData,
+{
+    /// call `f` on every window of the padded input, with the same window
+    /// geometry as `conv` with a kernel of shape `window_shape`.
+    fn map_windows<U>(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        f: impl FnMut(ArrayView<T, Dim<[Ix; N]>>) -> U,
+    ) -> Result<Array<U, Dim<[Ix; N]>>, crate::Error<N>>;
+}
+
+impl<T, S, const N: usize> WindowExt<T, S, N> for ArrayBase<S, Dim<[Ix; N]>>
+where
+    T: NumAssign + Copy + Debug,
+    S: Data<Elem = T>,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    fn map_windows<U>(
+        &self,
+        window_shape: [usize; N],
+        dilation: impl IntoDilation<N>,
+        conv_mode: ConvMode<N>,
+        padding_mode: PaddingMode<N, T>,
+        f: impl FnMut(ArrayView<T, Dim<[Ix; N]>>) -> U,
+    ) -> Result<Array<U, Dim<[Ix; N]>>, crate::Error<N>> {
+        if self.shape().iter().product::<usize>() == 0 {
+            return Err(crate::Error::DataShape(self.raw_dim()));
+        }
+        if window_shape.iter().product::<usize>() == 0 {
+            return Err(crate::Error::KernelShape(window_shape.into_dimension()));
+        }
+
+        let dilation = dilation.into_dilation();
+        let window_dim = dilated(window_shape, dilation)?;
+
+        let cm =
+            conv_mode.unfold_with_dim(window_dim, core::array::from_fn(|i| self.shape()[i]))?;
+        let windows = Windows::new(self, window_dim, &cm, conv_mode.padding_mode(padding_mode))
+            .ok_or(crate::Error::MismatchShape(conv_mode, window_dim))?;
+
+        Ok(windows.map(window_shape, dilation, f))
+    }
+}
+
+// the padded input and the position of every window in it.
+// shared by conv and the other sliding window operators.
+pub struct Windows<T, const N: usize> {
+    padded: Array<T, Dim<[Ix; N]>>,
+    output_shape: [usize; N],
+    // element strides between neighbouring windows, any sign
+    strides: [isize; N],
+}
+
+impl<T, const N: usize> Windows<T, N>
+where
+    T: NumAssign + Copy,
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+    SliceInfo<[SliceInfoElem; N], Dim<[Ix; N]>, Dim<[Ix; N]>>:
+        SliceArg<Dim<[Ix; N]>, OutDim = Dim<[Ix; N]>>,
+{
+    // window_dim is the window size with dilation.
+    // returns None if the window is larger than the padded input.
+    pub(crate) fn new<S>(
+        input: &ArrayBase<S, Dim<[Ix; N]>>,
+        window_dim: [usize; N],
+        cm: &ExplicitConv<N>,
+        padding_mode: PaddingMode<N, T>,
+    ) -> Option<Self>
+    where
+        S: Data<Elem = T>,
+    {
+        // the padded copy is a new standard layout array, so inputs of any
+        // strides, negative ones included, are gathered here
+        let padded = input.padding(padding_mode, cm.padding);
+
+        let padded_raw_dim = padded.raw_dim();
+        if !(0..N).all(|i| window_dim[i] <= padded_raw_dim[i]) {
+            return None;
+        }
+
+        let output_shape =
+            core::array::from_fn(|i| (padded_raw_dim[i] - window_dim[i]) / cm.strides[i] + 1);
+        let strides = core::array::from_fn(|i| cm.strides[i] as isize * padded.strides()[i]);
+
+        Some(Self {
+            padded,
+            output_shape,
+            strides,
+        })
+    }
+}
+
+impl<T, const N: usize> Windows<T, N>
+where
+    Dim<[Ix; N]>: RemoveAxis,
+    [Ix; N]: IntoDimension<Dim = Dim<[Ix; N]>>,
+{
+    pub(crate) fn output_shape(&self) -> [usize; N] {
+        self.output_shape
+    }
+
+    pub(crate) fn padded_strides(&self) -> &[isize] {
+        self.padded.strides()
+    }
+
+    // offsets of every tap of a window relative to its first element, in row major order
+    #[cfg(feature = "std")]
+    pub(crate) fn offsets(&self, window_shape: [usize; N], dilation: [usize; N]) -> Vec<isize> {
+        let strides: [isize; N] =
+            core::array::from_fn(|i| dilation[i] as isize * self.padded.strides()[i]);
+
+        ndarray::indices(window_shape)
+            .into_iter()
+            .map(|index| {
+                let index = index.into_dimension();
+                (0..N).map(|n| index[n] as isize * strides[n]).sum()
+            })
+            .collect()
+    }
+
+    // every window as a view into the padded input, in the output's row major order
+    pub(crate) fn map<U>(
+        &self,
+        window_shape: [usize; N],
+        dilation: [usize; N],
+        mut f: impl FnMut(ArrayView<T, Dim<[Ix; N]>>) -> U,
+    ) -> Array<U, Dim<[Ix; N]>> {
+        let strides: [isize; N] =
+            core::array::from_fn(|i| dilation[i] as isize * self.padded.strides()[i]);
+
+        self.origins().map(|cur| {
+            // the window lies inside the padded input, which outlives the view
+            f(unsafe { ArrayView::from_shape_ptr(window_shape.strides(as_dim(strides)), cur) })
+        })
+    }
+
+    // the first element of every window, in the output's row major order
+    pub(crate) fn origins(&self) -> ArrayView<'_, T, Dim<[Ix; N]>> {
+        // built from the pointer so the layout of the padded input doesn't matter,
+        // every origin lies inside it as the windows fit
+        unsafe {
+            ArrayView::from_shape_ptr(
+                self.output_shape.strides(as_dim(self.strides)),
+                self.padded.as_ptr(),
+            )
+        }
+    }
+}
+
+// ndarray takes negative strides cast to usize
+fn as_dim<const N: usize>(strides: [isize; N]) -> [usize; N] {
+    strides.map(|s| s as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, s, Array2};
+
+    use super::*;
+    use crate::{dilation::WithDilation, ConvExt};
+
+    #[test]
+    fn map_windows() {
+        let arr = Array2::from_shape_fn((7, 9), |(i, j)| (i * 9 + j) as i32);
+        let kernel = Array2::<i32>::ones((2, 3));
+
+        for conv_mode in [
+            ConvMode::Same,
+            ConvMode::Full,
+            ConvMode::Custom {
+                padding: [1, 2],
+                strides: [2, 3],
+            },
+        ] {
+            let res = arr
+                .map_windows([2, 3], [2, 1], conv_mode, PaddingMode::Circular, |w| {
+                    w.sum()
+                })
+                .unwrap();
+            let expect = arr
+                .conv(
+                    kernel.with_dilation([2, 1]),
+                    conv_mode,
+                    PaddingMode::Circular,
+                )
+                .unwrap();
+            assert_eq!(res, expect);
+        }
+
+        let arr = array![1, 2, 3, 4, 5];
+        let res = arr
+            .map_windows([2], 2, ConvMode::Valid, PaddingMode::Zeros, |w| w.to_vec())
+            .unwrap();
+        assert_eq!(res, array![vec![1, 3], vec![2, 4], vec![3, 5]]);
+
+        // reversed views are windowed in their logical order
+        let res = arr
+            .slice(s![..;-1])
+            .map_windows([2], 2, ConvMode::Valid, PaddingMode::Zeros, |w| w.to_vec())
+            .unwrap();
+        assert_eq!(res, array![vec![5, 3], vec![4, 2], vec![3, 1]]);
+
+        assert!(arr
+            .map_windows([6], 1, ConvMode::Valid, PaddingMode::Zeros, |w| w[0])
+            .is_err());
+    }
+}